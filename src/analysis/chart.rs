@@ -0,0 +1,755 @@
+// src/analysis/chart.rs
+
+use crate::analysis::ops::TimeUnit;
+use crate::analysis::table::Table;
+use crate::analysis::timeseries::parse_timestamp;
+use crate::errors::ServerError;
+use anyhow::Result;
+use chrono::DateTime;
+use resvg::{tiny_skia, usvg};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::Write;
+use std::sync::{Arc, OnceLock};
+
+/// Most points a line or scatter chart may draw.
+const MAX_POINTS: usize = 10_000;
+
+/// Most categories a bar chart may draw.
+const MAX_BARS: usize = 200;
+
+/// Most bins a histogram may have.
+const MAX_BINS: usize = 200;
+
+/// Series colours, in order.
+const PALETTE: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+];
+
+const FONT_FAMILY: &str = "DejaVu Sans, Liberation Sans, Arial, Helvetica, sans-serif";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartKind {
+    Line,
+    Bar,
+    Scatter,
+    Histogram,
+}
+
+impl ChartKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartKind::Line => "line",
+            ChartKind::Bar => "bar",
+            ChartKind::Scatter => "scatter",
+            ChartKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// One column name or several.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Columns {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// What to draw from a table and how.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ChartSpec {
+    #[serde(rename = "chart")]
+    pub kind: ChartKind,
+    /// Column along the horizontal axis; the row number if omitted. For histograms,
+    /// the column whose values are binned.
+    #[serde(default)]
+    pub x: Option<String>,
+    /// Columns drawn as series; every other numeric column if omitted.
+    #[serde(default)]
+    pub y: Option<Columns>,
+    #[serde(default)]
+    pub bins: Option<usize>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub x_label: Option<String>,
+    #[serde(default)]
+    pub y_label: Option<String>,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+}
+
+fn default_width() -> u32 {
+    800
+}
+
+fn default_height() -> u32 {
+    500
+}
+
+/// Draws `table` as an SVG document.
+pub fn render_svg(table: &Table, spec: &ChartSpec) -> Result<String> {
+    if table.rows.is_empty() {
+        return Err(ServerError::invalid("render_chart", "There are no rows to draw").into());
+    }
+    match spec.kind {
+        ChartKind::Line | ChartKind::Scatter => xy_chart(table, spec),
+        ChartKind::Bar => bar_chart(table, spec),
+        ChartKind::Histogram => histogram(table, spec),
+    }
+}
+
+/// Rasterizes an SVG document to PNG. Text is drawn with the system's fonts.
+pub fn svg_to_png(svg: &str) -> Result<Vec<u8>> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    let fontdb = FONTS
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+            Arc::new(fonts)
+        })
+        .clone();
+    let options = usvg::Options {
+        fontdb,
+        ..Default::default()
+    };
+    let failed = |e: String| ServerError::ToolError(format!("Could not draw the chart: {}", e));
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| failed(e.to_string()))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| failed("empty image".to_string()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png().map_err(|e| failed(e.to_string()))?)
+}
+
+/// The series columns: `y`, or every numeric column other than `x`.
+fn series_columns(table: &Table, spec: &ChartSpec) -> Result<Vec<(String, usize)>> {
+    let names = match &spec.y {
+        Some(Columns::One(name)) => vec![name.clone()],
+        Some(Columns::Many(names)) => names.clone(),
+        None => (0..table.columns.len())
+            .filter(|&i| table.is_numeric(i) && Some(&table.columns[i]) != spec.x.as_ref())
+            .map(|i| table.columns[i].clone())
+            .collect(),
+    };
+    if names.is_empty() {
+        return Err(ServerError::invalid(
+            "render_chart",
+            "There is no numeric column to draw; name one in 'y'",
+        )
+        .into());
+    }
+    names
+        .into_iter()
+        .map(|name| {
+            let index = column(table, &name)?;
+            if !table.is_numeric(index) {
+                return Err(ServerError::invalid(
+                    "render_chart",
+                    format!("Column '{}' does not hold numbers", name),
+                )
+                .into());
+            }
+            Ok((name, index))
+        })
+        .collect()
+}
+
+fn column(table: &Table, name: &str) -> Result<usize> {
+    table.column_index(name).ok_or_else(|| {
+        ServerError::invalid(
+            "render_chart",
+            format!(
+                "Unknown column '{}'; available columns: {}",
+                name,
+                table.columns.join(", ")
+            ),
+        )
+        .into()
+    })
+}
+
+/// How values along the horizontal axis are placed.
+enum XScale {
+    /// Numbers, or timestamps in milliseconds when `time` is set.
+    Linear { time: bool },
+    /// Evenly spaced labels, in order of first appearance.
+    Band(Vec<String>),
+}
+
+/// Position of each row along the horizontal axis, or `None` where it has no value.
+fn x_positions(table: &Table, x: Option<&str>) -> Result<(Vec<Option<f64>>, XScale)> {
+    let Some(x) = x else {
+        let positions = (1..=table.rows.len()).map(|i| Some(i as f64)).collect();
+        return Ok((positions, XScale::Linear { time: false }));
+    };
+    let index = column(table, x)?;
+    if table.is_numeric(index) {
+        let positions = table.rows.iter().map(|row| row[index].as_f64()).collect();
+        return Ok((positions, XScale::Linear { time: false }));
+    }
+    let times: Option<Vec<Option<f64>>> = table
+        .rows
+        .iter()
+        .map(|row| match &row[index] {
+            Value::Null => Some(None),
+            value => parse_timestamp(value, None, TimeUnit::Seconds)
+                .map(|t| Some(t.timestamp_millis() as f64)),
+        })
+        .collect();
+    if let Some(times) = times {
+        return Ok((times, XScale::Linear { time: true }));
+    }
+    let mut labels: Vec<String> = Vec::new();
+    let positions = table
+        .rows
+        .iter()
+        .map(|row| {
+            if row[index].is_null() {
+                return None;
+            }
+            let label = label(&row[index]);
+            let position = match labels.iter().position(|l| *l == label) {
+                Some(position) => position,
+                None => {
+                    labels.push(label);
+                    labels.len() - 1
+                }
+            };
+            Some(position as f64)
+        })
+        .collect();
+    Ok((positions, XScale::Band(labels)))
+}
+
+fn xy_chart(table: &Table, spec: &ChartSpec) -> Result<String> {
+    let series = series_columns(table, spec)?;
+    let (positions, scale) = x_positions(table, spec.x.as_deref())?;
+    if spec.kind == ChartKind::Scatter && matches!(scale, XScale::Band(_)) {
+        return Err(ServerError::invalid(
+            "render_chart",
+            "A scatter chart needs a numeric or timestamp 'x' column",
+        )
+        .into());
+    }
+
+    let mut lines: Vec<(String, Vec<(f64, f64)>)> = Vec::new();
+    for (name, index) in &series {
+        let mut points: Vec<(f64, f64)> = positions
+            .iter()
+            .zip(&table.rows)
+            .filter_map(|(x, row)| Some(((*x)?, row[*index].as_f64()?)))
+            .collect();
+        if spec.kind == ChartKind::Line && matches!(scale, XScale::Linear { .. }) {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        lines.push((name.clone(), points));
+    }
+    let count: usize = lines.iter().map(|(_, points)| points.len()).sum();
+    if count == 0 {
+        return Err(ServerError::invalid("render_chart", "There are no points to draw").into());
+    }
+    if count > MAX_POINTS {
+        return Err(ServerError::invalid(
+            "render_chart",
+            format!(
+                "The chart would have {} points, more than the limit of {}; aggregate the data first, e.g. with 'resample' or 'group_by' in 'ops'",
+                count, MAX_POINTS
+            ),
+        )
+        .into());
+    }
+
+    let all = || lines.iter().flat_map(|(_, points)| points.iter());
+    let y_ticks = nice_ticks(
+        all().map(|p| p.1).fold(f64::INFINITY, f64::min),
+        all().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max),
+    );
+    let names: Vec<String> = lines.iter().map(|(name, _)| name.clone()).collect();
+    let mut canvas = Canvas::new(spec, &names);
+    canvas.y_axis(&y_ticks, y_label(spec, &names).as_deref());
+
+    // Categories are centred in equal bands; numbers and times span the axis.
+    let band = match &scale {
+        XScale::Band(labels) => canvas.plot_width() / labels.len() as f64,
+        XScale::Linear { .. } => 0.0,
+    };
+    let x_range = match &scale {
+        XScale::Band(labels) => {
+            let ticks: Vec<(f64, String)> = labels
+                .iter()
+                .enumerate()
+                .map(|(i, l)| (canvas.left + band * (i as f64 + 0.5), l.clone()))
+                .collect();
+            canvas.x_axis(&ticks, spec.x.as_deref());
+            None
+        }
+        XScale::Linear { time } => {
+            let min = all().map(|p| p.0).fold(f64::INFINITY, f64::min);
+            let max = all().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+            let (min, max, ticks) = if *time {
+                let (min, max) = if min == max {
+                    (min - 3_600_000.0, max + 3_600_000.0)
+                } else {
+                    (min, max)
+                };
+                (min, max, time_ticks(min, max))
+            } else {
+                let ticks = nice_ticks(min, max);
+                let labels = number_labels(&ticks);
+                let (first, last) = (ticks[0], ticks[ticks.len() - 1]);
+                (first, last, ticks.into_iter().zip(labels).collect())
+            };
+            let ticks: Vec<(f64, String)> = ticks
+                .into_iter()
+                .map(|(x, label)| (canvas.x_linear(x, min, max), label))
+                .collect();
+            canvas.x_axis(&ticks, Some(spec.x.as_deref().unwrap_or("row")));
+            Some((min, max))
+        }
+    };
+    let to_px = |x: f64| match x_range {
+        Some((min, max)) => canvas.x_linear(x, min, max),
+        None => canvas.left + band * (x + 0.5),
+    };
+
+    let (lo, hi) = (y_ticks[0], y_ticks[y_ticks.len() - 1]);
+    let mut body = String::new();
+    for (i, (_, points)) in lines.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let pixels: Vec<(f64, f64)> = points
+            .iter()
+            .map(|&(x, y)| (to_px(x), canvas.y_linear(y, lo, hi)))
+            .collect();
+        if spec.kind == ChartKind::Line {
+            let path: Vec<String> = pixels
+                .iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                .collect();
+            let _ = writeln!(
+                body,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2" stroke-linejoin="round"/>"#,
+                path.join(" "),
+                color
+            );
+        }
+        if spec.kind == ChartKind::Scatter || pixels.len() <= 60 {
+            let radius = if spec.kind == ChartKind::Scatter {
+                3.5
+            } else {
+                2.5
+            };
+            for (x, y) in &pixels {
+                let _ = writeln!(
+                    body,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}" fill-opacity="0.8"/>"#,
+                    x, y, radius, color
+                );
+            }
+        }
+    }
+    canvas.svg.push_str(&body);
+    Ok(canvas.finish())
+}
+
+fn bar_chart(table: &Table, spec: &ChartSpec) -> Result<String> {
+    let series = series_columns(table, spec)?;
+    if table.rows.len() > MAX_BARS {
+        return Err(ServerError::invalid(
+            "render_chart",
+            format!(
+                "A bar chart can show at most {} categories, but the data has {} rows; aggregate it first, e.g. with 'group_by' in 'ops'",
+                MAX_BARS,
+                table.rows.len()
+            ),
+        )
+        .into());
+    }
+    let labels: Vec<String> = match &spec.x {
+        Some(x) => {
+            let index = column(table, x)?;
+            table.rows.iter().map(|row| label(&row[index])).collect()
+        }
+        None => (1..=table.rows.len()).map(|i| i.to_string()).collect(),
+    };
+
+    let values = || {
+        series
+            .iter()
+            .flat_map(|(_, index)| table.rows.iter().filter_map(|row| row[*index].as_f64()))
+    };
+    let y_ticks = nice_ticks(values().fold(0.0, f64::min), values().fold(0.0, f64::max));
+    let names: Vec<String> = series.iter().map(|(name, _)| name.clone()).collect();
+    let mut canvas = Canvas::new(spec, &names);
+    canvas.y_axis(&y_ticks, y_label(spec, &names).as_deref());
+    let band = canvas.plot_width() / labels.len() as f64;
+    let ticks: Vec<(f64, String)> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (canvas.left + band * (i as f64 + 0.5), l.clone()))
+        .collect();
+    canvas.x_axis(&ticks, spec.x.as_deref());
+
+    let (lo, hi) = (y_ticks[0], y_ticks[y_ticks.len() - 1]);
+    let baseline = canvas.y_linear(0.0, lo, hi);
+    let bar = band * 0.8 / series.len() as f64;
+    let mut body = String::new();
+    for (i, row) in table.rows.iter().enumerate() {
+        for (s, (_, index)) in series.iter().enumerate() {
+            let Some(value) = row[*index].as_f64() else {
+                continue;
+            };
+            let y = canvas.y_linear(value, lo, hi);
+            let _ = writeln!(
+                body,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                canvas.left + band * (i as f64 + 0.1) + bar * s as f64,
+                y.min(baseline),
+                bar,
+                (y - baseline).abs(),
+                PALETTE[s % PALETTE.len()]
+            );
+        }
+    }
+    canvas.svg.push_str(&body);
+    Ok(canvas.finish())
+}
+
+fn histogram(table: &Table, spec: &ChartSpec) -> Result<String> {
+    let name = match (&spec.x, &spec.y) {
+        (Some(x), _) => x.clone(),
+        (None, Some(Columns::One(y))) => y.clone(),
+        _ => series_columns(table, spec)?
+            .into_iter()
+            .next()
+            .map(|(name, _)| name)
+            .unwrap_or_default(),
+    };
+    let index = column(table, &name)?;
+    let numbers = table.numbers(index);
+    if numbers.is_empty() {
+        return Err(ServerError::invalid(
+            "render_chart",
+            format!("Column '{}' holds no numbers", name),
+        )
+        .into());
+    }
+    let bins = match spec.bins {
+        Some(bins) if (1..=MAX_BINS).contains(&bins) => bins,
+        Some(_) => {
+            return Err(ServerError::invalid(
+                "render_chart",
+                format!("'bins' must be between 1 and {}", MAX_BINS),
+            )
+            .into())
+        }
+        // Sturges' rule
+        None => ((numbers.len() as f64).log2().ceil() as usize + 1).min(MAX_BINS),
+    };
+    let min = numbers.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let (min, max) = if min == max {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    };
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0usize; bins];
+    for x in &numbers {
+        counts[(((x - min) / width) as usize).min(bins - 1)] += 1;
+    }
+
+    let y_ticks = nice_ticks(0.0, counts.iter().copied().max().unwrap_or(0) as f64);
+    let mut canvas = Canvas::new(spec, &[]);
+    canvas.y_axis(&y_ticks, Some(spec.y_label.as_deref().unwrap_or("count")));
+    let x_ticks = nice_ticks(min, max);
+    let labels = number_labels(&x_ticks);
+    let ticks: Vec<(f64, String)> = x_ticks
+        .into_iter()
+        .zip(labels)
+        .filter(|(x, _)| (min..=max).contains(x))
+        .map(|(x, label)| (canvas.x_linear(x, min, max), label))
+        .collect();
+    canvas.x_axis(&ticks, Some(&name));
+
+    let (lo, hi) = (y_ticks[0], y_ticks[y_ticks.len() - 1]);
+    let baseline = canvas.y_linear(0.0, lo, hi);
+    let mut body = String::new();
+    for (i, count) in counts.iter().enumerate() {
+        let left = canvas.x_linear(min + width * i as f64, min, max);
+        let right = canvas.x_linear(min + width * (i + 1) as f64, min, max);
+        let top = canvas.y_linear(*count as f64, lo, hi);
+        let _ = writeln!(
+            body,
+            r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" stroke="#ffffff"/>"##,
+            left,
+            top,
+            right - left,
+            baseline - top,
+            PALETTE[0]
+        );
+    }
+    canvas.svg.push_str(&body);
+    Ok(canvas.finish())
+}
+
+/// The vertical axis title: `y_label`, or the name of a single series.
+fn y_label(spec: &ChartSpec, names: &[String]) -> Option<String> {
+    match (&spec.y_label, names) {
+        (Some(label), _) => Some(label.clone()),
+        (None, [only]) => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// An SVG document being drawn, with the plot area inside its margins.
+struct Canvas {
+    svg: String,
+    height: f64,
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+    x_label: Option<String>,
+    legend: Vec<String>,
+}
+
+impl Canvas {
+    /// Starts a document with a background, the title, and a legend if there are
+    /// several series.
+    fn new(spec: &ChartSpec, series: &[String]) -> Self {
+        let (width, height) = (spec.width as f64, spec.height as f64);
+        let legend = if series.len() > 1 {
+            series.to_vec()
+        } else {
+            Vec::new()
+        };
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="{font}" font-size="12">"#,
+            w = spec.width,
+            h = spec.height,
+            font = FONT_FAMILY
+        );
+        let _ = writeln!(
+            svg,
+            r##"<rect width="100%" height="100%" fill="#ffffff"/>"##
+        );
+        if let Some(title) = &spec.title {
+            let _ = writeln!(
+                svg,
+                r##"<text x="{:.1}" y="28" text-anchor="middle" font-size="16" font-weight="bold" fill="#222222">{}</text>"##,
+                width / 2.0,
+                escape(title)
+            );
+        }
+        Canvas {
+            svg,
+            height,
+            left: 72.0,
+            right: width - if legend.is_empty() { 24.0 } else { 150.0 },
+            top: if spec.title.is_some() { 48.0 } else { 20.0 },
+            bottom: height - 56.0,
+            x_label: spec.x_label.clone(),
+            legend,
+        }
+    }
+
+    fn plot_width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    fn x_linear(&self, x: f64, min: f64, max: f64) -> f64 {
+        self.left + (x - min) / (max - min) * self.plot_width()
+    }
+
+    fn y_linear(&self, y: f64, lo: f64, hi: f64) -> f64 {
+        self.bottom - (y - lo) / (hi - lo) * (self.bottom - self.top)
+    }
+
+    /// Draws horizontal grid lines at `ticks`, which span the vertical axis.
+    fn y_axis(&mut self, ticks: &[f64], label: Option<&str>) {
+        let (lo, hi) = (ticks[0], ticks[ticks.len() - 1]);
+        for (tick, text) in ticks.iter().zip(number_labels(ticks)) {
+            let y = self.y_linear(*tick, lo, hi);
+            let _ = writeln!(
+                self.svg,
+                r##"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="#e0e0e0"/>"##,
+                self.left, self.right
+            );
+            let _ = writeln!(
+                self.svg,
+                r##"<text x="{:.1}" y="{:.1}" text-anchor="end" fill="#444444">{}</text>"##,
+                self.left - 8.0,
+                y + 4.0,
+                escape(&text)
+            );
+        }
+        if let Some(label) = label {
+            let (x, y) = (16.0, (self.top + self.bottom) / 2.0);
+            let _ = writeln!(
+                self.svg,
+                r##"<text x="{x}" y="{y:.1}" transform="rotate(-90 {x} {y:.1})" text-anchor="middle" fill="#222222">{}</text>"##,
+                escape(label)
+            );
+        }
+    }
+
+    /// Draws the horizontal axis with labels at pixel positions, skipping labels
+    /// where they would overlap.
+    fn x_axis(&mut self, ticks: &[(f64, String)], default_label: Option<&str>) {
+        let _ = writeln!(
+            self.svg,
+            r##"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="#444444"/>"##,
+            self.left,
+            self.right,
+            y = self.bottom
+        );
+        let longest = ticks
+            .iter()
+            .map(|(_, l)| l.chars().count())
+            .max()
+            .unwrap_or(1);
+        let room = (self.plot_width() / (longest.min(16) as f64 * 7.0 + 8.0)).max(1.0);
+        let step = (ticks.len() as f64 / room).ceil().max(1.0) as usize;
+        for (x, text) in ticks.iter().step_by(step) {
+            let _ = writeln!(
+                self.svg,
+                r##"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{:.1}" stroke="#444444"/>"##,
+                self.bottom,
+                self.bottom + 5.0
+            );
+            let _ = writeln!(
+                self.svg,
+                r##"<text x="{x:.1}" y="{:.1}" text-anchor="middle" fill="#444444">{}</text>"##,
+                self.bottom + 19.0,
+                escape(&truncate(text, 16))
+            );
+        }
+        if let Some(label) = self.x_label.as_deref().or(default_label) {
+            let _ = writeln!(
+                self.svg,
+                r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#222222">{}</text>"##,
+                (self.left + self.right) / 2.0,
+                self.height - 12.0,
+                escape(label)
+            );
+        }
+    }
+
+    /// Draws the legend, if any, and closes the document.
+    fn finish(mut self) -> String {
+        for (i, name) in self.legend.iter().enumerate() {
+            let y = self.top + 8.0 + 20.0 * i as f64;
+            let _ = writeln!(
+                self.svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" fill="{}"/>"#,
+                self.right + 16.0,
+                y - 6.0,
+                PALETTE[i % PALETTE.len()]
+            );
+            let _ = writeln!(
+                self.svg,
+                r##"<text x="{:.1}" y="{:.1}" fill="#222222">{}</text>"##,
+                self.right + 34.0,
+                y + 4.0,
+                escape(&truncate(name, 16))
+            );
+        }
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+}
+
+/// About five evenly spaced round numbers covering `min..=max`.
+fn nice_ticks(min: f64, max: f64) -> Vec<f64> {
+    let (min, max) = if min == max {
+        (min - 1.0, max + 1.0)
+    } else {
+        (min, max)
+    };
+    let raw = (max - min) / 5.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).floor() as i64;
+    let last = (max / step).ceil() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Tick labels with as many decimals as the spacing of `ticks` needs.
+fn number_labels(ticks: &[f64]) -> Vec<String> {
+    let step = match ticks {
+        [a, b, ..] => (b - a).abs(),
+        _ => 1.0,
+    };
+    let decimals = (0..10)
+        .find(|&d| {
+            let scaled = step * 10f64.powi(d);
+            (scaled - scaled.round()).abs() < 1e-6 * scaled.max(1.0)
+        })
+        .unwrap_or(10) as usize;
+    ticks
+        .iter()
+        .map(|t| {
+            format!(
+                "{:.*}",
+                decimals,
+                if t.abs() < step * 1e-9 { 0.0 } else { *t }
+            )
+        })
+        .collect()
+}
+
+/// Five evenly spaced timestamps from `min` to `max` milliseconds, labelled with as
+/// much of the date and time as the span needs.
+fn time_ticks(min: f64, max: f64) -> Vec<(f64, String)> {
+    let span = max - min;
+    let format = if span >= 2.0 * 86_400_000.0 {
+        "%Y-%m-%d"
+    } else if span >= 120_000.0 {
+        "%m-%d %H:%M"
+    } else {
+        "%H:%M:%S"
+    };
+    (0..5)
+        .map(|i| {
+            let t = min + span * i as f64 / 4.0;
+            let label = DateTime::from_timestamp_millis(t as i64)
+                .map(|t| t.format(format).to_string())
+                .unwrap_or_default();
+            (t, label)
+        })
+        .collect()
+}
+
+fn label(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max - 1).collect();
+    short.push('…');
+    short
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,585 @@
+// src/analysis/columnar.rs
+
+use crate::analysis::ops::{AggregateFn, Comparison, Op};
+use crate::analysis::table::{ColumnType, Table};
+use crate::errors::ServerError;
+use anyhow::Result;
+use arrow::array::{
+    Array, ArrayRef, AsArray, BooleanArray, Float64Array, RecordBatch, RecordBatchReader,
+    StringArray,
+};
+use arrow::compute::kernels::{boolean, cmp};
+use arrow::compute::{self, filter_record_batch};
+use arrow::datatypes::{DataType, Float64Type, Int64Type, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::reader::ChunkReader;
+use parquet::file::statistics::Statistics;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::io::{Read, Seek};
+use std::sync::Arc;
+
+/// A dataset held as Arrow record batches, as read from Parquet or Arrow IPC.
+#[derive(Debug, Clone)]
+pub struct ArrowData {
+    pub schema: SchemaRef,
+    pub batches: Vec<RecordBatch>,
+    /// What the reader skipped, reported alongside local results.
+    pub scan: Value,
+}
+
+/// Which columns and rows a pipeline needs, used to read less of a file.
+#[derive(Debug, Default, Clone)]
+pub struct ScanPlan {
+    /// Columns to read; all of them if `None`.
+    columns: Option<BTreeSet<String>>,
+    /// Numeric filters that hold for every row the pipeline keeps.
+    predicates: Vec<(String, Comparison, f64)>,
+}
+
+impl ScanPlan {
+    /// Derives the scan from a pipeline; reads everything unless `ops` are local ops.
+    pub fn from_ops(ops: Option<&Value>) -> Self {
+        match ops.and_then(Op::parse_all) {
+            Some(ops) => Self {
+                columns: required_columns(&ops),
+                predicates: leading_predicates(&ops),
+            },
+            None => Self::default(),
+        }
+    }
+
+    fn keeps_column(&self, name: &str) -> bool {
+        self.columns.as_ref().is_none_or(|c| c.contains(name))
+    }
+
+    /// Whether the statistics of a row group allow a row to pass every predicate.
+    fn may_match(&self, row_group: &RowGroupMetaData) -> bool {
+        self.predicates.iter().all(|(column, cmp, value)| {
+            let range = row_group
+                .columns()
+                .iter()
+                .find(|c| c.column_path().string() == *column)
+                .and_then(|c| c.statistics())
+                .and_then(numeric_range);
+            let Some((min, max)) = range else {
+                return true;
+            };
+            match cmp {
+                Comparison::Eq => min <= *value && *value <= max,
+                Comparison::Gt => max > *value,
+                Comparison::Gte => max >= *value,
+                Comparison::Lt => min < *value,
+                Comparison::Lte => min <= *value,
+                _ => true,
+            }
+        })
+    }
+}
+
+/// Columns a pipeline reads, or `None` if it needs all of them: when it returns the
+/// rows unprojected, describes every column, or picks a statistic's column itself.
+fn required_columns(ops: &[Op]) -> Option<BTreeSet<String>> {
+    let mut columns = BTreeSet::new();
+    let mut returns_rows = false;
+    for op in ops {
+        match op {
+            Op::Describe { columns: None } => return None,
+            Op::Describe {
+                columns: Some(names),
+            } => columns.extend(names.iter().cloned()),
+            Op::Count { column } => columns.extend(column.iter().cloned()),
+            Op::Sum { column }
+            | Op::Mean { column }
+            | Op::Median { column }
+            | Op::Stddev { column }
+            | Op::Min { column }
+            | Op::Max { column } => {
+                columns.insert(column.clone()?);
+            }
+            Op::Filter { column, .. } => {
+                columns.insert(column.clone());
+                returns_rows = true;
+            }
+            Op::Sort { by, .. } => {
+                columns.extend(by.iter().cloned());
+                returns_rows = true;
+            }
+            Op::Limit { .. } => returns_rows = true,
+            // Later steps only see the columns these produce.
+            Op::Select { columns: names } => {
+                columns.extend(names.iter().cloned());
+                return Some(columns);
+            }
+            Op::GroupBy { by, aggregations } => {
+                columns.extend(by.iter().cloned());
+                columns.extend(aggregations.iter().filter_map(|a| a.column.clone()));
+                return Some(columns);
+            }
+            Op::Aggregate { aggregations } => {
+                columns.extend(aggregations.iter().filter_map(|a| a.column.clone()));
+                return Some(columns);
+            }
+            Op::Correlation { columns: None, .. } => return None,
+            Op::Correlation {
+                columns: Some(names),
+                ..
+            } => columns.extend(names.iter().cloned()),
+            Op::TTest {
+                column,
+                by,
+                paired_with,
+                ..
+            } => {
+                columns.insert(column.clone());
+                columns.extend(by.iter().chain(paired_with).cloned());
+            }
+            Op::ChiSquare { column, by } => {
+                columns.insert(column.clone());
+                columns.extend(by.iter().cloned());
+            }
+            Op::Regression { y, x } => {
+                columns.insert(y.clone());
+                columns.extend(x.iter().cloned());
+            }
+            Op::Resample {
+                column,
+                aggregations,
+                ..
+            } => {
+                columns.insert(column.clone());
+                columns.extend(aggregations.iter().filter_map(|a| a.column.clone()));
+                return Some(columns);
+            }
+            Op::Anomalies { column, .. } => {
+                columns.insert(column.clone());
+            }
+            // These write a column, which later steps may read like any other; names
+            // the file lacks are simply not found when projecting.
+            Op::ParseTime { column, .. }
+            | Op::Rolling { column, .. }
+            | Op::Lag { column, .. }
+            | Op::Diff { column, .. } => {
+                columns.insert(column.clone());
+                returns_rows = true;
+            }
+        }
+    }
+    (!returns_rows).then_some(columns)
+}
+
+/// Numeric filters that apply to the rows as read, i.e. that come before any step
+/// changing which rows or columns later steps see.
+fn leading_predicates(ops: &[Op]) -> Vec<(String, Comparison, f64)> {
+    let mut predicates = Vec::new();
+    for op in ops {
+        match op {
+            Op::Filter { column, cmp, value } => {
+                if let Some(value) = value.as_f64() {
+                    predicates.push((column.clone(), *cmp, value));
+                }
+            }
+            Op::Limit { .. }
+            | Op::GroupBy { .. }
+            | Op::Aggregate { .. }
+            | Op::Resample { .. }
+            | Op::ParseTime { .. }
+            | Op::Rolling { .. }
+            | Op::Lag { .. }
+            | Op::Diff { .. } => break,
+            _ => {}
+        }
+    }
+    predicates
+}
+
+fn numeric_range(statistics: &Statistics) -> Option<(f64, f64)> {
+    match statistics {
+        Statistics::Int32(s) => Some((*s.min_opt()? as f64, *s.max_opt()? as f64)),
+        Statistics::Int64(s) => Some((*s.min_opt()? as f64, *s.max_opt()? as f64)),
+        Statistics::Float(s) => Some((*s.min_opt()? as f64, *s.max_opt()? as f64)),
+        Statistics::Double(s) => Some((*s.min_opt()?, *s.max_opt()?)),
+        _ => None,
+    }
+}
+
+/// Reads a Parquet file, skipping the columns and row groups `plan` rules out.
+///
+/// Given a file, only the footer and the column chunks that are kept are read from it.
+pub fn read_parquet(input: impl ChunkReader + 'static, plan: &ScanPlan) -> Result<ArrowData> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(input).map_err(|e| {
+        ServerError::invalid("data_analysis", format!("Invalid Parquet file: {}", e))
+    })?;
+
+    let fields = builder.schema().fields().clone();
+    let projection: Vec<usize> = (0..fields.len())
+        .filter(|&i| plan.keeps_column(fields[i].name()))
+        .collect();
+    let row_groups = builder.metadata().row_groups();
+    let kept: Vec<usize> = (0..row_groups.len())
+        .filter(|&i| plan.may_match(&row_groups[i]))
+        .collect();
+    let scan = json!({
+        "columns": projection.iter().map(|&i| fields[i].name()).collect::<Vec<_>>(),
+        "row_groups_read": kept.len(),
+        "row_groups_total": row_groups.len(),
+    });
+
+    let mask = ProjectionMask::roots(builder.parquet_schema(), projection);
+    let reader = builder
+        .with_projection(mask)
+        .with_row_groups(kept)
+        .build()?;
+    let schema = reader.schema();
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    Ok(ArrowData {
+        schema,
+        batches,
+        scan,
+    })
+}
+
+/// Reads an Arrow IPC file or stream, skipping the columns `plan` rules out.
+///
+/// Given a file in the IPC file format, only the record batches' blocks are read,
+/// and only the projected columns are decoded.
+pub fn read_ipc<R: Read + Seek>(mut input: R, plan: &ScanPlan) -> Result<ArrowData> {
+    let invalid = |e: ArrowError| {
+        ServerError::invalid("data_analysis", format!("Invalid Arrow IPC data: {}", e))
+    };
+    let mut magic = Vec::new();
+    (&mut input).take(6).read_to_end(&mut magic)?;
+    input.rewind()?;
+    let is_file = magic == b"ARROW1";
+    let schema = if is_file {
+        FileReader::try_new(&mut input, None).map(|r| r.schema())
+    } else {
+        StreamReader::try_new(&mut input, None).map(|r| r.schema())
+    }
+    .map_err(invalid)?;
+    input.rewind()?;
+
+    let projection: Vec<usize> = (0..schema.fields().len())
+        .filter(|&i| plan.keeps_column(schema.field(i).name()))
+        .collect();
+    let scan = json!({
+        "columns": projection.iter().map(|&i| schema.field(i).name()).collect::<Vec<_>>(),
+    });
+
+    // The readers report the file's full schema even when projecting.
+    let projected = Arc::new(schema.project(&projection)?);
+    let batches = if is_file {
+        let reader = FileReader::try_new(input, Some(projection)).map_err(invalid)?;
+        reader.collect::<Result<Vec<_>, _>>()?
+    } else {
+        let reader = StreamReader::try_new(input, Some(projection)).map_err(invalid)?;
+        reader.collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(ArrowData {
+        schema: projected,
+        batches,
+        scan,
+    })
+}
+
+fn column_type(data_type: &DataType) -> ColumnType {
+    match data_type {
+        t if t.is_numeric() => ColumnType::Number,
+        DataType::Boolean => ColumnType::Bool,
+        DataType::Null => ColumnType::Empty,
+        DataType::List(_)
+        | DataType::LargeList(_)
+        | DataType::FixedSizeList(_, _)
+        | DataType::Struct(_)
+        | DataType::Map(_, _) => ColumnType::Nested,
+        _ => ColumnType::Text,
+    }
+}
+
+impl ArrowData {
+    pub fn num_rows(&self) -> usize {
+        self.batches.iter().map(RecordBatch::num_rows).sum()
+    }
+
+    /// Names and kinds of all columns, from the Arrow schema.
+    pub fn schema(&self) -> Vec<(String, ColumnType)> {
+        self.schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().clone(), column_type(f.data_type())))
+            .collect()
+    }
+
+    /// Converts the first `limit` rows (all if `None`) to a row table.
+    pub fn to_table(&self, limit: Option<usize>) -> Result<Table> {
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        let mut rows = Vec::new();
+        for batch in &self.batches {
+            if remaining == 0 {
+                break;
+            }
+            let len = batch.num_rows().min(remaining);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|array| to_values(array, len))
+                .collect::<Result<Vec<_>>>()?;
+            rows.extend((0..len).map(|i| columns.iter().map(|c| c[i].clone()).collect()));
+            remaining -= len;
+        }
+        Ok(Table {
+            columns: self
+                .schema
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect(),
+            rows,
+        })
+    }
+
+    /// Runs a pipeline of filters and statistics directly on the Arrow arrays, with
+    /// the same output as the row engine.
+    ///
+    /// Returns `None` for pipelines that reshape the table or use comparisons the
+    /// columnar path lacks; those run on the row engine instead. `ops` must have
+    /// been validated.
+    pub fn execute(&self, ops: &[Op]) -> Option<Result<Value>> {
+        let supported = ops.iter().all(|op| match op {
+            Op::Filter { cmp, value, .. } => {
+                !matches!(cmp, Comparison::Contains | Comparison::In)
+                    && (value.is_number() || value.is_string() || value.is_boolean())
+            }
+            Op::Count { .. }
+            | Op::Sum { .. }
+            | Op::Mean { .. }
+            | Op::Median { .. }
+            | Op::Stddev { .. }
+            | Op::Min { .. }
+            | Op::Max { .. } => true,
+            _ => false,
+        });
+        supported.then(|| self.run(ops))
+    }
+
+    fn run(&self, ops: &[Op]) -> Result<Value> {
+        let mut batches = self.batches.clone();
+        let mut results = Vec::new();
+        let mut filtered = false;
+        for op in ops {
+            let (func, column) = match op {
+                Op::Filter { column, cmp, value } => {
+                    batches = batches
+                        .iter()
+                        .map(|batch| filter_batch(batch, column, *cmp, value))
+                        .collect::<Result<_>>()?;
+                    filtered = true;
+                    continue;
+                }
+                Op::Count { column: None } => {
+                    let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+                    results.push(json!({ "metric": "count", "value": rows }));
+                    continue;
+                }
+                Op::Count {
+                    column: Some(column),
+                } => (AggregateFn::Count, column),
+                Op::Sum { column: Some(c) } => (AggregateFn::Sum, c),
+                Op::Mean { column: Some(c) } => (AggregateFn::Mean, c),
+                Op::Median { column: Some(c) } => (AggregateFn::Median, c),
+                Op::Stddev { column: Some(c) } => (AggregateFn::Stddev, c),
+                Op::Min { column: Some(c) } => (AggregateFn::Min, c),
+                Op::Max { column: Some(c) } => (AggregateFn::Max, c),
+                _ => {
+                    return Err(ServerError::invalid(
+                        "data_analysis",
+                        format!("Unresolved op '{}'", op.name()),
+                    )
+                    .into())
+                }
+            };
+            let arrays = batches
+                .iter()
+                .map(|batch| {
+                    batch.column_by_name(column).cloned().ok_or_else(|| {
+                        ServerError::invalid(
+                            "data_analysis",
+                            format!("Unknown column '{}'", column),
+                        )
+                        .into()
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let value = match func {
+                AggregateFn::Count => {
+                    json!(arrays
+                        .iter()
+                        .map(|a| a.len() - a.null_count())
+                        .sum::<usize>())
+                }
+                func => json!(statistic(&arrays, func)?),
+            };
+            results.push(json!({ "metric": func.name(), "column": column, "value": value }));
+        }
+
+        let mut output = json!({
+            "status": "completed",
+            "engine": "local",
+            "results": results,
+            "scan": self.scan,
+        });
+        if filtered {
+            let data = ArrowData {
+                batches,
+                ..self.clone()
+            };
+            output["rows"] = data.to_table(None)?.to_json();
+        }
+        Ok(output)
+    }
+}
+
+/// Computes a statistic over numeric arrays, using Arrow kernels where they exist.
+fn statistic(arrays: &[ArrayRef], func: AggregateFn) -> Result<Option<f64>> {
+    let floats = arrays
+        .iter()
+        .map(|a| {
+            Ok(compute::cast(a, &DataType::Float64)?
+                .as_primitive::<Float64Type>()
+                .clone())
+        })
+        .collect::<Result<Vec<Float64Array>>>()?;
+    let partials = |f: fn(&Float64Array) -> Option<f64>| floats.iter().filter_map(f);
+    Ok(match func {
+        AggregateFn::Sum | AggregateFn::Mean => {
+            let count: usize = floats.iter().map(|a| a.len() - a.null_count()).sum();
+            if count == 0 {
+                return Ok(None);
+            }
+            let sum: f64 = partials(compute::sum).sum();
+            Some(if func == AggregateFn::Sum {
+                sum
+            } else {
+                sum / count as f64
+            })
+        }
+        AggregateFn::Min => partials(compute::min).reduce(f64::min),
+        AggregateFn::Max => partials(compute::max).reduce(f64::max),
+        func => {
+            let values: Vec<f64> = floats.iter().flat_map(|a| a.iter().flatten()).collect();
+            func.apply(&values)
+        }
+    })
+}
+
+/// Keeps the rows of `batch` where `column <cmp> value`, with the row engine's semantics:
+/// ordering comparisons need like types, and `ne` keeps nulls.
+fn filter_batch(
+    batch: &RecordBatch,
+    column: &str,
+    cmp: Comparison,
+    value: &Value,
+) -> Result<RecordBatch> {
+    let array = batch.column_by_name(column).ok_or_else(|| {
+        ServerError::invalid("data_analysis", format!("Unknown column '{}'", column))
+    })?;
+    let column_kind = column_type(array.data_type());
+
+    let mask = match (column_kind, value) {
+        (ColumnType::Number, Value::Number(n)) => {
+            let left = compute::cast(array, &DataType::Float64)?;
+            let right = Float64Array::new_scalar(n.as_f64().unwrap_or(f64::NAN));
+            compare(&left, &right, cmp)?
+        }
+        (ColumnType::Text, Value::String(s)) => {
+            let left = compute::cast(array, &DataType::Utf8)?;
+            compare(&left, &StringArray::new_scalar(s.as_str()), cmp)?
+        }
+        (ColumnType::Bool, Value::Bool(b)) => compare(array, &BooleanArray::new_scalar(*b), cmp)?,
+        // Values of different kinds are never equal or ordered.
+        _ => BooleanArray::from(vec![cmp == Comparison::Ne; batch.num_rows()]),
+    };
+    let mask = match cmp {
+        Comparison::Ne => boolean::or_kleene(&mask, &compute::is_null(array)?)?,
+        _ => mask,
+    };
+    Ok(filter_record_batch(batch, &mask)?)
+}
+
+fn compare(
+    left: &dyn arrow::array::Datum,
+    right: &dyn arrow::array::Datum,
+    cmp: Comparison,
+) -> Result<BooleanArray> {
+    Ok(match cmp {
+        Comparison::Eq => cmp::eq(left, right)?,
+        Comparison::Ne => cmp::neq(left, right)?,
+        Comparison::Gt => cmp::gt(left, right)?,
+        Comparison::Gte => cmp::gt_eq(left, right)?,
+        Comparison::Lt => cmp::lt(left, right)?,
+        Comparison::Lte => cmp::lt_eq(left, right)?,
+        Comparison::Contains | Comparison::In => {
+            return Err(
+                ServerError::invalid("data_analysis", "Unsupported columnar comparison").into(),
+            )
+        }
+    })
+}
+
+/// The first `len` values of an array as JSON.
+fn to_values(array: &ArrayRef, len: usize) -> Result<Vec<Value>> {
+    let data_type = array.data_type();
+    let values = if data_type.is_integer() {
+        let ints = compute::cast(array, &DataType::Int64)?;
+        let ints = ints.as_primitive::<Int64Type>();
+        (0..len)
+            .map(|i| {
+                if ints.is_null(i) {
+                    Value::Null
+                } else {
+                    json!(ints.value(i))
+                }
+            })
+            .collect()
+    } else if data_type.is_numeric() {
+        let floats = compute::cast(array, &DataType::Float64)?;
+        let floats = floats.as_primitive::<Float64Type>();
+        (0..len)
+            .map(|i| {
+                if floats.is_null(i) {
+                    Value::Null
+                } else {
+                    json!(floats.value(i))
+                }
+            })
+            .collect()
+    } else if *data_type == DataType::Boolean {
+        let bools = array.as_boolean();
+        (0..len)
+            .map(|i| {
+                if bools.is_null(i) {
+                    Value::Null
+                } else {
+                    json!(bools.value(i))
+                }
+            })
+            .collect()
+    } else {
+        let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())?;
+        (0..len)
+            .map(|i| {
+                if array.is_null(i) {
+                    Value::Null
+                } else {
+                    json!(formatter.value(i).to_string())
+                }
+            })
+            .collect()
+    };
+    Ok(values)
+}
@@ -0,0 +1,393 @@
+// src/analysis/engine.rs
+
+use crate::analysis::ops::{AggregateFn, Aggregation, Comparison, Op};
+use crate::analysis::stats;
+use crate::analysis::table::Table;
+use crate::analysis::timeseries;
+use crate::errors::ServerError;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Runs `ops` in order over `table`.
+///
+/// Statistics, statistical tests, and anomalies are collected in `results` as
+/// `{metric, column, value}` entries, in the shape returned by the AION-R API. The
+/// other ops, such as `filter`, `group_by`, and `resample`, transform the table; if any
+/// is present the final table is returned as `rows`.
+pub fn execute(table: Table, ops: &[Op]) -> Result<Value> {
+    Ok(run(table, ops)?.into_json())
+}
+
+/// What running a pipeline produced.
+pub struct Outcome {
+    /// The table after the last step.
+    pub table: Table,
+    /// Statistics, in the order their ops ran.
+    pub results: Vec<Value>,
+    /// Whether any step transformed the table.
+    pub transformed: bool,
+}
+
+impl Outcome {
+    /// The result in the shape returned by `execute`.
+    pub fn into_json(self) -> Value {
+        let mut output = json!({
+            "status": "completed",
+            "engine": "local",
+            "results": self.results,
+        });
+        if self.transformed {
+            output["rows"] = self.table.to_json();
+        }
+        output
+    }
+}
+
+/// Runs `ops` in order over `table`, keeping the final table.
+pub fn run(mut table: Table, ops: &[Op]) -> Result<Outcome> {
+    let mut results = Vec::new();
+    let mut transformed = false;
+
+    for op in ops {
+        match op {
+            Op::Describe { columns } => {
+                results.push(json!({
+                    "metric": "describe",
+                    "value": describe(&table, columns.as_deref())?,
+                }));
+            }
+            Op::Count { column: None } => {
+                results.push(json!({ "metric": "count", "value": table.rows.len() }));
+            }
+            Op::Count {
+                column: Some(column),
+            } => {
+                let index = column_index(&table, column)?;
+                results.push(json!({
+                    "metric": "count",
+                    "column": column,
+                    "value": table.values(index).count(),
+                }));
+            }
+            Op::Sum { column } => results.push(statistic(&table, AggregateFn::Sum, column)?),
+            Op::Mean { column } => results.push(statistic(&table, AggregateFn::Mean, column)?),
+            Op::Median { column } => results.push(statistic(&table, AggregateFn::Median, column)?),
+            Op::Stddev { column } => results.push(statistic(&table, AggregateFn::Stddev, column)?),
+            Op::Min { column } => results.push(statistic(&table, AggregateFn::Min, column)?),
+            Op::Max { column } => results.push(statistic(&table, AggregateFn::Max, column)?),
+            Op::Filter { column, cmp, value } => {
+                let index = column_index(&table, column)?;
+                table.rows.retain(|row| compare(&row[index], *cmp, value));
+                transformed = true;
+            }
+            Op::Sort { by, descending } => {
+                let indices = by
+                    .iter()
+                    .map(|c| column_index(&table, c))
+                    .collect::<Result<Vec<_>>>()?;
+                // Nulls stay last in either direction.
+                table.rows.sort_by(|a, b| {
+                    indices
+                        .iter()
+                        .map(|&i| match (&a[i], &b[i]) {
+                            (x, y) if *descending && !x.is_null() && !y.is_null() => {
+                                order(x, y).reverse()
+                            }
+                            (x, y) => order(x, y),
+                        })
+                        .find(|o| o.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                });
+                transformed = true;
+            }
+            Op::Select { columns } => {
+                let indices = columns
+                    .iter()
+                    .map(|c| column_index(&table, c))
+                    .collect::<Result<Vec<_>>>()?;
+                table = Table {
+                    columns: columns.clone(),
+                    rows: table
+                        .rows
+                        .iter()
+                        .map(|row| indices.iter().map(|&i| row[i].clone()).collect())
+                        .collect(),
+                };
+                transformed = true;
+            }
+            Op::Limit { n } => {
+                table.rows.truncate(*n);
+                transformed = true;
+            }
+            Op::GroupBy { by, aggregations } => {
+                table = group(&table, by, aggregations)?;
+                transformed = true;
+            }
+            Op::Aggregate { aggregations } => {
+                table = group(&table, &[], aggregations)?;
+                transformed = true;
+            }
+            Op::Correlation { columns, method } => {
+                results.push(stats::correlation(&table, columns.as_deref(), *method)?)
+            }
+            Op::TTest {
+                column,
+                by,
+                paired_with,
+                mu,
+                equal_variance,
+            } => results.push(stats::t_test(
+                &table,
+                column,
+                by.as_deref(),
+                paired_with.as_deref(),
+                *mu,
+                *equal_variance,
+            )?),
+            Op::ChiSquare { column, by } => {
+                results.push(stats::chi_square(&table, column, by.as_deref())?)
+            }
+            Op::Regression { y, x } => results.push(stats::regression(&table, y, x)?),
+            Op::ParseTime {
+                column,
+                format,
+                unit,
+                ..
+            } => {
+                let name = op.output_column().unwrap_or_else(|| column.clone());
+                timeseries::parse_time(&mut table, column, format.as_deref(), *unit, name)?;
+                transformed = true;
+            }
+            Op::Resample {
+                column,
+                every,
+                aggregations,
+            } => {
+                table = timeseries::resample(&table, column, *every, aggregations)?;
+                transformed = true;
+            }
+            Op::Rolling {
+                column,
+                window,
+                func,
+                ..
+            } => {
+                let name = op.output_column().unwrap_or_else(|| column.clone());
+                timeseries::rolling(&mut table, column, *window, *func, name)?;
+                transformed = true;
+            }
+            Op::Lag {
+                column, periods, ..
+            } => {
+                let name = op.output_column().unwrap_or_else(|| column.clone());
+                timeseries::shift(&mut table, column, *periods, false, name)?;
+                transformed = true;
+            }
+            Op::Diff {
+                column, periods, ..
+            } => {
+                let name = op.output_column().unwrap_or_else(|| column.clone());
+                timeseries::shift(&mut table, column, *periods, true, name)?;
+                transformed = true;
+            }
+            Op::Anomalies {
+                column,
+                method,
+                threshold,
+            } => results.push(timeseries::anomalies(&table, column, *method, *threshold)?),
+        }
+    }
+
+    Ok(Outcome {
+        table,
+        results,
+        transformed,
+    })
+}
+
+/// A single-column statistic as a `{metric, column, value}` result.
+fn statistic(table: &Table, func: AggregateFn, column: &Option<String>) -> Result<Value> {
+    let name = numeric_column(table, column.as_deref())?;
+    let index = column_index(table, &name)?;
+    Ok(json!({
+        "metric": func.name(),
+        "column": name,
+        "value": func.apply(&table.numbers(index)),
+    }))
+}
+
+pub(crate) fn column_index(table: &Table, column: &str) -> Result<usize> {
+    table.column_index(column).ok_or_else(|| {
+        ServerError::invalid("data_analysis", format!("Unknown column '{}'", column)).into()
+    })
+}
+
+/// The column a statistic applies to: the given one, or the only numeric column.
+fn numeric_column(table: &Table, column: Option<&str>) -> Result<String> {
+    if let Some(column) = column {
+        return Ok(column.to_string());
+    }
+    let numeric: Vec<&String> = (0..table.columns.len())
+        .filter(|&i| table.is_numeric(i))
+        .map(|i| &table.columns[i])
+        .collect();
+    match numeric.as_slice() {
+        [only] => Ok(only.to_string()),
+        [] => Err(ServerError::invalid("data_analysis", "The data has no numeric column").into()),
+        _ => Err(ServerError::invalid(
+            "data_analysis",
+            "'column' is required when the data has several numeric columns",
+        )
+        .into()),
+    }
+}
+
+fn describe(table: &Table, columns: Option<&[String]>) -> Result<Value> {
+    let columns = columns.unwrap_or(&table.columns);
+    let mut summary = serde_json::Map::new();
+    for column in columns {
+        let index = column_index(table, column)?;
+        let stats = if table.is_numeric(index) {
+            let numbers = table.numbers(index);
+            let mut stats = serde_json::Map::new();
+            for func in [
+                AggregateFn::Count,
+                AggregateFn::Mean,
+                AggregateFn::Stddev,
+                AggregateFn::Min,
+                AggregateFn::Median,
+                AggregateFn::Max,
+            ] {
+                stats.insert(func.name().to_string(), json!(func.apply(&numbers)));
+            }
+            Value::Object(stats)
+        } else {
+            let unique: HashSet<String> = table.values(index).map(Value::to_string).collect();
+            json!({ "count": table.values(index).count(), "unique": unique.len() })
+        };
+        summary.insert(column.clone(), stats);
+    }
+    Ok(Value::Object(summary))
+}
+
+fn group(table: &Table, by: &[String], aggregations: &[Aggregation]) -> Result<Table> {
+    let key_indices = by
+        .iter()
+        .map(|c| column_index(table, c))
+        .collect::<Result<Vec<_>>>()?;
+    let agg_indices = aggregation_indices(table, aggregations)?;
+
+    // Groups keep the order in which their keys first appear.
+    let mut groups: Vec<(Vec<Value>, Vec<&Vec<Value>>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for row in &table.rows {
+        let key: Vec<Value> = key_indices.iter().map(|&i| row[i].clone()).collect();
+        let position = *positions
+            .entry(Value::Array(key.clone()).to_string())
+            .or_insert_with(|| {
+                groups.push((key, Vec::new()));
+                groups.len() - 1
+            });
+        groups[position].1.push(row);
+    }
+    if by.is_empty() && groups.is_empty() {
+        groups.push((Vec::new(), Vec::new()));
+    }
+
+    let mut columns = by.to_vec();
+    columns.extend(aggregations.iter().map(Aggregation::output_name));
+
+    let rows = groups
+        .into_iter()
+        .map(|(mut key, rows)| {
+            for (aggregation, index) in aggregations.iter().zip(&agg_indices) {
+                key.push(aggregate(&rows, aggregation.func, *index));
+            }
+            key
+        })
+        .collect();
+
+    Ok(Table { columns, rows })
+}
+
+/// The input column index of each aggregation; `None` for a `count` of rows.
+pub(crate) fn aggregation_indices(
+    table: &Table,
+    aggregations: &[Aggregation],
+) -> Result<Vec<Option<usize>>> {
+    aggregations
+        .iter()
+        .map(|a| match (&a.column, a.func) {
+            (Some(column), _) => column_index(table, column).map(Some),
+            (None, AggregateFn::Count) => Ok(None),
+            (None, func) => Err(ServerError::invalid(
+                "data_analysis",
+                format!("Aggregation '{}' needs a 'column'", func.name()),
+            )
+            .into()),
+        })
+        .collect()
+}
+
+/// Applies `func` to column `index` of `rows`, or counts the rows if there is no column.
+pub(crate) fn aggregate(rows: &[&Vec<Value>], func: AggregateFn, index: Option<usize>) -> Value {
+    match index {
+        None => json!(rows.len()),
+        Some(i) if func == AggregateFn::Count => {
+            json!(rows.iter().filter(|r| !r[i].is_null()).count())
+        }
+        Some(i) => {
+            let numbers: Vec<f64> = rows.iter().filter_map(|r| r[i].as_f64()).collect();
+            json!(func.apply(&numbers))
+        }
+    }
+}
+
+/// Orders values: numbers, then strings, then booleans, then anything else; nulls last.
+fn order(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Number(_) => 0,
+            Value::String(_) => 1,
+            Value::Bool(_) => 2,
+            Value::Null => 4,
+            _ => 3,
+        }
+    }
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x
+            .as_f64()
+            .unwrap_or(f64::NAN)
+            .total_cmp(&y.as_f64().unwrap_or(f64::NAN)),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn compare(cell: &Value, cmp: Comparison, value: &Value) -> bool {
+    let same_kind = matches!(
+        (cell, value),
+        (Value::Number(_), Value::Number(_))
+            | (Value::String(_), Value::String(_))
+            | (Value::Bool(_), Value::Bool(_))
+    );
+    match cmp {
+        Comparison::Eq => same_kind && order(cell, value).is_eq() || cell == value,
+        Comparison::Ne => !(same_kind && order(cell, value).is_eq() || cell == value),
+        Comparison::Gt => same_kind && order(cell, value).is_gt(),
+        Comparison::Gte => same_kind && order(cell, value).is_ge(),
+        Comparison::Lt => same_kind && order(cell, value).is_lt(),
+        Comparison::Lte => same_kind && order(cell, value).is_le(),
+        Comparison::Contains => match (cell, value) {
+            (Value::String(s), Value::String(needle)) => s.contains(needle.as_str()),
+            (Value::Array(items), needle) => items.contains(needle),
+            _ => false,
+        },
+        Comparison::In => value
+            .as_array()
+            .is_some_and(|items| items.iter().any(|item| compare(cell, Comparison::Eq, item))),
+    }
+}
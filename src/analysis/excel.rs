@@ -0,0 +1,259 @@
+// src/analysis/excel.rs
+
+use crate::analysis::formats::looks_like_header;
+use crate::analysis::table::{text, Table};
+use crate::errors::ServerError;
+use anyhow::Result;
+use calamine::{open_workbook_auto_from_rs, Data, Dimensions, Range, Reader, Sheets};
+use chrono::{Datelike, Timelike};
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Cursor;
+
+/// Which part of a workbook to read.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ExcelOptions {
+    /// Worksheet name; the first sheet if omitted.
+    #[serde(default)]
+    pub sheet: Option<String>,
+    /// A1-style cells such as `B2:F40`, optionally qualified with a sheet
+    /// (`Sales!B2:F40`), or a defined name; the sheet's used range if omitted.
+    #[serde(default)]
+    pub range: Option<String>,
+    /// Whether the first row holds column names; detected if omitted.
+    #[serde(default)]
+    pub header: Option<bool>,
+}
+
+/// Top-left and bottom-right cells, as zero-based `(row, column)`.
+type Area = ((u32, u32), (u32, u32));
+
+/// Reads a sheet or range of an Excel (`.xlsx`, `.xlsm`, `.xlsb`, `.xls`) or
+/// OpenDocument workbook into a table.
+///
+/// Merged cells take the value of their top-left cell, dates and times become
+/// ISO 8601 text, and empty rows are dropped.
+pub fn read_workbook(bytes: Vec<u8>, options: &ExcelOptions) -> Result<Table> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).map_err(|e| {
+        ServerError::invalid("data_analysis", format!("Invalid Excel workbook: {}", e))
+    })?;
+    let (sheet, area) = locate(&workbook, options)?;
+    let range = workbook.worksheet_range(&sheet).map_err(|e| {
+        ServerError::invalid(
+            "data_analysis",
+            format!("Cannot read sheet '{}': {}", sheet, e),
+        )
+    })?;
+    let merges = merged_cells(&mut workbook, &sheet)?;
+
+    let (Some(used_start), Some(used_end)) = (range.start(), range.end()) else {
+        return Ok(Table::default());
+    };
+    let (start, end) = area.unwrap_or((used_start, used_end));
+    // Cells past the used range are empty, so there is no need to read them.
+    let end = (end.0.min(used_end.0), end.1.min(used_end.1));
+    if start.0 > end.0 || start.1 > end.1 {
+        return Ok(Table::default());
+    }
+
+    let mut grid: Vec<Vec<Value>> = (start.0..=end.0)
+        .map(|r| (start.1..=end.1).map(|c| cell(&range, (r, c))).collect())
+        .collect();
+    // Cells hidden under a merge, other than its top-left one.
+    let mut covered = vec![vec![false; grid[0].len()]; grid.len()];
+    for merge in &merges {
+        let value = cell(&range, merge.start);
+        for r in merge.start.0.max(start.0)..=merge.end.0.min(end.0) {
+            for c in merge.start.1.max(start.1)..=merge.end.1.min(end.1) {
+                let (i, j) = ((r - start.0) as usize, (c - start.1) as usize);
+                grid[i][j] = value.clone();
+                covered[i][j] = (r, c) != merge.start;
+            }
+        }
+    }
+
+    let header = options.header.unwrap_or_else(|| {
+        let first: Vec<String> = grid[0]
+            .iter()
+            .zip(&covered[0])
+            .filter(|(_, covered)| !**covered)
+            .map(|(value, _)| text(value))
+            .collect();
+        grid.len() > 1 && looks_like_header(&first)
+    });
+    let columns = if header {
+        column_names(&grid.remove(0))
+    } else {
+        (1..=grid[0].len())
+            .map(|i| format!("column_{}", i))
+            .collect()
+    };
+    let rows = grid
+        .into_iter()
+        .filter(|row| row.iter().any(|v| !v.is_null()))
+        .collect();
+    Ok(Table { columns, rows })
+}
+
+/// The sheet to read and, if a range was given, the cells within it.
+fn locate<RS>(workbook: &Sheets<RS>, options: &ExcelOptions) -> Result<(String, Option<Area>)>
+where
+    RS: std::io::Read + std::io::Seek,
+{
+    let mut sheet = options.sheet.clone();
+    let area = match &options.range {
+        None => None,
+        Some(range) => {
+            let reference = workbook
+                .defined_names()
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(range))
+                .map_or(range.as_str(), |(_, reference)| reference.as_str())
+                .trim_start_matches('=');
+            let cells = match reference.rsplit_once('!') {
+                Some((qualifier, cells)) => {
+                    let qualifier = qualifier.trim_matches('\'').replace("''", "'");
+                    match &sheet {
+                        Some(sheet) if *sheet != qualifier => {
+                            return Err(ServerError::invalid(
+                                "data_analysis",
+                                format!("'range' refers to sheet '{}', not '{}'", qualifier, sheet),
+                            )
+                            .into())
+                        }
+                        _ => sheet = Some(qualifier),
+                    }
+                    cells
+                }
+                None => reference,
+            };
+            Some(parse_area(cells).ok_or_else(|| {
+                ServerError::invalid(
+                    "data_analysis",
+                    format!(
+                        "Invalid range '{}'; expected a defined name or cells such as B2:F40",
+                        range
+                    ),
+                )
+            })?)
+        }
+    };
+
+    let names = workbook.sheet_names();
+    let sheet = match sheet {
+        Some(sheet) if names.contains(&sheet) => sheet,
+        Some(sheet) => {
+            return Err(ServerError::invalid(
+                "data_analysis",
+                format!(
+                    "Unknown sheet '{}'; available sheets: {}",
+                    sheet,
+                    names.join(", ")
+                ),
+            )
+            .into())
+        }
+        None => names
+            .first()
+            .cloned()
+            .ok_or_else(|| ServerError::invalid("data_analysis", "The workbook has no sheets"))?,
+    };
+    Ok((sheet, area))
+}
+
+/// Parses `B2:F40` or a single cell such as `B2`; `$` anchors are ignored.
+fn parse_area(cells: &str) -> Option<Area> {
+    let (first, last) = cells.split_once(':').unwrap_or((cells, cells));
+    let (a, b) = (parse_cell(first)?, parse_cell(last)?);
+    Some(((a.0.min(b.0), a.1.min(b.1)), (a.0.max(b.0), a.1.max(b.1))))
+}
+
+fn parse_cell(cell: &str) -> Option<(u32, u32)> {
+    let cell = cell.trim().replace('$', "");
+    let digits = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, number) = cell.split_at(digits);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let column = letters.chars().try_fold(0u32, |acc, c| {
+        acc.checked_mul(26)?
+            .checked_add(c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+    })?;
+    let row: u32 = number.parse().ok()?;
+    Some((row.checked_sub(1)?, column - 1))
+}
+
+fn merged_cells<RS>(workbook: &mut Sheets<RS>, sheet: &str) -> Result<Vec<Dimensions>>
+where
+    RS: std::io::Read + std::io::Seek,
+{
+    Ok(match workbook {
+        Sheets::Xlsx(xlsx) => xlsx
+            .worksheet_merge_cells(sheet)
+            .transpose()
+            .map_err(|e| {
+                ServerError::invalid("data_analysis", format!("Cannot read merged cells: {}", e))
+            })?
+            .unwrap_or_default(),
+        Sheets::Xls(xls) => xls.worksheet_merge_cells(sheet).unwrap_or_default(),
+        _ => Vec::new(),
+    })
+}
+
+fn cell(range: &Range<Data>, position: (u32, u32)) -> Value {
+    match range.get_value(position) {
+        None | Some(Data::Empty) | Some(Data::Error(_)) => Value::Null,
+        Some(Data::Int(n)) => Value::from(*n),
+        // Spreadsheets store every number as a float; whole ones read better as integers.
+        Some(Data::Float(f)) if f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 => {
+            Value::from(*f as i64)
+        }
+        Some(Data::Float(f)) => serde_json::Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        Some(Data::String(s)) if s.is_empty() => Value::Null,
+        Some(Data::String(s)) | Some(Data::DateTimeIso(s)) | Some(Data::DurationIso(s)) => {
+            Value::String(s.clone())
+        }
+        Some(Data::Bool(b)) => Value::Bool(*b),
+        Some(Data::DateTime(dt)) if dt.is_duration() => {
+            let seconds = (dt.as_f64() * 86_400.0).round() as i64;
+            Value::String(format!(
+                "{}:{:02}:{:02}",
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60
+            ))
+        }
+        Some(Data::DateTime(dt)) => dt.as_datetime().map_or(Value::Null, |dt| {
+            let date = format!("{:04}-{:02}-{:02}", dt.year(), dt.month(), dt.day());
+            Value::String(if dt.num_seconds_from_midnight() == 0 {
+                date
+            } else {
+                format!(
+                    "{}T{:02}:{:02}:{:02}",
+                    date,
+                    dt.hour(),
+                    dt.minute(),
+                    dt.second()
+                )
+            })
+        }),
+    }
+}
+
+/// Column names from a header row: blank cells get positional names and repeats,
+/// such as those left by a merged heading, get their position appended.
+fn column_names(header: &[Value]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(header.len());
+    for (i, value) in header.iter().enumerate() {
+        let name = text(value).trim().to_string();
+        names.push(if name.is_empty() {
+            format!("column_{}", i + 1)
+        } else if names.contains(&name) {
+            format!("{}_{}", name, i + 1)
+        } else {
+            name
+        });
+    }
+    names
+}
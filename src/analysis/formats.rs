@@ -0,0 +1,286 @@
+// src/analysis/formats.rs
+
+use crate::analysis::table::Table;
+use crate::errors::ServerError;
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+/// Encodings accepted for `data_analysis` datasets.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Json,
+    Csv,
+    Tsv,
+    Ndjson,
+    Parquet,
+    /// Arrow IPC, as a file or a stream.
+    Arrow,
+    /// An Excel or OpenDocument workbook.
+    Excel,
+}
+
+impl DataFormat {
+    /// Guesses the format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(DataFormat::Json),
+            "csv" => Some(DataFormat::Csv),
+            "tsv" | "tab" => Some(DataFormat::Tsv),
+            "ndjson" | "jsonl" => Some(DataFormat::Ndjson),
+            "parquet" | "pq" => Some(DataFormat::Parquet),
+            "arrow" | "arrows" | "feather" | "ipc" => Some(DataFormat::Arrow),
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Some(DataFormat::Excel),
+            _ => None,
+        }
+    }
+
+    /// Whether the format is binary, and so can only be read from a file or resource.
+    pub fn is_binary(self) -> bool {
+        matches!(
+            self,
+            DataFormat::Parquet | DataFormat::Arrow | DataFormat::Excel
+        )
+    }
+
+    /// Recognizes Parquet, Arrow IPC, and workbook data by their magic bytes.
+    pub fn detect_binary(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"PAR1") {
+            Some(DataFormat::Parquet)
+        } else if bytes.starts_with(b"ARROW1") || bytes.starts_with(&[0xff, 0xff, 0xff, 0xff]) {
+            Some(DataFormat::Arrow)
+        } else if bytes.starts_with(b"PK\x03\x04")
+            || bytes.starts_with(&[0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1])
+        {
+            // Zip (xlsx, xlsb, ods) and OLE (xls) containers
+            Some(DataFormat::Excel)
+        } else {
+            None
+        }
+    }
+
+    /// Guesses the format of text: JSON, NDJSON if every line is a JSON object,
+    /// TSV if the first line has tabs, and CSV otherwise.
+    pub fn detect(text: &str) -> Self {
+        let trimmed = text.trim_start();
+        if serde_json::from_str::<Value>(text).is_ok() {
+            return DataFormat::Json;
+        }
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty()).peekable();
+        if trimmed.starts_with('{')
+            && lines.peek().is_some()
+            && lines.all(|l| matches!(serde_json::from_str(l), Ok(Value::Object(_))))
+        {
+            return DataFormat::Ndjson;
+        }
+        match text.lines().next() {
+            Some(first) if first.contains('\t') => DataFormat::Tsv,
+            _ => DataFormat::Csv,
+        }
+    }
+}
+
+/// Options for CSV and TSV text.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CsvOptions {
+    /// Whether the first row holds column names; detected if omitted.
+    #[serde(default)]
+    pub header: Option<bool>,
+    /// Field delimiter; `,` for CSV and a tab for TSV by default.
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    /// Quote character, `"` by default.
+    #[serde(default)]
+    pub quote: Option<char>,
+    /// Convert numbers and booleans from text; empty fields become null either way.
+    #[serde(default = "default_infer_types")]
+    pub infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            header: None,
+            delimiter: None,
+            quote: None,
+            infer_types: true,
+        }
+    }
+}
+
+fn default_infer_types() -> bool {
+    true
+}
+
+/// Parses CSV, TSV, or NDJSON text into a table.
+pub fn parse_table(text: &str, format: DataFormat, options: &CsvOptions) -> Result<Table> {
+    match format {
+        DataFormat::Json => {
+            let value: Value = serde_json::from_str(text).map_err(|e| {
+                ServerError::invalid("data_analysis", format!("Invalid JSON data: {}", e))
+            })?;
+            Table::from_json(&value).ok_or_else(|| {
+                ServerError::invalid("data_analysis", "The JSON data is not tabular").into()
+            })
+        }
+        DataFormat::Ndjson => parse_ndjson(text),
+        DataFormat::Csv => parse_delimited(text, options.delimiter.unwrap_or(','), options),
+        DataFormat::Tsv => parse_delimited(text, options.delimiter.unwrap_or('\t'), options),
+        DataFormat::Parquet | DataFormat::Arrow | DataFormat::Excel => Err(ServerError::invalid(
+            "data_analysis",
+            "Parquet, Arrow, and Excel data must be read from 'data_file' or 'data_uri'",
+        )
+        .into()),
+    }
+}
+
+fn parse_ndjson(text: &str) -> Result<Table> {
+    let rows = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match serde_json::from_str(line) {
+            Ok(Value::Object(row)) => Ok(Value::Object(row)),
+            Ok(_) => Err(ServerError::invalid(
+                "data_analysis",
+                format!("NDJSON line {} is not an object", i + 1),
+            )
+            .into()),
+            Err(e) => Err(ServerError::invalid(
+                "data_analysis",
+                format!("NDJSON line {}: {}", i + 1, e),
+            )
+            .into()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Table::from_json(&Value::Array(rows)).unwrap_or_default())
+}
+
+fn ascii_byte(c: char, what: &str) -> Result<u8> {
+    u8::try_from(c).ok().filter(u8::is_ascii).ok_or_else(|| {
+        ServerError::invalid(
+            "data_analysis",
+            format!("The {} must be a single ASCII character", what),
+        )
+        .into()
+    })
+}
+
+fn parse_delimited(text: &str, delimiter: char, options: &CsvOptions) -> Result<Table> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(ascii_byte(delimiter, "delimiter")?)
+        .quote(ascii_byte(options.quote.unwrap_or('"'), "quote")?)
+        .from_reader(text.as_bytes());
+
+    let mut records: Vec<Vec<String>> = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record
+            .map_err(|e| ServerError::invalid("data_analysis", format!("Row {}: {}", i + 1, e)))?;
+        records.push(record.iter().map(str::to_string).collect());
+    }
+
+    let header = options.header.unwrap_or_else(|| {
+        records
+            .first()
+            .is_some_and(|first| looks_like_header(first))
+    });
+    let columns: Vec<String> = match (header, records.first()) {
+        (true, Some(first)) => first.clone(),
+        _ => (1..=records.iter().map(Vec::len).max().unwrap_or(0))
+            .map(|i| format!("column_{}", i))
+            .collect(),
+    };
+    let body = if header && !records.is_empty() {
+        &records[1..]
+    } else {
+        &records[..]
+    };
+
+    let offset = if header { 2 } else { 1 };
+    let mut rows = Vec::with_capacity(body.len());
+    for (i, record) in body.iter().enumerate() {
+        if record.len() > columns.len() {
+            return Err(ServerError::invalid(
+                "data_analysis",
+                format!(
+                    "Row {} has {} fields, expected {}",
+                    i + offset,
+                    record.len(),
+                    columns.len()
+                ),
+            )
+            .into());
+        }
+        rows.push(
+            (0..columns.len())
+                .map(|c| match record.get(c).map(String::as_str) {
+                    None | Some("") => Value::Null,
+                    Some(field) => Value::String(field.to_string()),
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let mut table = Table { columns, rows };
+    if options.infer_types {
+        for index in 0..table.columns.len() {
+            infer_column(&mut table, index);
+        }
+    }
+    Ok(table)
+}
+
+/// A first row whose fields are distinct, non-empty, and not numbers or booleans.
+pub(crate) fn looks_like_header(first: &[String]) -> bool {
+    first.iter().enumerate().all(|(i, field)| {
+        !field.trim().is_empty() && scalar(field).is_none() && !first[..i].contains(field)
+    })
+}
+
+/// The number or boolean a field spells, if any.
+fn scalar(field: &str) -> Option<Value> {
+    let field = field.trim();
+    if let Ok(int) = field.parse::<i64>() {
+        return Some(Value::from(int));
+    }
+    if let Some(float) = field
+        .parse::<f64>()
+        .ok()
+        .filter(|f| f.is_finite())
+        .and_then(serde_json::Number::from_f64)
+    {
+        return Some(Value::Number(float));
+    }
+    match field.to_ascii_lowercase().as_str() {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+/// Converts a column of text to numbers or booleans when every field allows it.
+fn infer_column(table: &mut Table, index: usize) {
+    let parsed: Option<Vec<Value>> = table
+        .rows
+        .iter()
+        .map(|row| match &row[index] {
+            Value::String(field) => scalar(field),
+            other => Some(other.clone()),
+        })
+        .collect();
+    let Some(parsed) = parsed else {
+        return;
+    };
+    let numbers = parsed.iter().all(|v| v.is_number() || v.is_null());
+    let bools = parsed.iter().all(|v| v.is_boolean() || v.is_null());
+    if numbers || bools {
+        for (row, value) in table.rows.iter_mut().zip(parsed) {
+            row[index] = value;
+        }
+    }
+}
@@ -0,0 +1,15 @@
+// src/analysis/mod.rs
+
+pub mod chart;
+pub mod columnar;
+pub mod engine;
+pub mod excel;
+pub mod formats;
+pub mod ops;
+pub mod profile;
+pub mod render;
+pub mod sample;
+pub mod stats;
+pub mod table;
+pub mod timeseries;
+pub mod validate;
@@ -0,0 +1,447 @@
+// src/analysis/ops.rs
+
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// An analysis operation the local engine can execute.
+///
+/// Ops are given as objects tagged by `op`, e.g. `{"op": "mean", "column": "price"}`;
+/// a bare string such as `"count"` is shorthand for an op without arguments.
+#[derive(Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Op {
+    /// Summary statistics of each column.
+    Describe {
+        /// Columns to describe; all columns if omitted.
+        #[serde(default)]
+        columns: Option<Vec<String>>,
+    },
+    /// Number of rows, or of non-null values in `column`.
+    Count {
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Sum of a numeric column.
+    Sum {
+        /// May be omitted when the data has a single numeric column.
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Arithmetic mean of a numeric column.
+    Mean {
+        /// May be omitted when the data has a single numeric column.
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Median of a numeric column.
+    Median {
+        /// May be omitted when the data has a single numeric column.
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Sample standard deviation of a numeric column.
+    Stddev {
+        /// May be omitted when the data has a single numeric column.
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Smallest value of a numeric column.
+    Min {
+        /// May be omitted when the data has a single numeric column.
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Largest value of a numeric column.
+    Max {
+        /// May be omitted when the data has a single numeric column.
+        #[serde(default)]
+        column: Option<String>,
+    },
+    /// Keeps the rows where `column <cmp> value` holds.
+    Filter {
+        column: String,
+        cmp: Comparison,
+        value: Value,
+    },
+    /// Sorts rows by one or more columns.
+    Sort {
+        by: Vec<String>,
+        #[serde(default)]
+        descending: bool,
+    },
+    /// Keeps only the listed columns, in that order.
+    Select { columns: Vec<String> },
+    /// Keeps the first `n` rows.
+    Limit { n: usize },
+    /// Replaces the table with one row per distinct value of the `by` columns.
+    GroupBy {
+        by: Vec<String>,
+        aggregations: Vec<Aggregation>,
+    },
+    /// Replaces the table with a single row of aggregates.
+    Aggregate { aggregations: Vec<Aggregation> },
+    /// Correlation matrix of numeric columns, with p-values.
+    Correlation {
+        /// Columns to correlate; all numeric columns if omitted.
+        #[serde(default)]
+        columns: Option<Vec<String>>,
+        #[serde(default)]
+        method: CorrelationMethod,
+    },
+    /// Two-sided t-test of the mean of a numeric column: one-sample against `mu`,
+    /// paired with another column, or between the two groups of a `by` column.
+    TTest {
+        column: String,
+        /// Column splitting the rows into exactly two groups.
+        #[serde(default)]
+        by: Option<String>,
+        /// Numeric column paired row by row with `column`.
+        #[serde(default)]
+        paired_with: Option<String>,
+        /// Mean, or mean difference, under the null hypothesis (default 0).
+        #[serde(default)]
+        mu: f64,
+        /// Use Student's pooled-variance test instead of Welch's for two groups.
+        #[serde(default)]
+        equal_variance: bool,
+    },
+    /// Pearson's chi-square test of independence between `column` and `by`, or of
+    /// equal frequencies of the values of `column` if `by` is omitted.
+    ChiSquare {
+        column: String,
+        #[serde(default)]
+        by: Option<String>,
+    },
+    /// Ordinary least squares regression of `y` on the `x` columns, with an intercept.
+    Regression { y: String, x: Vec<String> },
+    /// Parses a column of date-time text or epoch numbers into RFC 3339 timestamps in UTC.
+    ParseTime {
+        column: String,
+        /// `strftime`-style format, e.g. `%d/%m/%Y %H:%M`; if omitted, RFC 3339 and
+        /// `YYYY-MM-DD[ HH:MM[:SS]]` text are accepted.
+        #[serde(default)]
+        format: Option<String>,
+        /// Unit of numeric epoch timestamps.
+        #[serde(default)]
+        unit: TimeUnit,
+        /// Output column name; the column is replaced if omitted.
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+    },
+    /// Replaces the table with one row per fixed interval of the timestamp `column`,
+    /// from the first to the last timestamp, including empty intervals.
+    Resample {
+        column: String,
+        /// Interval length: a number followed by `ms`, `s`, `m`, `h`, `d`, or `w`,
+        /// e.g. `15m`. Intervals are aligned to the Unix epoch.
+        #[schemars(with = "String")]
+        every: Interval,
+        aggregations: Vec<Aggregation>,
+    },
+    /// Adds a column aggregating `column` over a window of the current and preceding
+    /// rows; it is null until the window is full.
+    Rolling {
+        column: String,
+        window: usize,
+        #[serde(rename = "fn", default = "AggregateFn::mean")]
+        func: AggregateFn,
+        /// Output column name; defaults to `rolling_<fn>_<column>`.
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+    },
+    /// Adds a column holding the value of `column` `periods` rows earlier, or later
+    /// if `periods` is negative.
+    Lag {
+        column: String,
+        #[serde(default = "default_periods")]
+        periods: i64,
+        /// Output column name; defaults to `lag_<column>`.
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+    },
+    /// Adds a column holding the change of a numeric column since `periods` rows earlier.
+    Diff {
+        column: String,
+        #[serde(default = "default_periods")]
+        periods: i64,
+        /// Output column name; defaults to `diff_<column>`.
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+    },
+    /// Finds the rows whose value of a numeric column is an outlier.
+    Anomalies {
+        column: String,
+        #[serde(default)]
+        method: AnomalyMethod,
+        /// Z-score above which, or multiple of the IQR beyond the quartiles at which, a
+        /// value is an outlier; 3 for `z_score` and 1.5 for `iqr` by default.
+        #[serde(default)]
+        threshold: Option<f64>,
+    },
+}
+
+fn default_periods() -> i64 {
+    1
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrelationMethod {
+    /// Linear correlation of the values.
+    #[default]
+    Pearson,
+    /// Correlation of the ranks, for monotonic relationships.
+    Spearman,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMethod {
+    /// Values more than `threshold` standard deviations from the mean.
+    #[default]
+    ZScore,
+    /// Values more than `threshold` interquartile ranges below the first or above
+    /// the third quartile.
+    Iqr,
+}
+
+/// A fixed length of time, given as a number and a unit such as `15m`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct Interval {
+    pub millis: i64,
+}
+
+impl TryFrom<String> for Interval {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let split = text
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len());
+        let (count, unit) = text.split_at(split);
+        let unit_millis = match unit {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            "w" => 604_800_000,
+            _ => 0,
+        };
+        count
+            .parse::<i64>()
+            .ok()
+            .and_then(|count| count.checked_mul(unit_millis))
+            .filter(|&millis| millis > 0)
+            .map(|millis| Interval { millis })
+            .ok_or_else(|| {
+                format!(
+                    "invalid interval '{}'; expected a number followed by ms, s, m, h, d, or w",
+                    text
+                )
+            })
+    }
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// Substring of a string column, or element of an array column.
+    Contains,
+    /// Equal to one of the elements of an array `value`.
+    In,
+}
+
+impl Comparison {
+    /// Whether the comparison orders values, and so needs operands of the same type.
+    pub(crate) fn is_ordering(self) -> bool {
+        matches!(
+            self,
+            Comparison::Gt | Comparison::Gte | Comparison::Lt | Comparison::Lte
+        )
+    }
+}
+
+/// One output column of `group_by` or `aggregate`.
+#[derive(Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Aggregation {
+    #[serde(rename = "fn")]
+    pub func: AggregateFn,
+    /// Input column; may be omitted for `count`.
+    #[serde(default)]
+    pub column: Option<String>,
+    /// Output column name; defaults to `<fn>_<column>`.
+    #[serde(default, rename = "as")]
+    pub alias: Option<String>,
+}
+
+impl Aggregation {
+    /// Name of the column this aggregation produces.
+    pub(crate) fn output_name(&self) -> String {
+        self.alias.clone().unwrap_or_else(|| match &self.column {
+            Some(column) => format!("{}_{}", self.func.name(), column),
+            None => self.func.name().to_string(),
+        })
+    }
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFn {
+    Count,
+    Sum,
+    Mean,
+    Median,
+    Stddev,
+    Min,
+    Max,
+}
+
+impl AggregateFn {
+    fn mean() -> Self {
+        AggregateFn::Mean
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            AggregateFn::Count => "count",
+            AggregateFn::Sum => "sum",
+            AggregateFn::Mean => "mean",
+            AggregateFn::Median => "median",
+            AggregateFn::Stddev => "stddev",
+            AggregateFn::Min => "min",
+            AggregateFn::Max => "max",
+        }
+    }
+
+    /// Applies the function to numbers; `None` when there are none (except for `count`).
+    pub(crate) fn apply(self, numbers: &[f64]) -> Option<f64> {
+        if numbers.is_empty() && self != AggregateFn::Count {
+            return None;
+        }
+        let n = numbers.len() as f64;
+        let sum: f64 = numbers.iter().sum();
+        Some(match self {
+            AggregateFn::Count => n,
+            AggregateFn::Sum => sum,
+            AggregateFn::Mean => sum / n,
+            AggregateFn::Median => {
+                let mut sorted = numbers.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                let mid = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[mid - 1] + sorted[mid]) / 2.0
+                } else {
+                    sorted[mid]
+                }
+            }
+            AggregateFn::Stddev => {
+                if numbers.len() < 2 {
+                    return None;
+                }
+                let mean = sum / n;
+                let var = numbers.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+                var.sqrt()
+            }
+            AggregateFn::Min => numbers.iter().cloned().fold(f64::INFINITY, f64::min),
+            AggregateFn::Max => numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+impl Op {
+    /// Parses a list of ops, or returns `None` if any of them is not supported locally.
+    pub fn parse_all(ops: &Value) -> Option<Vec<Op>> {
+        ops.as_array()?
+            .iter()
+            .map(|op| Op::parse(op).ok())
+            .collect()
+    }
+
+    /// Parses a single op, accepting a bare string as an op without arguments.
+    pub fn parse(op: &Value) -> serde_json::Result<Op> {
+        match op {
+            Value::String(name) => serde_json::from_value(json!({ "op": name })),
+            other => serde_json::from_value(other.clone()),
+        }
+    }
+
+    /// The `op` tag, for error messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Describe { .. } => "describe",
+            Op::Count { .. } => "count",
+            Op::Sum { .. } => "sum",
+            Op::Mean { .. } => "mean",
+            Op::Median { .. } => "median",
+            Op::Stddev { .. } => "stddev",
+            Op::Min { .. } => "min",
+            Op::Max { .. } => "max",
+            Op::Filter { .. } => "filter",
+            Op::Sort { .. } => "sort",
+            Op::Select { .. } => "select",
+            Op::Limit { .. } => "limit",
+            Op::GroupBy { .. } => "group_by",
+            Op::Aggregate { .. } => "aggregate",
+            Op::Correlation { .. } => "correlation",
+            Op::TTest { .. } => "t_test",
+            Op::ChiSquare { .. } => "chi_square",
+            Op::Regression { .. } => "regression",
+            Op::ParseTime { .. } => "parse_time",
+            Op::Resample { .. } => "resample",
+            Op::Rolling { .. } => "rolling",
+            Op::Lag { .. } => "lag",
+            Op::Diff { .. } => "diff",
+            Op::Anomalies { .. } => "anomalies",
+        }
+    }
+
+    /// Name of the column a `parse_time`, `rolling`, `lag`, or `diff` step writes.
+    pub(crate) fn output_column(&self) -> Option<String> {
+        let (alias, default) = match self {
+            Op::ParseTime { column, alias, .. } => (alias, column.clone()),
+            Op::Rolling {
+                column,
+                func,
+                alias,
+                ..
+            } => (alias, format!("rolling_{}_{}", func.name(), column)),
+            Op::Lag { column, alias, .. } => (alias, format!("lag_{}", column)),
+            Op::Diff { column, alias, .. } => (alias, format!("diff_{}", column)),
+            _ => return None,
+        };
+        Some(alias.clone().unwrap_or(default))
+    }
+
+    /// JSON schema of a single op, with all definitions inlined so it can be
+    /// embedded in a tool's input schema.
+    pub fn schema() -> Value {
+        let generator = SchemaSettings::draft2020_12()
+            .with(|s| s.inline_subschemas = true)
+            .into_generator();
+        let mut schema = generator.into_root_schema_for::<Op>().to_value();
+        if let Some(obj) = schema.as_object_mut() {
+            obj.remove("$schema");
+            obj.remove("title");
+        }
+        schema
+    }
+}
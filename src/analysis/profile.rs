@@ -0,0 +1,188 @@
+// src/analysis/profile.rs
+
+use crate::analysis::ops::TimeUnit;
+use crate::analysis::table::{text, ColumnType, Table};
+use crate::analysis::timeseries::{format_timestamp, parse_timestamp};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// Longest example text, in characters.
+const MAX_EXAMPLE_LENGTH: usize = 80;
+
+/// The inferred type of a column, finer than [`ColumnType`]: whole numbers and
+/// date-time text are told apart.
+fn type_name(table: &Table, index: usize) -> &'static str {
+    match table.column_type(index) {
+        ColumnType::Number if table.values(index).all(|v| v.is_i64() || v.is_u64()) => "integer",
+        ColumnType::Number => "number",
+        ColumnType::Text
+            if table
+                .values(index)
+                .all(|v| parse_timestamp(v, None, TimeUnit::default()).is_some()) =>
+        {
+            "timestamp"
+        }
+        ColumnType::Text => "text",
+        ColumnType::Bool => "boolean",
+        ColumnType::Nested => "nested",
+        ColumnType::Mixed => "mixed",
+        ColumnType::Empty => "empty",
+    }
+}
+
+/// Describes every column of `table`: its type, nulls, number of distinct values,
+/// range, and up to `examples` distinct example values.
+pub fn profile(table: &Table, examples: usize) -> Value {
+    let rows = table.rows.len();
+    let columns: Vec<Value> = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let kind = type_name(table, index);
+            let nulls = rows - table.values(index).count();
+            let mut seen = HashSet::new();
+            let mut samples = Vec::new();
+            for value in table.values(index) {
+                if seen.insert(value.to_string()) && samples.len() < examples {
+                    samples.push(example(value));
+                }
+            }
+
+            let mut column = Map::new();
+            column.insert("name".to_string(), json!(name));
+            column.insert("type".to_string(), json!(kind));
+            column.insert("nulls".to_string(), json!(nulls));
+            column.insert(
+                "null_rate".to_string(),
+                json!(if rows == 0 {
+                    0.0
+                } else {
+                    nulls as f64 / rows as f64
+                }),
+            );
+            column.insert("distinct".to_string(), json!(seen.len()));
+            column.insert(
+                "unique".to_string(),
+                json!(!seen.is_empty() && seen.len() == rows - nulls),
+            );
+            match kind {
+                "integer" | "number" => {
+                    let numbers = table.numbers(index);
+                    let order = |a: &&Value, b: &&Value| {
+                        let key = |v: &Value| v.as_f64().unwrap_or(f64::NAN);
+                        key(a).total_cmp(&key(b))
+                    };
+                    let min = table.values(index).min_by(order);
+                    let max = table.values(index).max_by(order);
+                    column.insert("min".to_string(), json!(min));
+                    column.insert("max".to_string(), json!(max));
+                    column.insert(
+                        "mean".to_string(),
+                        json!(numbers.iter().sum::<f64>() / numbers.len() as f64),
+                    );
+                }
+                "timestamp" => {
+                    let times: Vec<_> = table
+                        .values(index)
+                        .filter_map(|v| parse_timestamp(v, None, TimeUnit::default()))
+                        .collect();
+                    column.insert(
+                        "min".to_string(),
+                        json!(times.iter().min().copied().map(format_timestamp)),
+                    );
+                    column.insert(
+                        "max".to_string(),
+                        json!(times.iter().max().copied().map(format_timestamp)),
+                    );
+                }
+                "text" => {
+                    let lengths = table
+                        .values(index)
+                        .filter_map(Value::as_str)
+                        .map(|text| text.chars().count());
+                    let (min, max) = lengths.fold((None, None), |(min, max), n| {
+                        (
+                            Some(min.map_or(n, |m: usize| m.min(n))),
+                            Some(max.map_or(n, |m: usize| m.max(n))),
+                        )
+                    });
+                    column.insert("min_length".to_string(), json!(min));
+                    column.insert("max_length".to_string(), json!(max));
+                }
+                _ => {}
+            }
+            column.insert("examples".to_string(), Value::Array(samples));
+            Value::Object(column)
+        })
+        .collect();
+
+    json!({
+        "rows": rows,
+        "column_count": columns.len(),
+        "columns": columns,
+    })
+}
+
+/// An example value, with long text cut short.
+fn example(value: &Value) -> Value {
+    match value.as_str() {
+        Some(text) if text.chars().count() > MAX_EXAMPLE_LENGTH => {
+            let cut: String = text.chars().take(MAX_EXAMPLE_LENGTH - 1).collect();
+            json!(format!("{}…", cut))
+        }
+        _ => value.clone(),
+    }
+}
+
+/// A Markdown table summarizing a [`profile`], one row per column.
+pub fn summary(profile: &Value) -> String {
+    let columns = profile["columns"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    let table = Table {
+        columns: ["column", "type", "nulls", "distinct", "range", "examples"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+        rows: columns
+            .iter()
+            .map(|column| {
+                let range = match (&column["min"], &column["max"]) {
+                    (Value::Null, Value::Null) => {
+                        match (&column["min_length"], &column["max_length"]) {
+                            (Value::Number(min), Value::Number(max)) => {
+                                format!("{} – {} chars", min, max)
+                            }
+                            _ => String::new(),
+                        }
+                    }
+                    (min, max) => format!("{} – {}", text(min), text(max)),
+                };
+                let examples = column["examples"]
+                    .as_array()
+                    .map(|values| values.iter().map(text).collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+                vec![
+                    column["name"].clone(),
+                    column["type"].clone(),
+                    json!(format!(
+                        "{} ({:.1}%)",
+                        column["nulls"],
+                        column["null_rate"].as_f64().unwrap_or(0.0) * 100.0
+                    )),
+                    column["distinct"].clone(),
+                    json!(range),
+                    json!(examples),
+                ]
+            })
+            .collect(),
+    };
+    format!(
+        "{} rows, {} columns\n\n{}",
+        profile["rows"],
+        columns.len(),
+        table.to_markdown()
+    )
+}
@@ -0,0 +1,115 @@
+// src/analysis/render.rs
+
+use crate::analysis::table::Table;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Rows shown when the caller does not set `max_display_rows`.
+pub const DEFAULT_MAX_ROWS: usize = 50;
+
+/// Columns shown when the caller does not set `max_display_columns`.
+pub const DEFAULT_MAX_COLUMNS: usize = 20;
+
+/// Widest cell of a plain-text table, in characters.
+const MAX_TEXT_WIDTH: usize = 40;
+
+/// Omitted columns named in a truncation note.
+const MAX_NAMED_COLUMNS: usize = 10;
+
+/// How tabular results are shown as text.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// A GitHub-flavored Markdown table.
+    #[default]
+    Markdown,
+    Csv,
+    /// Columns aligned with spaces.
+    Text,
+    /// No text rendering; the rows are only returned as JSON.
+    Json,
+}
+
+/// How much of a table to show, and in which format.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    pub format: OutputFormat,
+    pub max_rows: usize,
+    pub max_columns: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::default(),
+            max_rows: DEFAULT_MAX_ROWS,
+            max_columns: DEFAULT_MAX_COLUMNS,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// MCP text content blocks showing the first rows and columns of `table`,
+    /// followed by a note naming what was left out. `None` for the JSON format.
+    pub fn content(&self, table: &Table) -> Option<Value> {
+        let shown = Table {
+            columns: table
+                .columns
+                .iter()
+                .take(self.max_columns)
+                .cloned()
+                .collect(),
+            rows: table
+                .rows
+                .iter()
+                .take(self.max_rows)
+                .map(|row| row.iter().take(self.max_columns).cloned().collect())
+                .collect(),
+        };
+        let text = match self.format {
+            OutputFormat::Markdown => shown.to_markdown(),
+            OutputFormat::Csv => shown.to_csv(),
+            OutputFormat::Text => shown.to_text(MAX_TEXT_WIDTH),
+            OutputFormat::Json => return None,
+        };
+        let mut content = vec![json!({ "type": "text", "text": text })];
+        if let Some(note) = truncation_note(table, &shown) {
+            content.push(json!({ "type": "text", "text": note }));
+        }
+        Some(Value::Array(content))
+    }
+}
+
+/// Says which rows and columns of `table` are missing from `shown`, if any.
+fn truncation_note(table: &Table, shown: &Table) -> Option<String> {
+    let mut parts = Vec::new();
+    if shown.rows.len() < table.rows.len() {
+        parts.push(format!(
+            "the first {} of {} rows",
+            shown.rows.len(),
+            table.rows.len()
+        ));
+    }
+    if shown.columns.len() < table.columns.len() {
+        let omitted = &table.columns[shown.columns.len()..];
+        let mut names: Vec<&str> = omitted
+            .iter()
+            .take(MAX_NAMED_COLUMNS)
+            .map(String::as_str)
+            .collect();
+        let more = format!(
+            "and {} more",
+            omitted.len().saturating_sub(MAX_NAMED_COLUMNS)
+        );
+        if omitted.len() > MAX_NAMED_COLUMNS {
+            names.push(&more);
+        }
+        parts.push(format!(
+            "{} of {} columns (omitted: {})",
+            shown.columns.len(),
+            table.columns.len(),
+            names.join(", ")
+        ));
+    }
+    (!parts.is_empty()).then(|| format!("Truncated: showing {}.", parts.join(" and ")))
+}
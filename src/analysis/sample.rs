@@ -0,0 +1,153 @@
+// src/analysis/sample.rs
+
+use crate::analysis::engine::column_index;
+use crate::analysis::table::Table;
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// How rows are chosen when a dataset is sampled down.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleMethod {
+    /// Rows drawn uniformly at random.
+    #[default]
+    Random,
+    /// Rows drawn at random within each group of `by`, in proportion to its size.
+    Stratified,
+    /// The first and last rows.
+    HeadTail,
+}
+
+impl SampleMethod {
+    pub fn name(self) -> &'static str {
+        match self {
+            SampleMethod::Random => "random",
+            SampleMethod::Stratified => "stratified",
+            SampleMethod::HeadTail => "head_tail",
+        }
+    }
+}
+
+/// The `sampling` input of `data_analysis`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SampleSpec {
+    #[serde(default)]
+    pub method: SampleMethod,
+    /// Column whose groups a stratified sample keeps in proportion.
+    #[serde(default)]
+    pub by: Option<String>,
+    /// Seed of the random choices, so the same data always yields the same sample.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl SampleSpec {
+    /// Describes the sample for results.
+    pub fn describe(&self) -> Value {
+        let mut description = json!({ "method": self.method.name() });
+        if self.method != SampleMethod::HeadTail {
+            description["seed"] = json!(self.seed);
+        }
+        if let (SampleMethod::Stratified, Some(by)) = (self.method, &self.by) {
+            description["by"] = json!(by);
+        }
+        description
+    }
+}
+
+/// The SplitMix64 generator: small, fast, and stable across releases, so seeded
+/// samples can be reproduced.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// `n` of `items` chosen at random, by a partial Fisher-Yates shuffle.
+    fn choose(&mut self, mut items: Vec<usize>, n: usize) -> Vec<usize> {
+        let n = n.min(items.len());
+        for i in 0..n {
+            let j = i + self.below(items.len() - i);
+            items.swap(i, j);
+        }
+        items.truncate(n);
+        items
+    }
+}
+
+/// At most `n` rows of `table` chosen by `spec`, in their original order.
+pub fn sample(table: &Table, n: usize, spec: &SampleSpec) -> Result<Table> {
+    let total = table.rows.len();
+    let mut rng = SplitMix64(spec.seed);
+    let mut chosen = match spec.method {
+        _ if n >= total => (0..total).collect(),
+        SampleMethod::Random => rng.choose((0..total).collect(), n),
+        SampleMethod::HeadTail => (0..n - n / 2).chain(total - n / 2..total).collect(),
+        SampleMethod::Stratified => {
+            let by = spec.by.as_deref().unwrap_or_default();
+            let index = column_index(table, by)?;
+            let mut groups: Vec<Vec<usize>> = Vec::new();
+            let mut positions: HashMap<String, usize> = HashMap::new();
+            for (row, values) in table.rows.iter().enumerate() {
+                let position = *positions
+                    .entry(values[index].to_string())
+                    .or_insert_with(|| {
+                        groups.push(Vec::new());
+                        groups.len() - 1
+                    });
+                groups[position].push(row);
+            }
+            quotas(&groups, n)
+                .into_iter()
+                .zip(groups)
+                .flat_map(|(quota, rows)| rng.choose(rows, quota))
+                .collect()
+        }
+    };
+    chosen.sort_unstable();
+    Ok(Table {
+        columns: table.columns.clone(),
+        rows: chosen.into_iter().map(|i| table.rows[i].clone()).collect(),
+    })
+}
+
+/// Splits `n` rows among `groups` in proportion to their sizes, by largest
+/// remainder. Every group gets at least one row when `n` allows it.
+fn quotas(groups: &[Vec<usize>], n: usize) -> Vec<usize> {
+    let total: usize = groups.iter().map(Vec::len).sum();
+    let shares: Vec<f64> = groups
+        .iter()
+        .map(|g| n as f64 * g.len() as f64 / total as f64)
+        .collect();
+    let mut quotas: Vec<usize> = shares.iter().map(|s| s.floor() as usize).collect();
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by(|&a, &b| shares[b].fract().total_cmp(&shares[a].fract()));
+    let assigned: usize = quotas.iter().sum();
+    for &i in order.iter().take(n.saturating_sub(assigned)) {
+        quotas[i] += 1;
+    }
+
+    if n >= groups.len() {
+        for i in 0..groups.len() {
+            if quotas[i] == 0 {
+                let largest = (0..groups.len()).max_by_key(|&j| quotas[j]).unwrap_or(i);
+                quotas[largest] -= 1;
+                quotas[i] = 1;
+            }
+        }
+    }
+    quotas
+}
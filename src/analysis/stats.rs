@@ -0,0 +1,555 @@
+// src/analysis/stats.rs
+
+use crate::analysis::engine::column_index;
+use crate::analysis::ops::CorrelationMethod;
+use crate::analysis::table::{text, Table};
+use crate::errors::ServerError;
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use statrs::distribution::{ChiSquared, ContinuousCDF, FisherSnedecor, StudentsT};
+
+/// Confidence level of the intervals reported with t-tests.
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Correlation matrix of `columns`, or of all numeric columns, as a
+/// `{metric, value}` result. Each pair uses the rows where both values are numbers.
+pub fn correlation(
+    table: &Table,
+    columns: Option<&[String]>,
+    method: CorrelationMethod,
+) -> Result<Value> {
+    let columns: Vec<String> = match columns {
+        Some(columns) => columns.to_vec(),
+        None => (0..table.columns.len())
+            .filter(|&i| table.is_numeric(i))
+            .map(|i| table.columns[i].clone())
+            .collect(),
+    };
+    if columns.len() < 2 {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            "correlation needs at least two numeric columns",
+        )
+        .into());
+    }
+    let indices = columns
+        .iter()
+        .map(|c| column_index(table, c))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut matrix = Map::new();
+    let mut p_values = Map::new();
+    for (a, &i) in columns.iter().zip(&indices) {
+        let mut r_row = Map::new();
+        let mut p_row = Map::new();
+        for (b, &j) in columns.iter().zip(&indices) {
+            let (x, y) = pairs(table, i, j);
+            let (x, y) = match method {
+                CorrelationMethod::Pearson => (x, y),
+                CorrelationMethod::Spearman => (ranks(&x), ranks(&y)),
+            };
+            let r = pearson(&x, &y);
+            r_row.insert(b.clone(), json!(r));
+            p_row.insert(
+                b.clone(),
+                json!(r.and_then(|r| correlation_p_value(r, x.len()))),
+            );
+        }
+        matrix.insert(a.clone(), Value::Object(r_row));
+        p_values.insert(a.clone(), Value::Object(p_row));
+    }
+
+    Ok(json!({
+        "metric": "correlation",
+        "value": {
+            "method": match method {
+                CorrelationMethod::Pearson => "Pearson correlation",
+                CorrelationMethod::Spearman => "Spearman rank correlation",
+            },
+            "alternative": "two-sided",
+            "columns": columns,
+            "matrix": matrix,
+            "p_values": p_values,
+        },
+    }))
+}
+
+/// A two-sided t-test of `column`, as a `{metric, column, value}` result.
+pub fn t_test(
+    table: &Table,
+    column: &str,
+    by: Option<&str>,
+    paired_with: Option<&str>,
+    mu: f64,
+    equal_variance: bool,
+) -> Result<Value> {
+    let index = column_index(table, column)?;
+    let value = match (by, paired_with) {
+        (Some(_), Some(_)) => {
+            return Err(ServerError::invalid(
+                "data_analysis",
+                "pass either 'by' or 'paired_with', not both",
+            )
+            .into())
+        }
+        (None, None) => {
+            let mut result = one_sample(&table.numbers(index), mu)?;
+            result["method"] = json!("One-sample t-test");
+            result
+        }
+        (None, Some(other)) => {
+            let (x, y) = pairs(table, index, column_index(table, other)?);
+            let differences: Vec<f64> = x.iter().zip(&y).map(|(a, b)| a - b).collect();
+            let mut result = one_sample(&differences, mu)?;
+            result["method"] = json!("Paired t-test");
+            result["paired_with"] = json!(other);
+            result
+        }
+        (Some(by), None) => two_sample(table, index, by, mu, equal_variance)?,
+    };
+    Ok(json!({ "metric": "t_test", "column": column, "value": value }))
+}
+
+fn one_sample(x: &[f64], mu: f64) -> Result<Value> {
+    if x.len() < 2 {
+        return Err(
+            ServerError::invalid("data_analysis", "a t-test needs at least two values").into(),
+        );
+    }
+    let n = x.len() as f64;
+    let estimate = mean(x);
+    let se = (variance(x) / n).sqrt();
+    let df = n - 1.0;
+    let t = (estimate - mu) / se;
+    Ok(json!({
+        "alternative": "two-sided",
+        "statistic": t,
+        "df": df,
+        "p_value": t_p_value(t, df),
+        "n": x.len(),
+        "estimate": estimate,
+        "mu": mu,
+        "confidence_level": CONFIDENCE_LEVEL,
+        "confidence_interval": interval(estimate, se, df),
+    }))
+}
+
+fn two_sample(
+    table: &Table,
+    index: usize,
+    by: &str,
+    mu: f64,
+    equal_variance: bool,
+) -> Result<Value> {
+    let by_index = column_index(table, by)?;
+    let mut groups: Vec<(&Value, Vec<f64>)> = Vec::new();
+    for row in &table.rows {
+        let (Some(x), key) = (row[index].as_f64(), &row[by_index]) else {
+            continue;
+        };
+        if key.is_null() {
+            continue;
+        }
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, values)) => values.push(x),
+            None => groups.push((key, vec![x])),
+        }
+    }
+    let [(key_a, a), (key_b, b)] = groups.as_slice() else {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            format!(
+                "column '{}' must split the rows into two groups, but has {}",
+                by,
+                groups.len()
+            ),
+        )
+        .into());
+    };
+    if a.len() < 2 || b.len() < 2 {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            "a two-sample t-test needs at least two values in each group",
+        )
+        .into());
+    }
+
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let (var_a, var_b) = (variance(a), variance(b));
+    let (se, df) = if equal_variance {
+        let pooled = ((n_a - 1.0) * var_a + (n_b - 1.0) * var_b) / (n_a + n_b - 2.0);
+        ((pooled * (1.0 / n_a + 1.0 / n_b)).sqrt(), n_a + n_b - 2.0)
+    } else {
+        let (s_a, s_b) = (var_a / n_a, var_b / n_b);
+        let df = (s_a + s_b).powi(2) / (s_a.powi(2) / (n_a - 1.0) + s_b.powi(2) / (n_b - 1.0));
+        ((s_a + s_b).sqrt(), df)
+    };
+    let estimate = mean(a) - mean(b);
+    let t = (estimate - mu) / se;
+    let group = |key: &Value, values: &[f64]| {
+        json!({
+            "value": key,
+            "n": values.len(),
+            "mean": mean(values),
+            "stddev": variance(values).sqrt(),
+        })
+    };
+
+    Ok(json!({
+        "method": if equal_variance {
+            "Student's two-sample t-test"
+        } else {
+            "Welch's two-sample t-test"
+        },
+        "alternative": "two-sided",
+        "by": by,
+        "statistic": t,
+        "df": df,
+        "p_value": t_p_value(t, df),
+        "estimate": estimate,
+        "mu": mu,
+        "confidence_level": CONFIDENCE_LEVEL,
+        "confidence_interval": interval(estimate, se, df),
+        "groups": [group(key_a, a), group(key_b, b)],
+    }))
+}
+
+/// Pearson's chi-square test, as a `{metric, column, value}` result: of independence
+/// between `column` and `by`, or of equal frequencies of the values of `column`.
+pub fn chi_square(table: &Table, column: &str, by: Option<&str>) -> Result<Value> {
+    let index = column_index(table, column)?;
+    let value = match by {
+        None => {
+            let mut counts: Vec<(String, usize)> = Vec::new();
+            for value in table.values(index) {
+                let label = text(value);
+                match counts.iter_mut().find(|(l, _)| *l == label) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((label, 1)),
+                }
+            }
+            if counts.len() < 2 {
+                return Err(ServerError::invalid(
+                    "data_analysis",
+                    format!("column '{}' needs at least two distinct values", column),
+                )
+                .into());
+            }
+            let n: usize = counts.iter().map(|(_, c)| c).sum();
+            let expected = n as f64 / counts.len() as f64;
+            let statistic: f64 = counts
+                .iter()
+                .map(|(_, o)| (*o as f64 - expected).powi(2) / expected)
+                .sum();
+            let df = (counts.len() - 1) as f64;
+            json!({
+                "method": "Chi-square goodness-of-fit test against equal frequencies",
+                "statistic": statistic,
+                "df": df,
+                "p_value": chi_square_p_value(statistic, df),
+                "n": n,
+                "expected": expected,
+                "observed": counts
+                    .into_iter()
+                    .map(|(label, count)| (label, json!(count)))
+                    .collect::<Map<_, _>>(),
+            })
+        }
+        Some(by) => {
+            let by_index = column_index(table, by)?;
+            let mut row_labels: Vec<String> = Vec::new();
+            let mut col_labels: Vec<String> = Vec::new();
+            let mut cells: Vec<(usize, usize)> = Vec::new();
+            for row in &table.rows {
+                if row[index].is_null() || row[by_index].is_null() {
+                    continue;
+                }
+                cells.push((
+                    position(&mut row_labels, text(&row[index])),
+                    position(&mut col_labels, text(&row[by_index])),
+                ));
+            }
+            let (r, c) = (row_labels.len(), col_labels.len());
+            if r < 2 || c < 2 {
+                return Err(ServerError::invalid(
+                    "data_analysis",
+                    format!(
+                        "columns '{}' and '{}' need at least two distinct values each",
+                        column, by
+                    ),
+                )
+                .into());
+            }
+            let n = cells.len();
+            let mut observed = vec![vec![0usize; c]; r];
+            for (i, j) in cells {
+                observed[i][j] += 1;
+            }
+            let row_totals: Vec<usize> = observed.iter().map(|row| row.iter().sum()).collect();
+            let col_totals: Vec<usize> = (0..c)
+                .map(|j| observed.iter().map(|row| row[j]).sum())
+                .collect();
+            let mut statistic = 0.0;
+            let mut min_expected = f64::INFINITY;
+            for i in 0..r {
+                for j in 0..c {
+                    let expected = (row_totals[i] * col_totals[j]) as f64 / n as f64;
+                    statistic += (observed[i][j] as f64 - expected).powi(2) / expected;
+                    min_expected = min_expected.min(expected);
+                }
+            }
+            let df = ((r - 1) * (c - 1)) as f64;
+            let table: Map<String, Value> = row_labels
+                .into_iter()
+                .zip(&observed)
+                .map(|(label, counts)| {
+                    let counts: Map<String, Value> = col_labels
+                        .iter()
+                        .cloned()
+                        .zip(counts.iter().map(|c| json!(c)))
+                        .collect();
+                    (label, Value::Object(counts))
+                })
+                .collect();
+            json!({
+                "method": "Pearson's chi-square test of independence",
+                "by": by,
+                "statistic": statistic,
+                "df": df,
+                "p_value": chi_square_p_value(statistic, df),
+                "n": n,
+                "cramers_v": (statistic / (n * (r.min(c) - 1)) as f64).sqrt(),
+                // Below 5, the chi-square approximation becomes unreliable.
+                "min_expected": min_expected,
+                "observed": table,
+            })
+        }
+    };
+    Ok(json!({ "metric": "chi_square", "column": column, "value": value }))
+}
+
+/// Ordinary least squares regression of `y` on `x` with an intercept, as a
+/// `{metric, column, value}` result. Rows missing any of the values are skipped.
+pub fn regression(table: &Table, y: &str, x: &[String]) -> Result<Value> {
+    let y_index = column_index(table, y)?;
+    let x_indices = x
+        .iter()
+        .map(|c| column_index(table, c))
+        .collect::<Result<Vec<_>>>()?;
+    let rows: Vec<(f64, Vec<f64>)> = table
+        .rows
+        .iter()
+        .filter_map(|row| {
+            let terms = std::iter::once(Some(1.0))
+                .chain(x_indices.iter().map(|&i| row[i].as_f64()))
+                .collect::<Option<Vec<f64>>>()?;
+            Some((row[y_index].as_f64()?, terms))
+        })
+        .collect();
+
+    let p = x.len() + 1;
+    let n = rows.len();
+    if n <= p {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            format!(
+                "regression on {} predictors needs more than {} complete rows, but has {}",
+                x.len(),
+                p,
+                n
+            ),
+        )
+        .into());
+    }
+
+    let mut xtx = vec![vec![0.0; p]; p];
+    let mut xty = vec![0.0; p];
+    for (y, terms) in &rows {
+        for a in 0..p {
+            xty[a] += terms[a] * y;
+            for b in 0..p {
+                xtx[a][b] += terms[a] * terms[b];
+            }
+        }
+    }
+    let inverse = invert(xtx).ok_or_else(|| {
+        ServerError::invalid(
+            "data_analysis",
+            "the predictors are collinear, so the coefficients are not unique",
+        )
+    })?;
+    let beta: Vec<f64> = inverse
+        .iter()
+        .map(|row| row.iter().zip(&xty).map(|(a, b)| a * b).sum())
+        .collect();
+
+    let y_mean = rows.iter().map(|(y, _)| y).sum::<f64>() / n as f64;
+    let (mut sse, mut sst) = (0.0, 0.0);
+    for (y, terms) in &rows {
+        let fitted: f64 = terms.iter().zip(&beta).map(|(t, b)| t * b).sum();
+        sse += (y - fitted).powi(2);
+        sst += (y - y_mean).powi(2);
+    }
+    let df_residual = (n - p) as f64;
+    let sigma2 = sse / df_residual;
+    let r_squared = 1.0 - sse / sst;
+    let k = x.len() as f64;
+    let f = ((sst - sse) / k) / sigma2;
+
+    let terms = std::iter::once("intercept").chain(x.iter().map(String::as_str));
+    let coefficients: Vec<Value> = terms
+        .zip(&beta)
+        .enumerate()
+        .map(|(j, (term, &estimate))| {
+            let se = (sigma2 * inverse[j][j]).sqrt();
+            let t = estimate / se;
+            json!({
+                "term": term,
+                "estimate": estimate,
+                "std_error": se,
+                "t_statistic": t,
+                "p_value": t_p_value(t, df_residual),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "metric": "regression",
+        "column": y,
+        "value": {
+            "method": "Ordinary least squares",
+            "n": n,
+            "df_residual": df_residual,
+            "coefficients": coefficients,
+            "r_squared": r_squared,
+            "adj_r_squared": 1.0 - (1.0 - r_squared) * (n - 1) as f64 / df_residual,
+            "residual_std_error": sigma2.sqrt(),
+            "f_statistic": f,
+            "f_p_value": FisherSnedecor::new(k, df_residual).ok().map(|d| d.sf(f)),
+        },
+    }))
+}
+
+/// Numbers of two columns, from the rows where both are numbers.
+fn pairs(table: &Table, a: usize, b: usize) -> (Vec<f64>, Vec<f64>) {
+    table
+        .rows
+        .iter()
+        .filter_map(|row| Some((row[a].as_f64()?, row[b].as_f64()?)))
+        .unzip()
+}
+
+fn mean(x: &[f64]) -> f64 {
+    x.iter().sum::<f64>() / x.len() as f64
+}
+
+/// Sample variance.
+fn variance(x: &[f64]) -> f64 {
+    let m = mean(x);
+    x.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (x.len() as f64 - 1.0)
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    if x.len() < 2 {
+        return None;
+    }
+    let (mx, my) = (mean(x), mean(y));
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mx, b - my);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    (sxx > 0.0 && syy > 0.0).then(|| (sxy / (sxx * syy).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Ranks starting at 1, with tied values sharing their average rank.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        for &i in &order[start..=end] {
+            ranks[i] = rank;
+        }
+        start = end + 1;
+    }
+    ranks
+}
+
+/// Two-sided p-value of a correlation coefficient over `n` pairs, from its t statistic.
+fn correlation_p_value(r: f64, n: usize) -> Option<f64> {
+    if n < 3 {
+        return None;
+    }
+    if r.abs() >= 1.0 {
+        return Some(0.0);
+    }
+    let df = (n - 2) as f64;
+    t_p_value(r * (df / (1.0 - r * r)).sqrt(), df)
+}
+
+/// Two-sided p-value of a t statistic.
+fn t_p_value(t: f64, df: f64) -> Option<f64> {
+    let distribution = StudentsT::new(0.0, 1.0, df).ok()?;
+    t.is_finite().then(|| 2.0 * distribution.sf(t.abs()))
+}
+
+fn chi_square_p_value(statistic: f64, df: f64) -> Option<f64> {
+    Some(ChiSquared::new(df).ok()?.sf(statistic))
+}
+
+/// Confidence interval around `estimate` at [`CONFIDENCE_LEVEL`].
+fn interval(estimate: f64, se: f64, df: f64) -> Option<[f64; 2]> {
+    let distribution = StudentsT::new(0.0, 1.0, df).ok()?;
+    let q = distribution.inverse_cdf(1.0 - (1.0 - CONFIDENCE_LEVEL) / 2.0);
+    Some([estimate - q * se, estimate + q * se])
+}
+
+/// Index of `label` in `labels`, appending it if new.
+fn position(labels: &mut Vec<String>, label: String) -> usize {
+    labels.iter().position(|l| *l == label).unwrap_or_else(|| {
+        labels.push(label);
+        labels.len() - 1
+    })
+}
+
+/// Inverts a square matrix by Gauss-Jordan elimination with partial pivoting;
+/// `None` if it is singular.
+fn invert(mut m: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let size = m.len();
+    let scale = (0..size).map(|i| m[i][i].abs()).fold(0.0, f64::max);
+    let mut inverse: Vec<Vec<f64>> = (0..size)
+        .map(|i| (0..size).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    for col in 0..size {
+        let pivot = (col..size).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        inverse.swap(col, pivot);
+        let divisor = m[col][col];
+        for j in 0..size {
+            m[col][j] /= divisor;
+            inverse[col][j] /= divisor;
+        }
+        for row in 0..size {
+            if row != col {
+                let factor = m[row][col];
+                for j in 0..size {
+                    m[row][j] -= factor * m[col][j];
+                    inverse[row][j] -= factor * inverse[col][j];
+                }
+            }
+        }
+    }
+    Some(inverse)
+}
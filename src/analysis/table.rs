@@ -0,0 +1,251 @@
+// src/analysis/table.rs
+
+use serde_json::{Map, Value};
+
+/// A dataset with named columns, built from tabular JSON.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    /// Reads a table from JSON: an array of objects (one per row), an object of
+    /// equal-length arrays (one per column), or an array of scalars (a single `value` column).
+    /// Returns `None` for any other shape.
+    pub fn from_json(data: &Value) -> Option<Self> {
+        match data {
+            Value::Array(items) if items.iter().all(Value::is_object) => {
+                let mut columns: Vec<String> = Vec::new();
+                for item in items {
+                    for key in item.as_object()?.keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                }
+                let rows = items
+                    .iter()
+                    .map(|item| {
+                        columns
+                            .iter()
+                            .map(|c| item.get(c).cloned().unwrap_or(Value::Null))
+                            .collect()
+                    })
+                    .collect();
+                Some(Self { columns, rows })
+            }
+            Value::Array(items) if items.iter().all(|v| !v.is_array() && !v.is_object()) => {
+                Some(Self {
+                    columns: vec!["value".to_string()],
+                    rows: items.iter().map(|v| vec![v.clone()]).collect(),
+                })
+            }
+            Value::Object(columns) => {
+                let arrays: Vec<&Vec<Value>> = columns
+                    .values()
+                    .map(Value::as_array)
+                    .collect::<Option<_>>()?;
+                let len = arrays.first().map_or(0, |a| a.len());
+                if arrays.iter().any(|a| a.len() != len) {
+                    return None;
+                }
+                Some(Self {
+                    columns: columns.keys().cloned().collect(),
+                    rows: (0..len)
+                        .map(|i| arrays.iter().map(|a| a[i].clone()).collect())
+                        .collect(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The rows as an array of JSON objects.
+    pub fn to_json(&self) -> Value {
+        Value::Array(
+            self.rows
+                .iter()
+                .map(|row| {
+                    let obj: Map<String, Value> = self
+                        .columns
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().cloned())
+                        .collect();
+                    Value::Object(obj)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// The non-null values of a column.
+    pub fn values(&self, index: usize) -> impl Iterator<Item = &Value> {
+        self.rows
+            .iter()
+            .map(move |row| &row[index])
+            .filter(|v| !v.is_null())
+    }
+
+    /// The numeric values of a column, skipping nulls and non-numbers.
+    pub fn numbers(&self, index: usize) -> Vec<f64> {
+        self.values(index).filter_map(Value::as_f64).collect()
+    }
+
+    /// Whether every non-null value of a column is a number, and there is at least one.
+    pub fn is_numeric(&self, index: usize) -> bool {
+        let mut values = self.values(index).peekable();
+        values.peek().is_some() && values.all(Value::is_number)
+    }
+
+    /// The table as a GitHub-flavored Markdown table; nulls are left blank.
+    pub fn to_markdown(&self) -> String {
+        fn cell(value: &Value) -> String {
+            text(value).replace('|', "\\|").replace(['\r', '\n'], " ")
+        }
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        let mut markdown = line(
+            self.columns
+                .iter()
+                .map(|c| cell(&Value::from(c.as_str())))
+                .collect(),
+        );
+        markdown.push_str(&line(vec!["---".to_string(); self.columns.len()]));
+        for row in &self.rows {
+            markdown.push_str(&line(row.iter().map(cell).collect()));
+        }
+        markdown
+    }
+
+    /// The table as CSV with a header row; nulls are empty fields and nested values JSON.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let _ = writer.write_record(&self.columns);
+        for row in &self.rows {
+            let _ = writer.write_record(row.iter().map(text));
+        }
+        let bytes = writer.into_inner().unwrap_or_default();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// The table as plain text in aligned columns, with numbers aligned right. Cells
+    /// longer than `max_width` characters are shortened with an ellipsis.
+    pub fn to_text(&self, max_width: usize) -> String {
+        let shorten = |text: String| {
+            let text = text.replace(['\r', '\n'], " ");
+            if text.chars().count() <= max_width {
+                return text;
+            }
+            let mut short: String = text.chars().take(max_width.saturating_sub(1)).collect();
+            short.push('…');
+            short
+        };
+        let header: Vec<String> = self.columns.iter().map(|c| shorten(c.clone())).collect();
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|v| shorten(text(v))).collect())
+            .collect();
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([header[i].chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let right: Vec<bool> = (0..self.columns.len())
+            .map(|i| self.is_numeric(i))
+            .collect();
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if right[i] {
+                        format!("{:>1$}", cell, widths[i])
+                    } else {
+                        format!("{:<1$}", cell, widths[i])
+                    }
+                })
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+        let mut out = line(&header);
+        let rules: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        out.push_str(&line(&rules));
+        for row in &cells {
+            out.push_str(&line(row));
+        }
+        out
+    }
+}
+
+/// A value as cell text: strings unquoted, nulls empty, anything else as JSON.
+pub(crate) fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The kind of values held by a column, ignoring nulls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Number,
+    Text,
+    Bool,
+    /// Arrays or objects.
+    Nested,
+    /// Values of more than one kind.
+    Mixed,
+    /// Only nulls, or no rows.
+    Empty,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Number(_) => ColumnType::Number,
+            Value::String(_) => ColumnType::Text,
+            Value::Bool(_) => ColumnType::Bool,
+            Value::Null => ColumnType::Empty,
+            Value::Array(_) | Value::Object(_) => ColumnType::Nested,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            ColumnType::Number => "numbers",
+            ColumnType::Text => "text",
+            ColumnType::Bool => "booleans",
+            ColumnType::Nested => "nested values",
+            ColumnType::Mixed => "mixed values",
+            ColumnType::Empty => "only nulls",
+        }
+    }
+}
+
+impl Table {
+    /// Names and kinds of all columns.
+    pub fn schema(&self) -> Vec<(String, ColumnType)> {
+        (0..self.columns.len())
+            .map(|i| (self.columns[i].clone(), self.column_type(i)))
+            .collect()
+    }
+
+    /// The kind of values in a column.
+    pub fn column_type(&self, index: usize) -> ColumnType {
+        self.values(index)
+            .map(ColumnType::of)
+            .reduce(|a, b| if a == b { a } else { ColumnType::Mixed })
+            .unwrap_or(ColumnType::Empty)
+    }
+}
@@ -0,0 +1,397 @@
+// src/analysis/timeseries.rs
+
+use crate::analysis::engine::{aggregate, aggregation_indices, column_index};
+use crate::analysis::ops::{AggregateFn, Aggregation, AnomalyMethod, Interval, TimeUnit};
+use crate::analysis::table::Table;
+use crate::errors::ServerError;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Most intervals a `resample` step may produce.
+const MAX_INTERVALS: i64 = 100_000;
+
+/// Layouts of date-time text without an offset accepted when no format is given.
+const DEFAULT_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+fn not_a_timestamp(value: &Value, column: &str, row: usize) -> String {
+    format!(
+        "Cannot read {} in row {} of column '{}' as a timestamp",
+        value, row, column
+    )
+}
+
+/// Parses an epoch number in `unit`, or date-time text in `format`. Without a format,
+/// RFC 3339 and `YYYY-MM-DD[ HH:MM[:SS]]` text is accepted. Text without an offset
+/// is taken to be in UTC.
+pub fn parse_timestamp(
+    value: &Value,
+    format: Option<&str>,
+    unit: TimeUnit,
+) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(n) => {
+            let millis = match unit {
+                TimeUnit::Seconds => n.as_f64()? * 1000.0,
+                TimeUnit::Milliseconds => n.as_f64()?,
+            };
+            DateTime::from_timestamp_millis(millis.round() as i64)
+        }
+        Value::String(text) => {
+            let text = text.trim();
+            let midnight = |date: NaiveDate| date.and_time(NaiveTime::MIN);
+            match format {
+                Some(format) => DateTime::parse_from_str(text, format)
+                    .map(|t| t.to_utc())
+                    .ok()
+                    .or_else(|| {
+                        NaiveDateTime::parse_from_str(text, format)
+                            .or_else(|_| NaiveDate::parse_from_str(text, format).map(midnight))
+                            .map(|t| t.and_utc())
+                            .ok()
+                    }),
+                None => DateTime::parse_from_rfc3339(text)
+                    .map(|t| t.to_utc())
+                    .ok()
+                    .or_else(|| {
+                        DEFAULT_FORMATS
+                            .iter()
+                            .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+                            .or_else(|| {
+                                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                                    .ok()
+                                    .map(midnight)
+                            })
+                            .map(|t| t.and_utc())
+                    }),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Formats a timestamp as RFC 3339 in UTC, with fractional seconds only if needed.
+pub fn format_timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Replaces the values of `column` with RFC 3339 timestamps, writing them to `name`.
+pub fn parse_time(
+    table: &mut Table,
+    column: &str,
+    format: Option<&str>,
+    unit: TimeUnit,
+    name: String,
+) -> Result<()> {
+    let index = column_index(table, column)?;
+    let values = table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(row, r)| match &r[index] {
+            Value::Null => Ok(Value::Null),
+            value => parse_timestamp(value, format, unit)
+                .map(|t| json!(format_timestamp(t)))
+                .ok_or_else(|| {
+                    ServerError::invalid("data_analysis", not_a_timestamp(value, column, row))
+                        .into()
+                }),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    set_column(table, name, values);
+    Ok(())
+}
+
+/// One row per `every`-long interval of the timestamps in `column`, from the first
+/// to the last, holding the interval start and the aggregates of its rows.
+///
+/// Rows without a timestamp are left out; empty intervals have a count of 0 and
+/// null for other aggregates.
+pub fn resample(
+    table: &Table,
+    column: &str,
+    every: Interval,
+    aggregations: &[Aggregation],
+) -> Result<Table> {
+    let index = column_index(table, column)?;
+    let agg_indices = aggregation_indices(table, aggregations)?;
+    let mut intervals: BTreeMap<i64, Vec<&Vec<Value>>> = BTreeMap::new();
+    for (row, r) in table.rows.iter().enumerate() {
+        if r[index].is_null() {
+            continue;
+        }
+        let time = parse_timestamp(&r[index], None, TimeUnit::Seconds).ok_or_else(|| {
+            ServerError::invalid(
+                "data_analysis",
+                format!(
+                    "{}; convert it with 'parse_time' first",
+                    not_a_timestamp(&r[index], column, row)
+                ),
+            )
+        })?;
+        intervals
+            .entry(time.timestamp_millis().div_euclid(every.millis))
+            .or_default()
+            .push(r);
+    }
+
+    let mut columns = vec![column.to_string()];
+    columns.extend(aggregations.iter().map(Aggregation::output_name));
+    let (Some(&first), Some(&last)) = (intervals.keys().next(), intervals.keys().next_back())
+    else {
+        return Ok(Table {
+            columns,
+            rows: Vec::new(),
+        });
+    };
+    if last - first >= MAX_INTERVALS {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            format!(
+                "Resampling would produce {} intervals, more than the limit of {}; use a longer 'every'",
+                last - first + 1,
+                MAX_INTERVALS
+            ),
+        )
+        .into());
+    }
+
+    let rows = (first..=last)
+        .map(|interval| {
+            let rows = intervals.get(&interval).map_or(&[][..], Vec::as_slice);
+            let start = DateTime::from_timestamp_millis(interval * every.millis);
+            let mut row = vec![start.map_or(Value::Null, |t| json!(format_timestamp(t)))];
+            for (aggregation, index) in aggregations.iter().zip(&agg_indices) {
+                row.push(aggregate(rows, aggregation.func, *index));
+            }
+            row
+        })
+        .collect();
+    Ok(Table { columns, rows })
+}
+
+/// Writes to `name` the `func` of `column` over each row and the `window - 1` rows
+/// before it; null while fewer rows precede.
+pub fn rolling(
+    table: &mut Table,
+    column: &str,
+    window: usize,
+    func: AggregateFn,
+    name: String,
+) -> Result<()> {
+    if window == 0 {
+        return Err(ServerError::invalid("data_analysis", "'window' must be at least 1").into());
+    }
+    let index = column_index(table, column)?;
+    let values = if func == AggregateFn::Median {
+        let rows: Vec<&Vec<Value>> = table.rows.iter().collect();
+        (0..rows.len())
+            .map(|i| match (i + 1).checked_sub(window) {
+                Some(start) => aggregate(&rows[start..=i], func, Some(index)),
+                None => Value::Null,
+            })
+            .collect()
+    } else {
+        let column: Vec<&Value> = table.rows.iter().map(|row| &row[index]).collect();
+        running(&column, window, func)
+    };
+    set_column(table, name, values);
+    Ok(())
+}
+
+/// A rolling `func` other than the median in a single pass: running sums for the
+/// count, sum, mean, and standard deviation, and a monotonic deque for min and max.
+fn running(column: &[&Value], window: usize, func: AggregateFn) -> Vec<Value> {
+    let number = |i: usize| column[i].as_f64();
+    let (mut count, mut n, mut sum, mut squares) = (0, 0, 0.0, 0.0);
+    // Rows that may still become the window's extreme, their numbers running from
+    // the extreme at the front.
+    let mut extremes: VecDeque<(usize, f64)> = VecDeque::new();
+    let mut values = Vec::with_capacity(column.len());
+    for i in 0..column.len() {
+        if let Some(old) = i.checked_sub(window) {
+            count -= usize::from(!column[old].is_null());
+            if let Some(x) = number(old) {
+                n -= 1;
+                sum -= x;
+                squares -= x * x;
+            }
+            if extremes.front().is_some_and(|&(j, _)| j == old) {
+                extremes.pop_front();
+            }
+            if n == 0 {
+                (sum, squares) = (0.0, 0.0);
+            }
+        }
+        count += usize::from(!column[i].is_null());
+        if let Some(x) = number(i) {
+            n += 1;
+            sum += x;
+            squares += x * x;
+            while extremes.back().is_some_and(|&(_, y)| match func {
+                AggregateFn::Max => y <= x,
+                _ => y >= x,
+            }) {
+                extremes.pop_back();
+            }
+            extremes.push_back((i, x));
+        }
+
+        if i + 1 < window {
+            values.push(Value::Null);
+            continue;
+        }
+        let size = n as f64;
+        let value = match func {
+            AggregateFn::Count => json!(count),
+            _ if n == 0 => Value::Null,
+            AggregateFn::Sum => json!(sum),
+            AggregateFn::Mean => json!(sum / size),
+            AggregateFn::Stddev if n < 2 => Value::Null,
+            AggregateFn::Stddev => {
+                let variance = (squares - sum * sum / size) / (size - 1.0);
+                json!(variance.max(0.0).sqrt())
+            }
+            _ => json!(extremes.front().map(|&(_, x)| x)),
+        };
+        values.push(value);
+    }
+    values
+}
+
+/// Writes to `name` the value of `column` `periods` rows earlier, or with `diff`,
+/// the change since then.
+pub fn shift(
+    table: &mut Table,
+    column: &str,
+    periods: i64,
+    diff: bool,
+    name: String,
+) -> Result<()> {
+    let index = column_index(table, column)?;
+    let values = (0..table.rows.len())
+        .map(|i| {
+            let earlier = (i as i64)
+                .checked_sub(periods)
+                .and_then(|j| usize::try_from(j).ok())
+                .and_then(|j| table.rows.get(j))
+                .map_or(Value::Null, |row| row[index].clone());
+            if !diff {
+                return earlier;
+            }
+            let current = &table.rows[i][index];
+            match (current.as_i64(), earlier.as_i64()) {
+                (Some(a), Some(b)) if a.checked_sub(b).is_some() => json!(a - b),
+                _ => match (current.as_f64(), earlier.as_f64()) {
+                    (Some(a), Some(b)) => json!(a - b),
+                    _ => Value::Null,
+                },
+            }
+        })
+        .collect();
+    set_column(table, name, values);
+    Ok(())
+}
+
+/// Outliers of a numeric column as a `{metric, column, value}` result, listing the
+/// bounds used and each anomalous row with its position in the table.
+pub fn anomalies(
+    table: &Table,
+    column: &str,
+    method: AnomalyMethod,
+    threshold: Option<f64>,
+) -> Result<Value> {
+    let index = column_index(table, column)?;
+    let numbers = table.numbers(index);
+    let mut value = Map::new();
+    let (lower, upper) = match method {
+        AnomalyMethod::ZScore => {
+            let threshold = threshold.unwrap_or(3.0);
+            let (Some(mean), Some(stddev)) = (
+                AggregateFn::Mean.apply(&numbers),
+                AggregateFn::Stddev.apply(&numbers),
+            ) else {
+                return Err(ServerError::invalid(
+                    "data_analysis",
+                    "z-score anomaly detection needs at least two values",
+                )
+                .into());
+            };
+            value.insert("method".to_string(), json!("z-score"));
+            value.insert("threshold".to_string(), json!(threshold));
+            value.insert("mean".to_string(), json!(mean));
+            value.insert("stddev".to_string(), json!(stddev));
+            (mean - threshold * stddev, mean + threshold * stddev)
+        }
+        AnomalyMethod::Iqr => {
+            let threshold = threshold.unwrap_or(1.5);
+            if numbers.is_empty() {
+                return Err(ServerError::invalid(
+                    "data_analysis",
+                    format!("Column '{}' has no numbers", column),
+                )
+                .into());
+            }
+            let mut sorted = numbers;
+            sorted.sort_by(f64::total_cmp);
+            let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+            value.insert("method".to_string(), json!("interquartile range"));
+            value.insert("threshold".to_string(), json!(threshold));
+            value.insert("q1".to_string(), json!(q1));
+            value.insert("q3".to_string(), json!(q3));
+            value.insert("iqr".to_string(), json!(q3 - q1));
+            (q1 - threshold * (q3 - q1), q3 + threshold * (q3 - q1))
+        }
+    };
+
+    let found: Vec<Value> = table
+        .rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row[index].as_f64().is_some_and(|x| x < lower || x > upper))
+        .map(|(i, row)| {
+            let record: Map<String, Value> = table
+                .columns
+                .iter()
+                .cloned()
+                .zip(row.iter().cloned())
+                .collect();
+            json!({ "index": i, "value": row[index], "row": record })
+        })
+        .collect();
+    value.insert("lower_bound".to_string(), json!(lower));
+    value.insert("upper_bound".to_string(), json!(upper));
+    value.insert("count".to_string(), json!(found.len()));
+    value.insert("anomalies".to_string(), Value::Array(found));
+    Ok(json!({ "metric": "anomalies", "column": column, "value": value }))
+}
+
+/// Quantile `p` of sorted numbers, interpolating linearly between neighbours.
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let position = (sorted.len() - 1) as f64 * p;
+    let (lo, hi) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lo] + (position - lo as f64) * (sorted[hi] - sorted[lo])
+}
+
+/// Writes `values` to column `name`, replacing it if it exists or appending it otherwise.
+fn set_column(table: &mut Table, name: String, values: Vec<Value>) {
+    match table.column_index(&name) {
+        Some(index) => {
+            for (row, value) in table.rows.iter_mut().zip(values) {
+                row[index] = value;
+            }
+        }
+        None => {
+            table.columns.push(name);
+            for (row, value) in table.rows.iter_mut().zip(values) {
+                row.push(value);
+            }
+        }
+    }
+}
@@ -0,0 +1,439 @@
+// src/analysis/validate.rs
+
+use crate::analysis::ops::{AggregateFn, Aggregation, Comparison, Op};
+use crate::analysis::table::ColumnType;
+use crate::errors::ServerError;
+use anyhow::Result;
+use serde_json::Value;
+
+/// Checks a pipeline against the named and typed columns of a dataset before anything runs.
+///
+/// Each step is checked against the columns produced by the steps before it: column
+/// references must exist and statistics need numeric columns. Errors name the
+/// offending step, e.g. `ops[2] (mean): column 'region' holds text, not numbers`.
+/// Returns the ops with omitted statistic columns filled in.
+pub fn validate(columns: Vec<(String, ColumnType)>, ops: Vec<Op>) -> Result<Vec<Op>> {
+    let mut schema = Schema { columns };
+    ops.into_iter()
+        .enumerate()
+        .map(|(i, op)| {
+            let name = op.name();
+            schema.check(op).map_err(|details| {
+                ServerError::invalid(
+                    "data_analysis",
+                    format!("ops[{}] ({}): {}", i, name, details),
+                )
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// Names and types of the columns at a point in the pipeline.
+struct Schema {
+    columns: Vec<(String, ColumnType)>,
+}
+
+impl Schema {
+    fn get(&self, column: &str) -> Result<ColumnType, String> {
+        self.columns
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, ty)| *ty)
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.columns.iter().map(|(n, _)| n.as_str()).collect();
+                format!(
+                    "unknown column '{}'; available columns: {}",
+                    column,
+                    names.join(", ")
+                )
+            })
+    }
+
+    fn require_numeric(&self, column: &str) -> Result<(), String> {
+        match self.get(column)? {
+            ColumnType::Number | ColumnType::Empty => Ok(()),
+            other => Err(format!(
+                "column '{}' holds {}, not numbers",
+                column,
+                other.describe()
+            )),
+        }
+    }
+
+    /// The column a statistic applies to: the given one, or the only numeric column.
+    fn numeric_column(&self, column: Option<String>) -> Result<String, String> {
+        if let Some(column) = column {
+            self.require_numeric(&column)?;
+            return Ok(column);
+        }
+        let numeric: Vec<&String> = self
+            .columns
+            .iter()
+            .filter(|(_, ty)| *ty == ColumnType::Number)
+            .map(|(name, _)| name)
+            .collect();
+        match numeric.as_slice() {
+            [only] => Ok(only.to_string()),
+            [] => Err("the data has no numeric column".to_string()),
+            several => Err(format!(
+                "'column' is required when the data has several numeric columns ({})",
+                several
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Checks one step and updates the schema to what it produces.
+    fn check(&mut self, op: Op) -> Result<Op, String> {
+        let output = op.output_column().unwrap_or_default();
+        Ok(match op {
+            Op::Describe { columns } => {
+                for column in columns.iter().flatten() {
+                    self.get(column)?;
+                }
+                Op::Describe { columns }
+            }
+            Op::Count { column } => {
+                if let Some(column) = &column {
+                    self.get(column)?;
+                }
+                Op::Count { column }
+            }
+            Op::Sum { column } => Op::Sum {
+                column: Some(self.numeric_column(column)?),
+            },
+            Op::Mean { column } => Op::Mean {
+                column: Some(self.numeric_column(column)?),
+            },
+            Op::Median { column } => Op::Median {
+                column: Some(self.numeric_column(column)?),
+            },
+            Op::Stddev { column } => Op::Stddev {
+                column: Some(self.numeric_column(column)?),
+            },
+            Op::Min { column } => Op::Min {
+                column: Some(self.numeric_column(column)?),
+            },
+            Op::Max { column } => Op::Max {
+                column: Some(self.numeric_column(column)?),
+            },
+            Op::Filter { column, cmp, value } => {
+                self.check_filter(&column, cmp, &value)?;
+                Op::Filter { column, cmp, value }
+            }
+            Op::Sort { by, descending } => {
+                if by.is_empty() {
+                    return Err("'by' needs at least one column".to_string());
+                }
+                for column in &by {
+                    self.get(column)?;
+                }
+                Op::Sort { by, descending }
+            }
+            Op::Select { columns } => {
+                let mut selected = Vec::new();
+                for column in &columns {
+                    if selected.iter().any(|(name, _)| name == column) {
+                        return Err(format!("column '{}' is selected twice", column));
+                    }
+                    selected.push((column.clone(), self.get(column)?));
+                }
+                self.columns = selected;
+                Op::Select { columns }
+            }
+            Op::Limit { n } => Op::Limit { n },
+            Op::GroupBy { by, aggregations } => {
+                self.check_grouping(&by, &aggregations)?;
+                Op::GroupBy { by, aggregations }
+            }
+            Op::Aggregate { aggregations } => {
+                self.check_grouping(&[], &aggregations)?;
+                Op::Aggregate { aggregations }
+            }
+            Op::Correlation { columns, method } => {
+                let columns = match columns {
+                    Some(columns) => {
+                        for column in &columns {
+                            self.require_numeric(column)?;
+                        }
+                        columns
+                    }
+                    None => self
+                        .columns
+                        .iter()
+                        .filter(|(_, ty)| *ty == ColumnType::Number)
+                        .map(|(name, _)| name.clone())
+                        .collect(),
+                };
+                if columns.len() < 2 {
+                    return Err("needs at least two numeric columns".to_string());
+                }
+                Op::Correlation {
+                    columns: Some(columns),
+                    method,
+                }
+            }
+            Op::TTest {
+                column,
+                by,
+                paired_with,
+                mu,
+                equal_variance,
+            } => {
+                self.require_numeric(&column)?;
+                match (&by, &paired_with) {
+                    (Some(_), Some(_)) => {
+                        return Err("pass either 'by' or 'paired_with', not both".to_string())
+                    }
+                    (Some(by), None) => {
+                        self.get(by)?;
+                    }
+                    (None, Some(other)) => self.require_numeric(other)?,
+                    (None, None) => {}
+                }
+                Op::TTest {
+                    column,
+                    by,
+                    paired_with,
+                    mu,
+                    equal_variance,
+                }
+            }
+            Op::ChiSquare { column, by } => {
+                self.get(&column)?;
+                if let Some(by) = &by {
+                    self.get(by)?;
+                }
+                Op::ChiSquare { column, by }
+            }
+            Op::Regression { y, x } => {
+                self.require_numeric(&y)?;
+                if x.is_empty() {
+                    return Err("'x' needs at least one column".to_string());
+                }
+                for (i, column) in x.iter().enumerate() {
+                    self.require_numeric(column)?;
+                    if *column == y || x[..i].contains(column) {
+                        return Err(format!("column '{}' appears twice in the model", column));
+                    }
+                }
+                Op::Regression { y, x }
+            }
+            Op::ParseTime {
+                column,
+                format,
+                unit,
+                alias,
+            } => {
+                self.require_time(&column)?;
+                // Replacing the column keeps its place, as the engine does.
+                match self.columns.iter().position(|(name, _)| *name == output) {
+                    Some(i) if output == column => self.columns[i].1 = ColumnType::Text,
+                    _ => self.add_column(output, ColumnType::Text)?,
+                }
+                Op::ParseTime {
+                    column,
+                    format,
+                    unit,
+                    alias,
+                }
+            }
+            Op::Resample {
+                column,
+                every,
+                aggregations,
+            } => {
+                self.require_time(&column)?;
+                self.check_grouping(std::slice::from_ref(&column), &aggregations)?;
+                self.columns[0].1 = ColumnType::Text;
+                Op::Resample {
+                    column,
+                    every,
+                    aggregations,
+                }
+            }
+            Op::Rolling {
+                column,
+                window,
+                func,
+                alias,
+            } => {
+                self.require_numeric(&column)?;
+                if window == 0 {
+                    return Err("'window' must be at least 1".to_string());
+                }
+                self.add_column(output, ColumnType::Number)?;
+                Op::Rolling {
+                    column,
+                    window,
+                    func,
+                    alias,
+                }
+            }
+            Op::Lag {
+                column,
+                periods,
+                alias,
+            } => {
+                let column_type = self.get(&column)?;
+                self.add_column(output, column_type)?;
+                Op::Lag {
+                    column,
+                    periods,
+                    alias,
+                }
+            }
+            Op::Diff {
+                column,
+                periods,
+                alias,
+            } => {
+                self.require_numeric(&column)?;
+                self.add_column(output, ColumnType::Number)?;
+                Op::Diff {
+                    column,
+                    periods,
+                    alias,
+                }
+            }
+            Op::Anomalies {
+                column,
+                method,
+                threshold,
+            } => {
+                self.require_numeric(&column)?;
+                if threshold.is_some_and(|t| !(t.is_finite() && t > 0.0)) {
+                    return Err("'threshold' must be a positive number".to_string());
+                }
+                Op::Anomalies {
+                    column,
+                    method,
+                    threshold,
+                }
+            }
+        })
+    }
+
+    /// Checks that a column can hold timestamps: date-time text or epoch numbers.
+    fn require_time(&self, column: &str) -> Result<(), String> {
+        match self.get(column)? {
+            ColumnType::Text | ColumnType::Number | ColumnType::Empty => Ok(()),
+            other => Err(format!(
+                "column '{}' holds {}, not timestamps",
+                column,
+                other.describe()
+            )),
+        }
+    }
+
+    /// Adds a column written by a step, refusing to overwrite an existing one.
+    fn add_column(&mut self, name: String, column_type: ColumnType) -> Result<(), String> {
+        if self.columns.iter().any(|(n, _)| *n == name) {
+            return Err(format!(
+                "output column '{}' already exists; set 'as' to rename it",
+                name
+            ));
+        }
+        self.columns.push((name, column_type));
+        Ok(())
+    }
+
+    fn check_filter(&self, column: &str, cmp: Comparison, value: &Value) -> Result<(), String> {
+        let column_type = self.get(column)?;
+        let value_type = match value {
+            Value::Number(_) => ColumnType::Number,
+            Value::String(_) => ColumnType::Text,
+            Value::Bool(_) => ColumnType::Bool,
+            Value::Null => ColumnType::Empty,
+            _ => ColumnType::Nested,
+        };
+        match cmp {
+            Comparison::In if !value.is_array() => {
+                Err("'in' needs an array of values to compare with".to_string())
+            }
+            Comparison::Contains
+                if !matches!(
+                    column_type,
+                    ColumnType::Text | ColumnType::Nested | ColumnType::Empty
+                ) =>
+            {
+                Err(format!(
+                    "'contains' needs a text or array column, but '{}' holds {}",
+                    column,
+                    column_type.describe()
+                ))
+            }
+            Comparison::Contains
+                if column_type == ColumnType::Text && value_type != ColumnType::Text =>
+            {
+                Err(format!(
+                    "'contains' on text column '{}' needs a text value",
+                    column
+                ))
+            }
+            cmp if cmp.is_ordering()
+                && !matches!(
+                    value_type,
+                    ColumnType::Number | ColumnType::Text | ColumnType::Bool
+                ) =>
+            {
+                Err("ordering comparisons need a number, text, or boolean value".to_string())
+            }
+            cmp if cmp.is_ordering()
+                && column_type != ColumnType::Empty
+                && column_type != value_type =>
+            {
+                Err(format!(
+                    "cannot compare column '{}', which holds {}, with {}",
+                    column,
+                    column_type.describe(),
+                    value_type.describe()
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_grouping(
+        &mut self,
+        by: &[String],
+        aggregations: &[Aggregation],
+    ) -> Result<(), String> {
+        let mut output = Vec::new();
+        for column in by {
+            output.push((column.clone(), self.get(column)?));
+        }
+        for (i, aggregation) in aggregations.iter().enumerate() {
+            match (&aggregation.column, aggregation.func) {
+                (Some(column), AggregateFn::Count) => {
+                    self.get(column)?;
+                }
+                (Some(column), _) => self
+                    .require_numeric(column)
+                    .map_err(|e| format!("aggregations[{}]: {}", i, e))?,
+                (None, AggregateFn::Count) => {}
+                (None, func) => {
+                    return Err(format!(
+                        "aggregations[{}]: '{}' needs a 'column'",
+                        i,
+                        func.name()
+                    ))
+                }
+            }
+            let name = aggregation.output_name();
+            if output.iter().any(|(n, _)| *n == name) {
+                return Err(format!(
+                    "aggregations[{}]: output column '{}' already exists; set 'as' to rename it",
+                    i, name
+                ));
+            }
+            output.push((name, ColumnType::Number));
+        }
+        self.columns = output;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+// src/api/backend.rs
+
+use crate::api::client::ApiClient;
+use crate::api::mock::MockBackend;
+use crate::api::ollama::OllamaBackend;
+use crate::api::openai::OpenAiBackend;
+use crate::api::privacy::{AnonymizingBackend, PrivacyPolicy};
+use crate::api::router::RoutingBackend;
+use crate::config::{BackendKind, Config};
+use crate::errors::ServerError;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A service that runs model inference and lists the models it offers.
+#[async_trait]
+pub trait InferenceBackend: Send + Sync {
+    /// Runs `prompt` through `model`; the result mirrors the AION-R `/api/v1/infer` response.
+    async fn run_inference(
+        &self,
+        model: &str,
+        prompt: &str,
+        params: &Option<Value>,
+    ) -> Result<Value>;
+
+    /// Returns the model catalog as a JSON array of model descriptions.
+    async fn list_models(&self) -> Result<Value>;
+}
+
+/// A service that runs data analysis operations over a dataset.
+#[async_trait]
+pub trait AnalysisBackend: Send + Sync {
+    /// Applies `ops` to `data`; the result mirrors the AION-R `/api/v1/analyze` response.
+    async fn data_analysis(&self, data: &Value, ops: &Value) -> Result<Value>;
+
+    /// The data as `data_analysis` would send it off the machine, e.g. after
+    /// anonymizing it. Budgets on outgoing data are measured on this.
+    fn outgoing_data<'a>(&self, data: &'a Value) -> Result<Cow<'a, Value>> {
+        Ok(Cow::Borrowed(data))
+    }
+
+    /// Reads the raw contents of the dataset `aion-r://datasets/<id>`, failing if they
+    /// exceed `max_bytes`.
+    async fn read_dataset(&self, id: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let _ = max_bytes;
+        Err(ServerError::ToolError(format!(
+            "This analysis backend does not serve datasets (requested '{}')",
+            id
+        ))
+        .into())
+    }
+}
+
+/// The backends the MCP layer talks to.
+#[derive(Clone)]
+pub struct Backends {
+    pub inference: Arc<dyn InferenceBackend>,
+    pub analysis: Arc<dyn AnalysisBackend>,
+}
+
+impl Backends {
+    /// Builds the backends selected by the configuration.
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let timeout = Duration::from_secs(config.request_timeout);
+        let client = Arc::new(ApiClient::new(config).await?);
+        let mock = match config.backend {
+            BackendKind::Mock => Some(Arc::new(MockBackend::from_file(
+                config.mock_scenario.as_deref(),
+            )?)),
+            _ => None,
+        };
+        let analysis: Arc<dyn AnalysisBackend> = match &mock {
+            Some(mock) => mock.clone(),
+            None => client.clone(),
+        };
+        let analysis: Arc<dyn AnalysisBackend> = match &config.privacy_policy {
+            Some(path) => Arc::new(AnonymizingBackend::new(
+                analysis,
+                PrivacyPolicy::from_file(path)?,
+            )),
+            None => analysis,
+        };
+        let inference: Arc<dyn InferenceBackend> = match (&config.routes, config.backend) {
+            (Some(routes), _) => Arc::new(RoutingBackend::from_file(routes, timeout)?),
+            (None, BackendKind::AionR) => client,
+            (None, BackendKind::OpenAi) => Arc::new(OpenAiBackend::new(
+                &config.openai_api_url,
+                config.openai_api_key.as_deref(),
+                timeout,
+            )?),
+            (None, BackendKind::Ollama) => {
+                Arc::new(OllamaBackend::new(&config.ollama_url, None, timeout)?)
+            }
+            (None, BackendKind::Mock) => mock.unwrap_or_default(),
+        };
+        Ok(Self {
+            inference,
+            analysis,
+        })
+    }
+}
@@ -1,8 +1,11 @@
 // src/api/client.rs
 
+use crate::api::backend::{AnalysisBackend, InferenceBackend};
 use crate::config::Config;
 use crate::errors::ServerError;
+use crate::util;
 use anyhow::Result;
+use async_trait::async_trait;
 use reqwest::{header, Client, Response};
 use serde_json::Value;
 use std::time::Duration;
@@ -15,40 +18,61 @@ pub struct ApiClient {
 
 impl ApiClient {
     pub async fn new(config: &Config) -> Result<Self> {
-        let mut headers = header::HeaderMap::new();
-
-        if let Some(api_key) = &config.aion_r_api_key {
-            let mut auth_value = header::HeaderValue::from_str(&format!("Bearer {}", api_key))?;
-            auth_value.set_sensitive(true);
-            headers.insert(header::AUTHORIZATION, auth_value);
-        }
-
-        let client = Client::builder()
-            .default_headers(headers)
-            .timeout(Duration::from_secs(60))
-            .build()?;
+        Self::with_url(
+            &config.aion_r_api_url,
+            config.aion_r_api_key.as_deref(),
+            Duration::from_secs(config.request_timeout),
+        )
+    }
 
+    /// Creates a client for an AION-R API at `api_url`, authenticating with `api_key`
+    /// and giving up on requests after `timeout`.
+    pub fn with_url(api_url: &str, api_key: Option<&str>, timeout: Duration) -> Result<Self> {
         Ok(Self {
-            client,
-            api_url: config.aion_r_api_url.clone(),
+            client: http_client(api_key, timeout)?,
+            api_url: api_url.trim_end_matches('/').to_string(),
         })
     }
+}
 
-    async fn handle_response(response: Response) -> Result<Value> {
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json::<Value>().await?)
-        } else {
-            let error_body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "<failed to read error body>".to_string());
-            let msg = format!("API request failed with status {}: {}", status, error_body);
-            Err(ServerError::ToolError(msg).into())
-        }
+/// Builds an HTTP client that sends `api_key` as a bearer token on every request
+/// and fails requests that take longer than `timeout`.
+pub(crate) fn http_client(api_key: Option<&str>, timeout: Duration) -> Result<Client> {
+    let mut headers = header::HeaderMap::new();
+
+    if let Some(api_key) = api_key {
+        let mut auth_value = header::HeaderValue::from_str(&format!("Bearer {}", api_key))?;
+        auth_value.set_sensitive(true);
+        headers.insert(header::AUTHORIZATION, auth_value);
     }
 
-    pub async fn run_inference(
+    let client = Client::builder()
+        .default_headers(headers)
+        .timeout(timeout)
+        .build()?;
+    Ok(client)
+}
+
+/// Returns the JSON body of a successful response, or a tool error with the body
+/// of a failed one.
+pub(crate) async fn handle_response(response: Response) -> Result<Value> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response.json::<Value>().await?)
+    } else {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<failed to read error body>".to_string());
+        let msg = format!("API request failed with status {}: {}", status, error_body);
+        Err(ServerError::ToolError(msg).into())
+    }
+}
+
+/// The AION-R HTTP API, which serves both inference and analysis.
+#[async_trait]
+impl InferenceBackend for ApiClient {
+    async fn run_inference(
         &self,
         model: &str,
         prompt: &str,
@@ -62,10 +86,20 @@ impl ApiClient {
         });
 
         let response = self.client.post(&url).json(&body).send().await?;
-        Self::handle_response(response).await
+        handle_response(response).await
     }
 
-    pub async fn data_analysis(&self, data: &Value, ops: &Value) -> Result<Value> {
+    async fn list_models(&self) -> Result<Value> {
+        // This is an example, the prompt didn't specify a concrete endpoint for this
+        let url = format!("{}/api/v1/models", self.api_url);
+        let response = self.client.get(&url).send().await?;
+        handle_response(response).await
+    }
+}
+
+#[async_trait]
+impl AnalysisBackend for ApiClient {
+    async fn data_analysis(&self, data: &Value, ops: &Value) -> Result<Value> {
         let url = format!("{}/api/v1/analyze", self.api_url);
         let body = serde_json::json!({
             "data": data,
@@ -73,13 +107,26 @@ impl ApiClient {
         });
 
         let response = self.client.post(&url).json(&body).send().await?;
-        Self::handle_response(response).await
+        handle_response(response).await
     }
 
-    pub async fn list_models(&self) -> Result<Value> {
-        // This is an example, the prompt didn't specify a concrete endpoint for this
-        let url = format!("{}/api/v1/models", self.api_url);
+    async fn read_dataset(&self, id: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let url = format!("{}/api/v1/datasets/{}", self.api_url, id);
         let response = self.client.get(&url).send().await?;
-        Self::handle_response(response).await
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<failed to read error body>".to_string());
+            let msg = format!("API request failed with status {}: {}", status, error_body);
+            return Err(ServerError::ToolError(msg).into());
+        }
+
+        let what = format!("aion-r://datasets/{}", id);
+        if response.content_length().is_some_and(|len| len > max_bytes) {
+            return Err(util::size_limit_error(&what, max_bytes));
+        }
+        util::collect_limited(response.bytes_stream(), max_bytes, &what).await
     }
 }
@@ -0,0 +1,274 @@
+// src/api/mock.rs
+
+use crate::api::backend::{AnalysisBackend, InferenceBackend};
+use crate::api::router::glob_match;
+use crate::errors::ServerError;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// A scripted scenario for the mock backend, loaded from `--mock-scenario`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MockScenario {
+    /// Model catalog; a small fixed catalog is used if omitted.
+    #[serde(default)]
+    pub models: Option<Value>,
+    /// Rules for `run_inference`, tried in order.
+    #[serde(default)]
+    pub inference: Vec<MockRule>,
+    /// Rules for `data_analysis`, tried in order.
+    #[serde(default)]
+    pub analysis: Vec<MockRule>,
+    /// Datasets served as `aion-r://datasets/<id>`; strings are served as text, other
+    /// values as JSON.
+    #[serde(default)]
+    pub datasets: HashMap<String, Value>,
+}
+
+/// One scripted reply. A rule without conditions matches every call.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MockRule {
+    /// Glob the model name must match (inference only).
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Text the prompt must contain (inference only).
+    #[serde(default)]
+    pub prompt_contains: Option<String>,
+    /// Name of an op the request must contain (analysis only).
+    #[serde(default)]
+    pub op: Option<String>,
+    /// Delay before replying.
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Fail the call with this message instead of replying.
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Inference output text.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Inference output delivered as a stream of chunks, `chunk_delay_ms` apart.
+    #[serde(default)]
+    pub chunks: Option<Vec<String>>,
+    #[serde(default)]
+    pub chunk_delay_ms: u64,
+    /// Complete response returned verbatim.
+    #[serde(default)]
+    pub response: Option<Value>,
+}
+
+impl MockRule {
+    fn matches_inference(&self, model: &str, prompt: &str) -> bool {
+        self.model.as_deref().is_none_or(|g| glob_match(g, model))
+            && self
+                .prompt_contains
+                .as_deref()
+                .is_none_or(|text| prompt.contains(text))
+    }
+
+    fn matches_analysis(&self, ops: &Value) -> bool {
+        self.op.as_deref().is_none_or(|op| {
+            ops.as_array()
+                .is_some_and(|ops| ops.iter().any(|o| op_name(o) == Some(op)))
+        })
+    }
+
+    /// Waits for the configured latency, then fails if the rule scripts an error.
+    async fn apply_latency_and_error(&self) -> Result<()> {
+        if self.latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.latency_ms)).await;
+        }
+        match &self.error {
+            Some(message) => Err(ServerError::ToolError(format!(
+                "API request failed with status 500 Internal Server Error: {}",
+                message
+            ))
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An offline backend answering from deterministic fixtures or a scripted scenario.
+#[derive(Default)]
+pub struct MockBackend {
+    scenario: MockScenario,
+}
+
+impl MockBackend {
+    pub fn new(scenario: MockScenario) -> Self {
+        Self { scenario }
+    }
+
+    /// Loads the scenario from a JSON file, or uses the built-in fixtures if `path` is `None`.
+    pub fn from_file(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ServerError::ConfigError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let scenario = serde_json::from_str(&contents).map_err(|e| {
+            ServerError::ConfigError(format!("Invalid mock scenario {}: {}", path.display(), e))
+        })?;
+        Ok(Self::new(scenario))
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for MockBackend {
+    async fn run_inference(
+        &self,
+        model: &str,
+        prompt: &str,
+        _params: &Option<Value>,
+    ) -> Result<Value> {
+        let rule = self
+            .scenario
+            .inference
+            .iter()
+            .find(|rule| rule.matches_inference(model, prompt));
+        if let Some(rule) = rule {
+            rule.apply_latency_and_error().await?;
+            if let Some(response) = &rule.response {
+                return Ok(response.clone());
+            }
+        }
+
+        let (output, chunks) = match rule.and_then(|r| r.chunks.as_ref().map(|c| (r, c))) {
+            Some((rule, chunks)) => {
+                let mut output = String::new();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    if i > 0 && rule.chunk_delay_ms > 0 {
+                        tokio::time::sleep(Duration::from_millis(rule.chunk_delay_ms)).await;
+                    }
+                    output.push_str(chunk);
+                }
+                (output, Some(chunks.len()))
+            }
+            None => (
+                rule.and_then(|r| r.output.clone())
+                    .unwrap_or_else(|| format!("Mock response from {} to: {}", model, prompt)),
+                None,
+            ),
+        };
+
+        let mut result = json!({
+            "status": "success",
+            "inference_id": format!("mock-{:016x}", fnv1a(&[model, prompt])),
+            "model": model,
+            "output": output,
+            "finish_reason": "stop",
+            "usage": {
+                "prompt_tokens": prompt.split_whitespace().count(),
+                "completion_tokens": output.split_whitespace().count(),
+            },
+        });
+        if let Some(chunks) = chunks {
+            result["chunks"] = json!(chunks);
+        }
+        Ok(result)
+    }
+
+    async fn list_models(&self) -> Result<Value> {
+        Ok(self.scenario.models.clone().unwrap_or_else(|| {
+            json!([
+                { "id": "mock-small", "name": "mock-small" },
+                { "id": "mock-large", "name": "mock-large" }
+            ])
+        }))
+    }
+}
+
+#[async_trait]
+impl AnalysisBackend for MockBackend {
+    async fn data_analysis(&self, data: &Value, ops: &Value) -> Result<Value> {
+        let rule = self
+            .scenario
+            .analysis
+            .iter()
+            .find(|rule| rule.matches_analysis(ops));
+        if let Some(rule) = rule {
+            rule.apply_latency_and_error().await?;
+            if let Some(response) = &rule.response {
+                return Ok(response.clone());
+            }
+        }
+
+        let numbers: Vec<f64> = data
+            .as_array()
+            .map(|items| items.iter().filter_map(Value::as_f64).collect())
+            .unwrap_or_default();
+        let mut results = serde_json::Map::new();
+        for op in ops.as_array().into_iter().flatten() {
+            if let Some(name) = op_name(op) {
+                results.insert(name.to_string(), fixture_stat(name, &numbers));
+            }
+        }
+
+        Ok(json!({
+            "status": "success",
+            "analysis_id": format!("mock-{:016x}", fnv1a(&[&data.to_string(), &ops.to_string()])),
+            "results": results,
+        }))
+    }
+
+    async fn read_dataset(&self, id: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let bytes = match self.scenario.datasets.get(id) {
+            Some(Value::String(text)) => text.clone().into_bytes(),
+            Some(value) => serde_json::to_vec(value)?,
+            None => {
+                return Err(ServerError::ToolError(format!(
+                    "API request failed with status 404 Not Found: no dataset '{}'",
+                    id
+                ))
+                .into())
+            }
+        };
+        if bytes.len() as u64 > max_bytes {
+            return Err(crate::util::size_limit_error(
+                &format!("aion-r://datasets/{}", id),
+                max_bytes,
+            ));
+        }
+        Ok(bytes)
+    }
+}
+
+/// The name of an op given either as a string or as an object with an `op` field.
+fn op_name(op: &Value) -> Option<&str> {
+    op.as_str().or_else(|| op.get("op").and_then(Value::as_str))
+}
+
+/// Computes simple statistics over numeric data; other ops yield `null`.
+fn fixture_stat(name: &str, numbers: &[f64]) -> Value {
+    if numbers.is_empty() {
+        return Value::Null;
+    }
+    let sum: f64 = numbers.iter().sum();
+    match name {
+        "count" => json!(numbers.len()),
+        "sum" => json!(sum),
+        "mean" => json!(sum / numbers.len() as f64),
+        "min" => json!(numbers.iter().cloned().fold(f64::INFINITY, f64::min)),
+        "max" => json!(numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max)),
+        _ => Value::Null,
+    }
+}
+
+/// Stable 64-bit FNV-1a hash, used for deterministic ids.
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
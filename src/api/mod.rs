@@ -1 +1,7 @@
+pub mod backend;
 pub mod client;
+pub mod mock;
+pub mod ollama;
+pub mod openai;
+pub mod privacy;
+pub mod router;
@@ -0,0 +1,196 @@
+// src/api/ollama.rs
+
+use crate::api::backend::InferenceBackend;
+use crate::api::client::{handle_response, http_client};
+use crate::errors::ServerError;
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::Client;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// `params` keys sent as top-level request fields rather than as model `options`.
+const REQUEST_PARAMS: &[&str] = &["format", "keep_alive", "template", "raw"];
+
+/// `params` keys interpreted by this backend.
+const RESERVED_PARAMS: &[&str] = &["mode", "system", "stream", "options"];
+
+/// An inference backend for a local Ollama-style API.
+///
+/// `run_inference` uses `/api/chat`, or `/api/generate` when `params.mode` is
+/// `"generate"`, and reads the NDJSON stream the server replies with. Remaining
+/// `params` become Ollama `options`; `max_tokens` is mapped to `num_predict`.
+#[derive(Debug, Clone)]
+pub struct OllamaBackend {
+    client: Client,
+    api_url: String,
+}
+
+impl OllamaBackend {
+    pub fn new(api_url: &str, api_key: Option<&str>, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: http_client(api_key, timeout)?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for OllamaBackend {
+    async fn run_inference(
+        &self,
+        model: &str,
+        prompt: &str,
+        params: &Option<Value>,
+    ) -> Result<Value> {
+        let params = params.as_ref().and_then(Value::as_object);
+        let mode = params
+            .and_then(|p| p.get("mode"))
+            .and_then(Value::as_str)
+            .unwrap_or("chat");
+        let system = params.and_then(|p| p.get("system")).and_then(Value::as_str);
+
+        let mut body = Map::new();
+        body.insert("model".to_string(), json!(model));
+        body.insert("stream".to_string(), json!(true));
+        let endpoint = match mode {
+            "chat" => {
+                let mut messages = Vec::new();
+                if let Some(system) = system {
+                    messages.push(json!({ "role": "system", "content": system }));
+                }
+                messages.push(json!({ "role": "user", "content": prompt }));
+                body.insert("messages".to_string(), Value::Array(messages));
+                "chat"
+            }
+            "generate" | "completion" => {
+                body.insert("prompt".to_string(), json!(prompt));
+                if let Some(system) = system {
+                    body.insert("system".to_string(), json!(system));
+                }
+                "generate"
+            }
+            other => {
+                return Err(ServerError::invalid(
+                    "run_inference",
+                    format!("Unknown mode '{}', expected 'chat' or 'generate'", other),
+                )
+                .into())
+            }
+        };
+
+        let mut options = params
+            .and_then(|p| p.get("options"))
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        for (key, value) in params.into_iter().flatten() {
+            if REQUEST_PARAMS.contains(&key.as_str()) {
+                body.insert(key.clone(), value.clone());
+            } else if key == "max_tokens" {
+                options.insert("num_predict".to_string(), value.clone());
+            } else if !RESERVED_PARAMS.contains(&key.as_str()) {
+                options.insert(key.clone(), value.clone());
+            }
+        }
+        if !options.is_empty() {
+            body.insert("options".to_string(), Value::Object(options));
+        }
+
+        let url = format!("{}/api/{}", self.api_url, endpoint);
+        let response = self.client.post(&url).json(&body).send().await?;
+        if !response.status().is_success() {
+            return handle_response(response).await;
+        }
+
+        // The reply is one JSON object per line; text arrives in pieces and the last
+        // object (`"done": true`) carries the statistics.
+        let mut output = String::new();
+        let mut chunks = 0usize;
+        let mut last = Value::Null;
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stream = response.bytes_stream();
+        loop {
+            let next = stream.next().await.transpose()?;
+            let at_end = next.is_none();
+            if let Some(bytes) = next {
+                buffer.extend_from_slice(&bytes);
+            }
+            while let Some(line) = take_line(&mut buffer, at_end) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let chunk: Value = serde_json::from_str(&line)?;
+                if let Some(error) = chunk["error"].as_str() {
+                    return Err(ServerError::ToolError(format!("Ollama error: {}", error)).into());
+                }
+                if let Some(text) = chunk["message"]["content"]
+                    .as_str()
+                    .or_else(|| chunk["response"].as_str())
+                {
+                    output.push_str(text);
+                }
+                chunks += 1;
+                last = chunk;
+            }
+            if at_end {
+                break;
+            }
+        }
+
+        if last["done"] != json!(true) {
+            return Err(ServerError::ToolError(
+                "Ollama stream ended before the response was complete".to_string(),
+            )
+            .into());
+        }
+
+        Ok(json!({
+            "status": "success",
+            "inference_id": Value::Null,
+            "model": last.get("model").cloned().unwrap_or_else(|| json!(model)),
+            "output": output,
+            "finish_reason": last["done_reason"],
+            "usage": {
+                "prompt_tokens": last["prompt_eval_count"],
+                "completion_tokens": last["eval_count"],
+            },
+            "chunks": chunks,
+        }))
+    }
+
+    async fn list_models(&self) -> Result<Value> {
+        let url = format!("{}/api/tags", self.api_url);
+        let response = self.client.get(&url).send().await?;
+        let response = handle_response(response).await?;
+
+        let models = response["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|model| {
+                json!({
+                    "id": model["name"],
+                    "name": model["name"],
+                    "size": model["size"],
+                    "family": model["details"]["family"],
+                    "parameter_size": model["details"]["parameter_size"],
+                })
+            })
+            .collect();
+        Ok(Value::Array(models))
+    }
+}
+
+/// Removes and returns the next complete line from `buffer`, or the remainder if
+/// the stream has ended.
+fn take_line(buffer: &mut Vec<u8>, at_end: bool) -> Option<String> {
+    let end = match buffer.iter().position(|&b| b == b'\n') {
+        Some(pos) => pos + 1,
+        None if at_end && !buffer.is_empty() => buffer.len(),
+        None => return None,
+    };
+    let line: Vec<u8> = buffer.drain(..end).collect();
+    Some(String::from_utf8_lossy(&line).into_owned())
+}
@@ -0,0 +1,125 @@
+// src/api/openai.rs
+
+use crate::api::backend::InferenceBackend;
+use crate::api::client::{handle_response, http_client};
+use crate::errors::ServerError;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// `params` keys interpreted by this backend rather than forwarded to the server.
+const RESERVED_PARAMS: &[&str] = &["mode", "system", "stream"];
+
+/// An inference backend for OpenAI-compatible servers such as vLLM, the llama.cpp
+/// server, or LiteLLM.
+///
+/// `run_inference` uses `/v1/chat/completions`, or `/v1/completions` when
+/// `params.mode` is `"completion"`. Responses are normalized to the AION-R shape.
+#[derive(Debug, Clone)]
+pub struct OpenAiBackend {
+    client: Client,
+    api_url: String,
+}
+
+impl OpenAiBackend {
+    /// Creates a backend for the server at `api_url` (without the `/v1` suffix).
+    pub fn new(api_url: &str, api_key: Option<&str>, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: http_client(api_key, timeout)?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for OpenAiBackend {
+    async fn run_inference(
+        &self,
+        model: &str,
+        prompt: &str,
+        params: &Option<Value>,
+    ) -> Result<Value> {
+        let params = params.as_ref().and_then(Value::as_object);
+        let mode = params
+            .and_then(|p| p.get("mode"))
+            .and_then(Value::as_str)
+            .unwrap_or("chat");
+
+        let mut body = Map::new();
+        body.insert("model".to_string(), json!(model));
+        match mode {
+            "chat" => {
+                let mut messages = Vec::new();
+                if let Some(system) = params.and_then(|p| p.get("system")).and_then(Value::as_str) {
+                    messages.push(json!({ "role": "system", "content": system }));
+                }
+                messages.push(json!({ "role": "user", "content": prompt }));
+                body.insert("messages".to_string(), Value::Array(messages));
+            }
+            "completion" => {
+                body.insert("prompt".to_string(), json!(prompt));
+            }
+            other => {
+                return Err(ServerError::invalid(
+                    "run_inference",
+                    format!("Unknown mode '{}', expected 'chat' or 'completion'", other),
+                )
+                .into())
+            }
+        }
+        for (key, value) in params.into_iter().flatten() {
+            if !RESERVED_PARAMS.contains(&key.as_str()) {
+                body.insert(key.clone(), value.clone());
+            }
+        }
+        body.insert("stream".to_string(), json!(false));
+
+        let endpoint = if mode == "chat" {
+            "chat/completions"
+        } else {
+            "completions"
+        };
+        let url = format!("{}/v1/{}", self.api_url, endpoint);
+        let response = self.client.post(&url).json(&body).send().await?;
+        let response = handle_response(response).await?;
+
+        let choice = &response["choices"][0];
+        let output = choice["message"]["content"]
+            .as_str()
+            .or_else(|| choice["text"].as_str())
+            .ok_or_else(|| {
+                ServerError::ToolError(format!("Unexpected completion response: {}", response))
+            })?;
+
+        Ok(json!({
+            "status": "success",
+            "inference_id": response["id"],
+            "model": response.get("model").cloned().unwrap_or_else(|| json!(model)),
+            "output": output,
+            "finish_reason": choice["finish_reason"],
+            "usage": response["usage"],
+        }))
+    }
+
+    async fn list_models(&self) -> Result<Value> {
+        let url = format!("{}/v1/models", self.api_url);
+        let response = self.client.get(&url).send().await?;
+        let response = handle_response(response).await?;
+
+        let models = response["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|model| {
+                json!({
+                    "id": model["id"],
+                    "name": model["id"],
+                    "owned_by": model["owned_by"],
+                })
+            })
+            .collect();
+        Ok(Value::Array(models))
+    }
+}
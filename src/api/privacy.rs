@@ -0,0 +1,306 @@
+// src/api/privacy.rs
+
+use crate::analysis::table::{self, Table};
+use crate::api::backend::AnalysisBackend;
+use crate::api::router::glob_match;
+use crate::errors::ServerError;
+use anyhow::Result;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Contents of the `--privacy-policy` file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PrivacyPolicyConfig {
+    /// Rules tried in order; the first one naming a column decides its transform.
+    #[serde(default)]
+    pub rules: Vec<PrivacyRule>,
+    /// What happens to columns no rule names.
+    #[serde(default)]
+    pub unmatched: Unmatched,
+    /// Secret key for `hash` rules.
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// Environment variable holding the salt, to keep secrets out of the file.
+    #[serde(default)]
+    pub salt_env: Option<String>,
+}
+
+/// A rule such as `{"columns": ["phone"], "action": "mask", "keep_last": 4}`.
+#[derive(Deserialize, Debug)]
+pub struct PrivacyRule {
+    /// Column names, or globs where `*` is any run of characters and `?` one character.
+    pub columns: Vec<String>,
+    #[serde(flatten)]
+    pub transform: Transform,
+}
+
+/// How the values of a column are changed before they leave the machine.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Transform {
+    /// Sends the column as it is.
+    Keep,
+    /// Leaves the column out.
+    Drop,
+    /// Replaces each value with the hex HMAC-SHA256 of its text, keyed by the salt.
+    Hash,
+    /// Replaces the characters of each value with `mask_char`, except the first
+    /// `keep_first` and last `keep_last`.
+    Mask {
+        #[serde(default)]
+        keep_first: usize,
+        #[serde(default)]
+        keep_last: usize,
+        #[serde(default = "default_mask_char")]
+        mask_char: char,
+    },
+    /// Replaces each number with the lower bound of its `width`-wide bucket,
+    /// counted from `origin`. Values that are not numbers become null.
+    Bucket {
+        width: f64,
+        #[serde(default)]
+        origin: f64,
+    },
+}
+
+fn default_mask_char() -> char {
+    '*'
+}
+
+impl Transform {
+    fn name(&self) -> &'static str {
+        match self {
+            Transform::Keep => "keep",
+            Transform::Drop => "drop",
+            Transform::Hash => "hash",
+            Transform::Mask { .. } => "mask",
+            Transform::Bucket { .. } => "bucket",
+        }
+    }
+}
+
+/// What happens to columns that no rule names.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Unmatched {
+    #[default]
+    Keep,
+    Drop,
+}
+
+/// Per-column transforms applied to every dataset sent to the analysis backend.
+pub struct PrivacyPolicy {
+    rules: Vec<(Vec<String>, Transform)>,
+    unmatched: Unmatched,
+    salt: Vec<u8>,
+}
+
+impl PrivacyPolicy {
+    /// Loads the policy from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ServerError::ConfigError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let config: PrivacyPolicyConfig = serde_json::from_str(&contents).map_err(|e| {
+            ServerError::ConfigError(format!("Invalid privacy policy {}: {}", path.display(), e))
+        })?;
+        Self::from_config(config)
+    }
+
+    pub fn from_config(config: PrivacyPolicyConfig) -> Result<Self> {
+        let salt = match &config.salt_env {
+            Some(var) => Some(std::env::var(var).map_err(|_| {
+                ServerError::ConfigError(format!(
+                    "The privacy policy reads its salt from ${}, which is not set",
+                    var
+                ))
+            })?),
+            None => config.salt,
+        };
+        let salt = salt.filter(|salt| !salt.is_empty());
+
+        let mut rules = Vec::with_capacity(config.rules.len());
+        for (i, rule) in config.rules.into_iter().enumerate() {
+            let problem = match &rule.transform {
+                _ if rule.columns.is_empty() => Some("names no 'columns'"),
+                Transform::Hash if salt.is_none() => {
+                    Some("hashes values but the policy sets no 'salt' or 'salt_env'")
+                }
+                Transform::Bucket { width, origin }
+                    if !(width.is_finite() && *width > 0.0 && origin.is_finite()) =>
+                {
+                    Some("needs a positive 'width' and a finite 'origin'")
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                return Err(
+                    ServerError::ConfigError(format!("Privacy rule {} {}", i, problem)).into(),
+                );
+            }
+            rules.push((rule.columns, rule.transform));
+        }
+
+        Ok(Self {
+            rules,
+            unmatched: config.unmatched,
+            salt: salt.unwrap_or_default().into_bytes(),
+        })
+    }
+
+    /// The transform for `column`.
+    fn transform(&self, column: &str) -> Transform {
+        self.rules
+            .iter()
+            .find(|(patterns, _)| patterns.iter().any(|p| glob_match(p, column)))
+            .map(|(_, transform)| transform.clone())
+            .unwrap_or(match self.unmatched {
+                Unmatched::Keep => Transform::Keep,
+                Unmatched::Drop => Transform::Drop,
+            })
+    }
+
+    /// Applies the policy to `data`, which must be tabular. Returns the data to
+    /// send, as row objects if anything changed, and the transform applied to
+    /// each changed column.
+    pub fn apply(&self, data: &Value) -> Result<(Option<Value>, BTreeMap<String, &'static str>)> {
+        let table = Table::from_json(data).ok_or_else(|| {
+            ServerError::AccessDenied(
+                "The privacy policy only lets tabular data reach the analysis backend".to_string(),
+            )
+        })?;
+        let transforms: Vec<Transform> = table.columns.iter().map(|c| self.transform(c)).collect();
+        let applied: BTreeMap<String, &'static str> = table
+            .columns
+            .iter()
+            .zip(&transforms)
+            .filter(|(_, transform)| **transform != Transform::Keep)
+            .map(|(column, transform)| (column.clone(), transform.name()))
+            .collect();
+        if applied.is_empty() {
+            return Ok((None, applied));
+        }
+
+        let kept: Vec<usize> = (0..transforms.len())
+            .filter(|&i| transforms[i] != Transform::Drop)
+            .collect();
+        let anonymized = Table {
+            columns: kept.iter().map(|&i| table.columns[i].clone()).collect(),
+            rows: table
+                .rows
+                .iter()
+                .map(|row| {
+                    kept.iter()
+                        .map(|&i| self.apply_value(&transforms[i], &row[i]))
+                        .collect()
+                })
+                .collect(),
+        };
+        Ok((Some(anonymized.to_json()), applied))
+    }
+
+    fn apply_value(&self, transform: &Transform, value: &Value) -> Value {
+        if value.is_null() {
+            return Value::Null;
+        }
+        match transform {
+            Transform::Keep | Transform::Drop => value.clone(),
+            Transform::Hash => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(&self.salt)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(table::text(value).as_bytes());
+                let digest = mac.finalize().into_bytes();
+                json!(digest
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>())
+            }
+            Transform::Mask {
+                keep_first,
+                keep_last,
+                mask_char,
+            } => {
+                let chars: Vec<char> = table::text(value).chars().collect();
+                // Too short to reveal anything safely: mask it all.
+                let shown = if keep_first + keep_last < chars.len() {
+                    (*keep_first, chars.len() - keep_last)
+                } else {
+                    (0, chars.len())
+                };
+                let masked: String = chars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &c)| {
+                        if i < shown.0 || i >= shown.1 {
+                            c
+                        } else {
+                            *mask_char
+                        }
+                    })
+                    .collect();
+                json!(masked)
+            }
+            Transform::Bucket { width, origin } => match value.as_f64() {
+                Some(n) => {
+                    let lower = ((n - origin) / width).floor() * width + origin;
+                    if lower.fract() == 0.0 && lower.abs() < i64::MAX as f64 {
+                        json!(lower as i64)
+                    } else {
+                        json!(lower)
+                    }
+                }
+                None => Value::Null,
+            },
+        }
+    }
+}
+
+/// An analysis backend that anonymizes every dataset with a [`PrivacyPolicy`]
+/// before passing it on, and lists what it changed in the result's `anonymized`.
+pub struct AnonymizingBackend {
+    inner: Arc<dyn AnalysisBackend>,
+    policy: PrivacyPolicy,
+}
+
+impl AnonymizingBackend {
+    pub fn new(inner: Arc<dyn AnalysisBackend>, policy: PrivacyPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl AnalysisBackend for AnonymizingBackend {
+    async fn data_analysis(&self, data: &Value, ops: &Value) -> Result<Value> {
+        let (anonymized, applied) = self.policy.apply(data)?;
+        tracing::debug!(columns = ?applied, "Applied the privacy policy");
+        let mut result = self
+            .inner
+            .data_analysis(anonymized.as_ref().unwrap_or(data), ops)
+            .await?;
+        if let (false, Some(obj)) = (applied.is_empty(), result.as_object_mut()) {
+            obj.insert("anonymized".to_string(), json!(applied));
+        }
+        Ok(result)
+    }
+
+    fn outgoing_data<'a>(&self, data: &'a Value) -> Result<Cow<'a, Value>> {
+        match self.policy.apply(data)?.0 {
+            Some(anonymized) => Ok(Cow::Owned(
+                self.inner.outgoing_data(&anonymized)?.into_owned(),
+            )),
+            None => self.inner.outgoing_data(data),
+        }
+    }
+
+    async fn read_dataset(&self, id: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        self.inner.read_dataset(id, max_bytes).await
+    }
+}
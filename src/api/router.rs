@@ -0,0 +1,264 @@
+// src/api/router.rs
+
+use crate::api::backend::InferenceBackend;
+use crate::api::client::ApiClient;
+use crate::api::mock::MockBackend;
+use crate::api::ollama::OllamaBackend;
+use crate::api::openai::OpenAiBackend;
+use crate::config::BackendKind;
+use crate::errors::ServerError;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Contents of the `--routes` file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RoutesConfig {
+    /// Named backend instances.
+    pub backends: BTreeMap<String, BackendConfig>,
+    /// Rules tried in order; the first one matching the model wins.
+    #[serde(default)]
+    pub routes: Vec<RouteConfig>,
+    /// Backend used when no rule matches.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BackendConfig {
+    pub kind: BackendKind,
+    /// Base URL of the backend; not used by `mock`.
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Environment variable holding the API key, to keep secrets out of the file.
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    /// Scenario file for the `mock` kind.
+    #[serde(default)]
+    pub scenario: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    /// Matches models whose name starts with this string.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Matches models against a glob where `*` is any run of characters and `?` one character.
+    #[serde(default)]
+    pub glob: Option<String>,
+    pub backend: String,
+}
+
+enum ModelPattern {
+    Prefix(String),
+    Glob(String),
+}
+
+impl ModelPattern {
+    fn matches(&self, model: &str) -> bool {
+        match self {
+            ModelPattern::Prefix(prefix) => model.starts_with(prefix.as_str()),
+            ModelPattern::Glob(glob) => glob_match(glob, model),
+        }
+    }
+}
+
+/// An inference backend that forwards each call to one of several backends,
+/// chosen by the `model` name, and merges their model catalogs.
+pub struct RoutingBackend {
+    backends: Vec<(String, Arc<dyn InferenceBackend>)>,
+    routes: Vec<(ModelPattern, usize)>,
+    default: Option<usize>,
+}
+
+impl RoutingBackend {
+    /// Loads the routing table from a JSON file; requests to every backend time
+    /// out after `timeout`.
+    pub fn from_file(path: &Path, timeout: Duration) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ServerError::ConfigError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let config: RoutesConfig = serde_json::from_str(&contents).map_err(|e| {
+            ServerError::ConfigError(format!("Invalid routes file {}: {}", path.display(), e))
+        })?;
+        Self::from_config(config, timeout)
+    }
+
+    pub fn from_config(config: RoutesConfig, timeout: Duration) -> Result<Self> {
+        let mut backends: Vec<(String, Arc<dyn InferenceBackend>)> = Vec::new();
+        for (name, backend) in config.backends {
+            let api_key = match &backend.api_key_env {
+                Some(var) => Some(std::env::var(var).map_err(|_| {
+                    ServerError::ConfigError(format!(
+                        "Backend '{}' reads its API key from ${}, which is not set",
+                        name, var
+                    ))
+                })?),
+                None => backend.api_key.clone(),
+            };
+            let api_key = api_key.as_deref();
+            if backend.url.is_empty() && backend.kind != BackendKind::Mock {
+                return Err(
+                    ServerError::ConfigError(format!("Backend '{}' needs a 'url'", name)).into(),
+                );
+            }
+            let instance: Arc<dyn InferenceBackend> = match backend.kind {
+                BackendKind::AionR => {
+                    Arc::new(ApiClient::with_url(&backend.url, api_key, timeout)?)
+                }
+                BackendKind::OpenAi => {
+                    Arc::new(OpenAiBackend::new(&backend.url, api_key, timeout)?)
+                }
+                BackendKind::Ollama => {
+                    Arc::new(OllamaBackend::new(&backend.url, api_key, timeout)?)
+                }
+                BackendKind::Mock => Arc::new(MockBackend::from_file(backend.scenario.as_deref())?),
+            };
+            backends.push((name, instance));
+        }
+
+        let index_of = |name: &str| {
+            backends
+                .iter()
+                .position(|(n, _)| n == name)
+                .ok_or_else(|| ServerError::ConfigError(format!("Unknown backend '{}'", name)))
+        };
+
+        let mut routes = Vec::new();
+        for route in config.routes {
+            let pattern = match (route.prefix, route.glob) {
+                (Some(prefix), None) => ModelPattern::Prefix(prefix),
+                (None, Some(glob)) => ModelPattern::Glob(glob),
+                _ => {
+                    return Err(ServerError::ConfigError(format!(
+                        "Route to '{}' needs exactly one of 'prefix' or 'glob'",
+                        route.backend
+                    ))
+                    .into())
+                }
+            };
+            routes.push((pattern, index_of(&route.backend)?));
+        }
+        let default = config.default.as_deref().map(index_of).transpose()?;
+
+        Ok(Self {
+            backends,
+            routes,
+            default,
+        })
+    }
+
+    /// The name and instance of the backend serving `model`.
+    fn route(&self, model: &str) -> Result<&(String, Arc<dyn InferenceBackend>)> {
+        self.routes
+            .iter()
+            .find(|(pattern, _)| pattern.matches(model))
+            .map(|(_, index)| *index)
+            .or(self.default)
+            .map(|index| &self.backends[index])
+            .ok_or_else(|| {
+                ServerError::invalid(
+                    "run_inference",
+                    format!("No backend route matches model '{}'", model),
+                )
+                .into()
+            })
+    }
+}
+
+#[async_trait]
+impl InferenceBackend for RoutingBackend {
+    async fn run_inference(
+        &self,
+        model: &str,
+        prompt: &str,
+        params: &Option<Value>,
+    ) -> Result<Value> {
+        let (name, backend) = self.route(model)?;
+        tracing::debug!(model, backend = %name, "Routing inference");
+        let mut result = backend.run_inference(model, prompt, params).await?;
+        if let Some(obj) = result.as_object_mut() {
+            obj.insert("source".to_string(), json!(name));
+        }
+        Ok(result)
+    }
+
+    async fn list_models(&self) -> Result<Value> {
+        let catalogs = futures::future::join_all(
+            self.backends
+                .iter()
+                .map(|(_, backend)| backend.list_models()),
+        )
+        .await;
+
+        let mut merged = Vec::new();
+        let mut errors = Vec::new();
+        for ((name, _), catalog) in self.backends.iter().zip(catalogs) {
+            let models = match catalog {
+                Ok(Value::Array(models)) => models,
+                Ok(other) => vec![other],
+                Err(e) => {
+                    tracing::warn!(backend = %name, error = %e, "Failed to list models");
+                    errors.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            for model in models {
+                let mut entry = match model {
+                    Value::Object(obj) => obj,
+                    other => {
+                        let mut obj = serde_json::Map::new();
+                        obj.insert("id".to_string(), other);
+                        obj
+                    }
+                };
+                entry.insert("source".to_string(), json!(name));
+                merged.push(Value::Object(entry));
+            }
+        }
+
+        if merged.is_empty() && !errors.is_empty() {
+            return Err(ServerError::ToolError(format!(
+                "No backend returned a model catalog: {}",
+                errors.join("; ")
+            ))
+            .into());
+        }
+        Ok(Value::Array(merged))
+    }
+}
+
+/// Matches `text` against a glob where `*` is any run of characters and `?` one character.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
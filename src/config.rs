@@ -1,6 +1,8 @@
 // src/config.rs
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use std::path::PathBuf;
 
 /// A production-ready Rust implementation of the AION-R MCP server.
 #[derive(Parser, Debug, Clone)]
@@ -13,4 +15,134 @@ pub struct Config {
     /// An optional API key for the backend AION-R API.
     #[arg(long, env = "AION_R_API_KEY")]
     pub aion_r_api_key: Option<String>,
+
+    /// Which backend serves `run_inference` and the model catalog.
+    /// Data analysis is served by the AION-R API, or by the mock backend with `mock`.
+    #[arg(long, env = "AION_R_BACKEND", value_enum, default_value_t = BackendKind::AionR)]
+    pub backend: BackendKind,
+
+    /// Base URL (without `/v1`) of the OpenAI-compatible server used by `--backend openai`.
+    #[arg(long, env = "OPENAI_API_URL", default_value = "http://localhost:8000")]
+    pub openai_api_url: String,
+
+    /// An optional API key for the OpenAI-compatible server.
+    #[arg(long, env = "OPENAI_API_KEY")]
+    pub openai_api_key: Option<String>,
+
+    /// Base URL of the Ollama-style API used by `--backend ollama`.
+    #[arg(long, env = "OLLAMA_URL", default_value = "http://localhost:11434")]
+    pub ollama_url: String,
+
+    /// JSON scenario file scripting the replies of `--backend mock`.
+    #[arg(long, env = "AION_R_MOCK_SCENARIO")]
+    pub mock_scenario: Option<PathBuf>,
+
+    /// JSON file routing models to several backend instances by name prefix or glob.
+    /// Overrides `--backend` for inference and the model catalog.
+    #[arg(long, env = "AION_R_ROUTES")]
+    pub routes: Option<PathBuf>,
+
+    /// Ask the user to confirm `run_inference` calls estimated to use more tokens than this.
+    #[arg(long, env = "AION_R_CONFIRM_TOKEN_THRESHOLD")]
+    pub confirm_token_threshold: Option<u64>,
+
+    /// Largest dataset in bytes that `data_analysis` reads from a file or URI.
+    #[arg(long, env = "AION_R_MAX_DATA_BYTES", default_value_t = 50 * 1024 * 1024)]
+    pub max_data_bytes: u64,
+
+    /// Most rows `data_analysis` sends to the analysis backend; larger datasets are
+    /// sampled down first.
+    #[arg(long, env = "AION_R_MAX_BACKEND_ROWS")]
+    pub max_backend_rows: Option<usize>,
+
+    /// Most bytes of JSON `data_analysis` sends to the analysis backend; larger
+    /// datasets are sampled down first.
+    #[arg(long, env = "AION_R_MAX_BACKEND_BYTES")]
+    pub max_backend_bytes: Option<u64>,
+
+    /// Seconds a request to a backend may take before it is abandoned.
+    #[arg(
+        long,
+        env = "AION_R_REQUEST_TIMEOUT",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub request_timeout: u64,
+
+    /// Seconds a `query_data` query may run before it is interrupted.
+    #[arg(
+        long,
+        env = "AION_R_QUERY_TIMEOUT",
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub query_timeout: u64,
+
+    /// JSON file of per-column transforms (drop, hash, mask, bucket) applied to every
+    /// dataset before it is sent to the analysis backend.
+    #[arg(long, env = "AION_R_PRIVACY_POLICY")]
+    pub privacy_policy: Option<PathBuf>,
+
+    /// JSON file listing tools to disable, e.g. `{"disabled": ["data_analysis"]}`.
+    /// Re-read on SIGHUP; connected clients are notified when the tool list changes.
+    #[arg(long, env = "AION_R_TOOLS_CONFIG")]
+    pub tools_config: Option<PathBuf>,
+
+    /// Write logs to this file instead of stderr.
+    #[arg(long, env = "AION_R_LOG_FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// How the log file is rotated. Ignored unless `--log-file` is set.
+    #[arg(long, env = "AION_R_LOG_ROTATION", value_enum, default_value_t = LogRotation::Never)]
+    pub log_rotation: LogRotation,
+
+    /// Maximum size in bytes of the log file before it is rotated (`--log-rotation size`).
+    #[arg(long, env = "AION_R_LOG_MAX_SIZE", default_value_t = 10 * 1024 * 1024)]
+    pub log_max_size: u64,
+
+    /// Number of rotated log files to keep. Unlimited for time-based rotation if unset.
+    #[arg(long, env = "AION_R_LOG_MAX_FILES")]
+    pub log_max_files: Option<usize>,
+
+    /// The format of emitted log lines.
+    #[arg(long, env = "AION_R_LOG_FORMAT", value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+}
+
+/// Inference backend implementation.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// The AION-R HTTP API.
+    #[value(name = "aion-r")]
+    #[serde(rename = "aion-r")]
+    AionR,
+    /// An OpenAI-compatible HTTP API.
+    #[value(name = "openai")]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// A local Ollama-style API.
+    #[value(name = "ollama")]
+    #[serde(rename = "ollama")]
+    Ollama,
+    /// Offline fixtures or a scripted scenario; also serves data analysis.
+    #[value(name = "mock")]
+    #[serde(rename = "mock")]
+    Mock,
+}
+
+/// Log file rotation policy.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    Never,
+    Minutely,
+    Hourly,
+    Daily,
+    Size,
+}
+
+/// Log line format.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
 }
@@ -23,9 +23,47 @@ pub enum ServerError {
     #[error("Invalid parameters for method '{method}': {details}")]
     InvalidParameters { method: String, details: String },
 
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+
     #[error("Internal tool error: {0}")]
     ToolError(String),
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
 }
+
+impl ServerError {
+    /// An `InvalidParameters` error reported for `method`.
+    pub fn invalid(method: &str, details: impl Into<String>) -> Self {
+        ServerError::InvalidParameters {
+            method: method.to_string(),
+            details: details.into(),
+        }
+    }
+
+    /// Attributes an `InvalidParameters` error to `method`, e.g. one raised while
+    /// loading data on behalf of another tool. Other errors are returned as they are.
+    pub fn with_method(error: anyhow::Error, method: &str) -> anyhow::Error {
+        Self::reattribute(error, method, |details| details)
+    }
+
+    /// Like [`ServerError::with_method`], prefixing the details with `context`.
+    pub fn with_method_context(error: anyhow::Error, method: &str, context: &str) -> anyhow::Error {
+        Self::reattribute(error, method, |details| format!("{}: {}", context, details))
+    }
+
+    fn reattribute(
+        error: anyhow::Error,
+        method: &str,
+        details: impl FnOnce(String) -> String,
+    ) -> anyhow::Error {
+        match error.downcast() {
+            Ok(ServerError::InvalidParameters { details: d, .. }) => {
+                Self::invalid(method, details(d)).into()
+            }
+            Ok(other) => other.into(),
+            Err(e) => e,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// src/lib.rs
+
+//! The AION-R MCP server as a library.
+//!
+//! [`McpServer`] speaks MCP over any `AsyncRead`/`AsyncWrite` pair, so it can be
+//! embedded in other services or driven in-process, e.g. over `tokio::io::duplex`.
+//! The `aionr2` binary is a thin wrapper that serves it over stdio.
+
+pub mod analysis;
+pub mod api;
+pub mod config;
+pub mod errors;
+pub mod logging;
+pub mod mcp;
+pub mod tools;
+pub mod util;
+
+pub use api::backend::{AnalysisBackend, Backends, InferenceBackend};
+pub use api::client::ApiClient;
+pub use config::Config;
+pub use mcp::server::McpServer;
+pub use tools::context::ToolContext;
+pub use tools::registry::{Tool, ToolRegistry};
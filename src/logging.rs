@@ -0,0 +1,146 @@
+// src/logging.rs
+
+use crate::config::{Config, LogFormat, LogRotation};
+use crate::errors::ServerError;
+use anyhow::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{self, RollingFileAppender};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
+
+/// Number of rotated files kept by size-based rotation when `--log-max-files` is unset.
+const DEFAULT_SIZE_ROTATION_FILES: usize = 5;
+
+/// Installs the global tracing subscriber.
+///
+/// Logs never go to stdout, which is reserved for protocol frames written by
+/// `util::write_message`. They are written to stderr unless a log file is configured.
+/// The returned guard flushes buffered file output on drop and must be kept alive
+/// for the lifetime of the process.
+pub fn init(config: &Config) -> Result<Option<WorkerGuard>> {
+    let (writer, guard) = match &config.log_file {
+        Some(path) => {
+            let (non_blocking, guard) =
+                tracing_appender::non_blocking(open_log_file(config, path)?);
+            (BoxMakeWriter::new(non_blocking), Some(guard))
+        }
+        None => (BoxMakeWriter::new(io::stderr), None),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(writer)
+        .with_ansi(false);
+
+    match config.log_format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+
+    Ok(guard)
+}
+
+fn open_log_file(config: &Config, path: &Path) -> Result<Box<dyn Write + Send>> {
+    let rotation = match config.log_rotation {
+        LogRotation::Size => {
+            let max_files = config.log_max_files.unwrap_or(DEFAULT_SIZE_ROTATION_FILES);
+            let writer = SizeRollingFile::open(path.to_path_buf(), config.log_max_size, max_files)?;
+            return Ok(Box::new(writer));
+        }
+        LogRotation::Never => rolling::Rotation::NEVER,
+        LogRotation::Minutely => rolling::Rotation::MINUTELY,
+        LogRotation::Hourly => rolling::Rotation::HOURLY,
+        LogRotation::Daily => rolling::Rotation::DAILY,
+    };
+
+    let file_name = path.file_name().ok_or_else(|| {
+        ServerError::ConfigError(format!("Invalid log file path: {}", path.display()))
+    })?;
+    let directory = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(file_name.to_string_lossy());
+    if let Some(max_files) = config.log_max_files {
+        builder = builder.max_log_files(max_files);
+    }
+    let appender = builder
+        .build(directory)
+        .map_err(|e| ServerError::ConfigError(format!("Cannot open log file: {}", e)))?;
+    Ok(Box::new(appender))
+}
+
+/// A log file that is rotated once it grows beyond `max_size` bytes.
+///
+/// Rotated files are renamed to `<path>.1`, `<path>.2`, ... with `.1` being the
+/// most recent; at most `max_files` of them are kept.
+struct SizeRollingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl SizeRollingFile {
+    fn open(path: PathBuf, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -1,14 +1,6 @@
 // src/main.rs
 
-// Declare modules
-mod api;
-mod config;
-mod errors;
-mod mcp;
-mod tools;
-mod util;
-
-use crate::config::Config;
+use aionr2::{logging, Config, McpServer};
 use anyhow::Result;
 use clap::Parser;
 
@@ -20,10 +12,8 @@ async fn main() -> Result<()> {
     // Parse command-line arguments, which will also read from environment variables
     let config = Config::parse();
 
-    // Initialize tracing subscriber for logging
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    // Initialize logging; stdout is reserved for protocol messages
+    let _log_guard = logging::init(&config)?;
 
     tracing::info!(
         version = env!("CARGO_PKG_VERSION"),
@@ -31,7 +21,22 @@ async fn main() -> Result<()> {
     );
 
     // Create and run the MCP server
-    let mcp_server = mcp::server::McpServer::new(&config).await?;
+    let mcp_server = McpServer::new(&config).await?;
+
+    // Re-read the tools config on SIGHUP
+    #[cfg(unix)]
+    {
+        let server = mcp_server.clone();
+        let mut hangups = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                tracing::info!("Received SIGHUP, reloading tools config");
+                if let Err(e) = server.reload_tools_config() {
+                    tracing::error!(error = %e, "Failed to reload tools config");
+                }
+            }
+        });
+    }
 
     // Run the server and handle graceful shutdown
     if let Err(e) = mcp_server.run().await {
@@ -0,0 +1,57 @@
+// src/mcp/elicitation.rs
+
+use crate::errors::ServerError;
+use crate::mcp::peer::Peer;
+use crate::mcp::types::{ElicitAction, ElicitParams, ElicitResult};
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+/// Lets tools ask the user, through the client, for input via `elicitation/create`.
+#[derive(Clone)]
+pub struct ElicitationClient {
+    peer: Peer,
+}
+
+impl ElicitationClient {
+    pub fn new(peer: Peer) -> Self {
+        Self { peer }
+    }
+
+    /// Whether the client declared the `elicitation` capability.
+    pub fn is_available(&self) -> bool {
+        self.peer.client_supports("elicitation")
+    }
+
+    /// Asks the user to fill in a flat object described by `requested_schema`.
+    pub async fn create(&self, message: &str, requested_schema: Value) -> Result<ElicitResult> {
+        if !self.is_available() {
+            return Err(ServerError::ToolError(
+                "The client does not support elicitation".to_string(),
+            )
+            .into());
+        }
+        let params = ElicitParams {
+            message: message.to_string(),
+            requested_schema,
+        };
+        let result = self
+            .peer
+            .request("elicitation/create", Some(serde_json::to_value(params)?))
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Asks the user for the given fields and returns them only if the user accepted.
+    pub async fn request_fields(
+        &self,
+        message: &str,
+        requested_schema: Value,
+    ) -> Result<Option<Map<String, Value>>> {
+        let result = self.create(message, requested_schema).await?;
+        match (result.action, result.content) {
+            (ElicitAction::Accept, Some(Value::Object(content))) => Ok(Some(content)),
+            (ElicitAction::Accept, _) => Ok(Some(Map::new())),
+            (ElicitAction::Decline | ElicitAction::Cancel, _) => Ok(None),
+        }
+    }
+}
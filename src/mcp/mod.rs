@@ -1,4 +1,8 @@
 // src/mcp/mod.rs
 
+pub mod elicitation;
+pub mod peer;
+pub mod roots;
+pub mod sampling;
 pub mod server;
 pub mod types;
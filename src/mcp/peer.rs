@@ -0,0 +1,162 @@
+// src/mcp/peer.rs
+
+use crate::errors::ServerError;
+use crate::mcp::types::{JsonRpcRequest, JsonRpcResponse};
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long the server waits for the client to answer a server-initiated request.
+/// Generous because requests like sampling may wait on a human approving them.
+const CLIENT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+type PendingMap = HashMap<u64, oneshot::Sender<JsonRpcResponse>>;
+
+/// Handle to the client on the other end of a session.
+///
+/// Sends responses, notifications and server-initiated requests, and correlates
+/// the client's responses to those requests by id.
+#[derive(Clone)]
+pub struct Peer {
+    outgoing: mpsc::UnboundedSender<String>,
+    pending: Arc<Mutex<PendingMap>>,
+    /// Set once the client can no longer answer requests.
+    closed: Arc<AtomicBool>,
+    next_id: Arc<AtomicU64>,
+    client_capabilities: Arc<RwLock<Value>>,
+}
+
+impl Peer {
+    pub fn new(outgoing: mpsc::UnboundedSender<String>) -> Self {
+        Self {
+            outgoing,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            closed: Arc::new(AtomicBool::new(false)),
+            next_id: Arc::new(AtomicU64::new(1)),
+            client_capabilities: Arc::new(RwLock::new(Value::Null)),
+        }
+    }
+
+    /// Queues a serialized message for delivery to the client.
+    pub fn send(&self, message: String) -> Result<()> {
+        self.outgoing
+            .send(message)
+            .map_err(|_| ServerError::ToolError("Client connection is closed".to_string()))?;
+        Ok(())
+    }
+
+    /// Sends a notification to the client.
+    pub fn notify(&self, method: &str, params: Option<Value>) -> Result<()> {
+        let notification = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: None,
+        };
+        self.send(serde_json::to_string(&notification)?)
+    }
+
+    /// Sends a request to the client and waits for its response.
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        {
+            // Checked under the lock so a request cannot slip in after `cancel_pending`.
+            let mut pending = self.pending_map();
+            if self.closed.load(Ordering::Acquire) {
+                return Err(ServerError::ToolError(format!(
+                    "Client connection closed before sending '{}'",
+                    method
+                ))
+                .into());
+            }
+            pending.insert(id, tx);
+        }
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: Some(Value::from(id)),
+        };
+        if let Err(e) = self.send(serde_json::to_string(&request)?) {
+            self.pending_map().remove(&id);
+            return Err(e);
+        }
+
+        let response = match tokio::time::timeout(CLIENT_REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(ServerError::ToolError(format!(
+                    "Client connection closed before answering '{}'",
+                    method
+                ))
+                .into())
+            }
+            Err(_) => {
+                self.pending_map().remove(&id);
+                return Err(ServerError::ToolError(format!(
+                    "Timed out waiting for the client to answer '{}'",
+                    method
+                ))
+                .into());
+            }
+        };
+
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(ServerError::ToolError(format!(
+                "Client rejected '{}' (code {}): {}",
+                method, error.code, error.message
+            ))
+            .into()),
+            (Some(result), None) => Ok(result),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    /// Routes a response from the client to the request awaiting it.
+    /// Returns `false` if no request with that id is pending.
+    pub fn handle_response(&self, response: JsonRpcResponse) -> bool {
+        let sender = response
+            .id
+            .as_u64()
+            .and_then(|id| self.pending_map().remove(&id));
+        match sender {
+            Some(sender) => sender.send(response).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Fails every request still waiting on the client, e.g. once its input has closed,
+    /// and any request made after this.
+    pub fn cancel_pending(&self) {
+        let mut pending = self.pending_map();
+        self.closed.store(true, Ordering::Release);
+        pending.clear();
+    }
+
+    /// Records the capabilities the client declared in `initialize`.
+    pub fn set_client_capabilities(&self, capabilities: Value) {
+        *self
+            .client_capabilities
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = capabilities;
+    }
+
+    /// Whether the client declared the given top-level capability, e.g. `"sampling"`.
+    pub fn client_supports(&self, capability: &str) -> bool {
+        self.client_capabilities
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(capability)
+            .is_some_and(|c| !c.is_null())
+    }
+
+    fn pending_map(&self) -> std::sync::MutexGuard<'_, PendingMap> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,151 @@
+// src/mcp/roots.rs
+
+use crate::errors::ServerError;
+use crate::mcp::peer::Peer;
+use crate::util;
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use url::Url;
+
+#[derive(Deserialize, Debug)]
+struct ListRootsResult {
+    roots: Vec<Root>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Root {
+    uri: String,
+}
+
+/// The filesystem roots declared by the client, which bound all local file access.
+///
+/// Roots are fetched with `roots/list` the first time they are needed and again after
+/// the client sends `notifications/roots/list_changed`.
+#[derive(Clone)]
+pub struct Roots {
+    peer: Peer,
+    cache: Arc<RwLock<Option<Vec<PathBuf>>>>,
+}
+
+impl Roots {
+    pub fn new(peer: Peer) -> Self {
+        Self {
+            peer,
+            cache: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Drops the cached roots so they are fetched again on next use.
+    pub async fn invalidate(&self) {
+        *self.cache.write().await = None;
+    }
+
+    /// The canonicalized root directories currently declared by the client.
+    pub async fn list(&self) -> Result<Vec<PathBuf>> {
+        if let Some(roots) = self.cache.read().await.as_ref() {
+            return Ok(roots.clone());
+        }
+
+        let roots = if self.peer.client_supports("roots") {
+            self.fetch().await?
+        } else {
+            Vec::new()
+        };
+        *self.cache.write().await = Some(roots.clone());
+        Ok(roots)
+    }
+
+    /// Resolves a local path or `file://` URI to a canonical path inside one of the roots.
+    ///
+    /// Symlinks are resolved before the check, so links pointing outside every root
+    /// are rejected just like paths that are outside to begin with.
+    pub async fn resolve(&self, path_or_uri: &str) -> Result<PathBuf> {
+        let path = parse_local_path(path_or_uri)?;
+        if !path.is_absolute() {
+            return Err(ServerError::AccessDenied(format!(
+                "'{}' is not an absolute path",
+                path_or_uri
+            ))
+            .into());
+        }
+
+        let roots = self.list().await?;
+        if roots.is_empty() {
+            return Err(ServerError::AccessDenied(format!(
+                "Cannot read '{}': the client has not declared any roots",
+                path_or_uri
+            ))
+            .into());
+        }
+
+        let canonical = tokio::fs::canonicalize(&path).await.map_err(|e| {
+            ServerError::AccessDenied(format!("Cannot access '{}': {}", path.display(), e))
+        })?;
+        if !roots.iter().any(|root| canonical.starts_with(root)) {
+            return Err(ServerError::AccessDenied(format!(
+                "'{}' resolves to '{}', which is outside the client's roots",
+                path.display(),
+                canonical.display()
+            ))
+            .into());
+        }
+        Ok(canonical)
+    }
+
+    /// Opens a file within the roots, failing if it is larger than `max_bytes`.
+    /// Readers should still stop after `max_bytes`, as the file may grow.
+    pub async fn open_file(&self, path_or_uri: &str, max_bytes: u64) -> Result<tokio::fs::File> {
+        let path = self.resolve(path_or_uri).await?;
+        let file = tokio::fs::File::open(&path).await?;
+        if file.metadata().await?.len() > max_bytes {
+            return Err(util::size_limit_error(path_or_uri, max_bytes));
+        }
+        Ok(file)
+    }
+
+    /// Reads a whole file of at most `max_bytes` after checking it lies within the roots.
+    pub async fn read_file(&self, path_or_uri: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let file = self.open_file(path_or_uri, max_bytes).await?;
+        util::read_limited(file, max_bytes, path_or_uri).await
+    }
+
+    async fn fetch(&self) -> Result<Vec<PathBuf>> {
+        let result = self.peer.request("roots/list", None).await?;
+        let result: ListRootsResult = serde_json::from_value(result)?;
+
+        let mut roots = Vec::new();
+        for root in result.roots {
+            let path = match Url::parse(&root.uri).ok().filter(|u| u.scheme() == "file") {
+                Some(url) => url.to_file_path().ok(),
+                None => None,
+            };
+            let Some(path) = path else {
+                tracing::warn!(uri = %root.uri, "Ignoring root that is not a local file URI");
+                continue;
+            };
+            match tokio::fs::canonicalize(&path).await {
+                Ok(canonical) => roots.push(canonical),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "Ignoring inaccessible root")
+                }
+            }
+        }
+        tracing::debug!(count = roots.len(), "Loaded client roots");
+        Ok(roots)
+    }
+}
+
+fn parse_local_path(path_or_uri: &str) -> Result<PathBuf> {
+    if !path_or_uri.starts_with("file:") {
+        return Ok(Path::new(path_or_uri).to_path_buf());
+    }
+    Url::parse(path_or_uri)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .ok_or_else(|| {
+            ServerError::AccessDenied(format!("Invalid file URI '{}'", path_or_uri)).into()
+        })
+}
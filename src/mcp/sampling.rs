@@ -0,0 +1,71 @@
+// src/mcp/sampling.rs
+
+use crate::errors::ServerError;
+use crate::mcp::peer::Peer;
+use crate::mcp::types::{
+    CreateMessageParams, CreateMessageResult, SamplingContent, SamplingMessage,
+};
+use anyhow::Result;
+
+/// Lets tools request completions from the client's LLM via `sampling/createMessage`.
+#[derive(Clone)]
+pub struct SamplingClient {
+    peer: Peer,
+}
+
+impl SamplingClient {
+    pub fn new(peer: Peer) -> Self {
+        Self { peer }
+    }
+
+    /// Whether the client declared the `sampling` capability.
+    pub fn is_available(&self) -> bool {
+        self.peer.client_supports("sampling")
+    }
+
+    /// Asks the client to sample a message.
+    pub async fn create_message(&self, params: CreateMessageParams) -> Result<CreateMessageResult> {
+        if !self.is_available() {
+            return Err(
+                ServerError::ToolError("The client does not support sampling".to_string()).into(),
+            );
+        }
+        let result = self
+            .peer
+            .request(
+                "sampling/createMessage",
+                Some(serde_json::to_value(params)?),
+            )
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Convenience wrapper that sends a single user prompt and returns the text reply.
+    pub async fn complete(
+        &self,
+        system_prompt: &str,
+        prompt: &str,
+        max_tokens: u32,
+    ) -> Result<String> {
+        let params = CreateMessageParams {
+            messages: vec![SamplingMessage {
+                role: "user".to_string(),
+                content: SamplingContent::Text {
+                    text: prompt.to_string(),
+                },
+            }],
+            system_prompt: Some(system_prompt.to_string()),
+            max_tokens,
+            temperature: Some(0.0),
+            model_preferences: None,
+            stop_sequences: None,
+        };
+        match self.create_message(params).await?.content {
+            SamplingContent::Text { text } => Ok(text),
+            SamplingContent::Image { .. } => Err(ServerError::ToolError(
+                "Expected a text reply from the client model, got an image".to_string(),
+            )
+            .into()),
+        }
+    }
+}
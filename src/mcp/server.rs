@@ -1,77 +1,283 @@
 // src/mcp/server.rs
 
 use crate::{
-    api::client::ApiClient,
+    api::backend::Backends,
     config::Config,
     errors::ServerError,
+    mcp::elicitation::ElicitationClient,
+    mcp::peer::Peer,
+    mcp::roots::Roots,
+    mcp::sampling::SamplingClient,
     mcp::types::{
-        InitializeResult, JsonRpcRequest, JsonRpcResponse, ResourcesListParams, ServerInfo,
-        ToolDefinition, ToolsCallParams, ToolsListResult,
+        InitializeParams, InitializeResult, JsonRpcRequest, JsonRpcResponse, ListChangedCapability,
+        ResourcesListParams, ServerCapabilities, ServerInfo, ToolsCallParams, ToolsListResult,
     },
-    tools, util,
+    tools::{context::ToolContext, registry::ToolRegistry},
+    util,
 };
 use anyhow::Result;
+use serde::Deserialize;
 use serde_json::{json, Value};
-use tokio::io::BufReader;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 
 const MCP_VERSION: &str = "2024-11-05";
 
+#[derive(Clone)]
 pub struct McpServer {
-    api_client: ApiClient,
+    config: Arc<Config>,
+    backends: Backends,
+    tools: Arc<RwLock<ToolRegistry>>,
+    sessions: Arc<Mutex<HashMap<u64, Session>>>,
+    next_session_id: Arc<AtomicU64>,
+}
+
+/// Per-connection state: the client handle and what we know about the client.
+#[derive(Clone)]
+struct Session {
+    peer: Peer,
+    roots: Roots,
+    /// Set once the client has been told in `initialize` that we send `listChanged`.
+    tools_list_changed: Arc<AtomicBool>,
+}
+
+impl Session {
+    fn new(outgoing: mpsc::UnboundedSender<String>) -> Self {
+        let peer = Peer::new(outgoing);
+        Self {
+            roots: Roots::new(peer.clone()),
+            peer,
+            tools_list_changed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Contents of the `--tools-config` file.
+#[derive(Deserialize, Debug, Default)]
+struct ToolsConfig {
+    #[serde(default)]
+    disabled: HashSet<String>,
 }
 
 impl McpServer {
+    /// Creates a server using the backends selected by `config`.
     pub async fn new(config: &Config) -> Result<Self> {
-        let api_client = ApiClient::new(config).await?;
-        Ok(Self { api_client })
+        let backends = Backends::from_config(config).await?;
+        Self::with_backends(config, backends)
+    }
+
+    /// Creates a server that uses the given backends, e.g. custom implementations
+    /// or decorators around the built-in ones.
+    pub fn with_backends(config: &Config, backends: Backends) -> Result<Self> {
+        let server = Self {
+            config: Arc::new(config.clone()),
+            backends,
+            tools: Arc::new(RwLock::new(ToolRegistry::with_builtin_tools())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            next_session_id: Arc::new(AtomicU64::new(1)),
+        };
+        server.reload_tools_config()?;
+        Ok(server)
+    }
+
+    /// Applies a change to the tool set and notifies every session that was told the
+    /// list can change, if the change is visible to clients.
+    pub fn update_tools<F: FnOnce(&mut ToolRegistry)>(&self, update: F) {
+        let (before, after) = {
+            let mut tools = self.tools.write().unwrap_or_else(|e| e.into_inner());
+            let before = tools.version();
+            update(&mut tools);
+            (before, tools.version())
+        };
+        if before == after {
+            return;
+        }
+
+        tracing::info!(version = after, "Tool list changed");
+        let sessions: Vec<Session> = self
+            .sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        for session in sessions {
+            if session.tools_list_changed.load(Ordering::Relaxed) {
+                if let Err(e) = session
+                    .peer
+                    .notify("notifications/tools/list_changed", None)
+                {
+                    tracing::warn!(error = %e, "Failed to send tools/list_changed");
+                }
+            }
+        }
     }
 
+    /// Re-reads `--tools-config` and enables or disables tools to match it.
+    pub fn reload_tools_config(&self) -> Result<()> {
+        let Some(path) = &self.config.tools_config else {
+            return Ok(());
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ServerError::ConfigError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let tools_config: ToolsConfig = serde_json::from_str(&contents).map_err(|e| {
+            ServerError::ConfigError(format!("Invalid tools config {}: {}", path.display(), e))
+        })?;
+
+        self.update_tools(|tools| {
+            for name in tools.names() {
+                tools.set_enabled(&name, !tools_config.disabled.contains(&name));
+            }
+        });
+        Ok(())
+    }
+
+    /// Serves a single client over stdio until stdin is closed.
     pub async fn run(&self) -> Result<()> {
-        let mut stdin = BufReader::new(tokio::io::stdin());
-        let mut stdout = tokio::io::stdout();
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Serves a single client over the given transport until `reader` reaches EOF.
+    ///
+    /// Several sessions may be served concurrently by the same server; they share
+    /// the backend client and tool set.
+    pub async fn serve<R, W>(&self, reader: R, writer: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut stdin = BufReader::new(reader);
+
+        // All output goes through a single writer task so that concurrently handled
+        // requests and server-initiated messages never interleave their frames.
+        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn(async move {
+            let mut stdout = writer;
+            while let Some(message) = outgoing_rx.recv().await {
+                util::write_message(&mut stdout, &message).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        });
+
+        let session = Session::new(outgoing_tx);
+        let session_id = self.next_session_id.fetch_add(1, Ordering::Relaxed);
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session_id, session.clone());
+        let peer = session.peer.clone();
+        let mut in_flight = JoinSet::new();
 
         loop {
             match util::read_message(&mut stdin).await? {
                 Some(message_str) => {
-                    let request: JsonRpcRequest = match serde_json::from_str(&message_str) {
-                        Ok(req) => req,
+                    let message: Value = match serde_json::from_str(&message_str) {
+                        Ok(message) => message,
                         Err(e) => {
                             let err_resp = self.create_error_response(
                                 None,
                                 -32700,
                                 format!("Parse error: {}", e),
                             );
-                            util::write_message(&mut stdout, &err_resp).await?;
+                            peer.send(err_resp)?;
                             continue;
                         }
                     };
 
-                    let request_id = request.id.clone();
-                    let response = self.dispatch(request).await;
+                    // Messages without a method are responses to our own requests
+                    if message.get("method").is_none() {
+                        match serde_json::from_value::<JsonRpcResponse>(message) {
+                            Ok(response) => {
+                                if !peer.handle_response(response) {
+                                    tracing::warn!("Ignoring response to an unknown request");
+                                }
+                            }
+                            Err(e) => {
+                                let err_resp = self.create_error_response(
+                                    None,
+                                    -32600,
+                                    format!("Invalid Request: {}", e),
+                                );
+                                peer.send(err_resp)?;
+                            }
+                        }
+                        continue;
+                    }
+
+                    let request: JsonRpcRequest = match serde_json::from_value(message) {
+                        Ok(req) => req,
+                        Err(e) => {
+                            let err_resp = self.create_error_response(
+                                None,
+                                -32600,
+                                format!("Invalid Request: {}", e),
+                            );
+                            peer.send(err_resp)?;
+                            continue;
+                        }
+                    };
+
+                    // Handled inline so that requests read after it see the new roots
+                    if request.method == "notifications/roots/list_changed" {
+                        session.roots.invalidate().await;
+                        continue;
+                    }
+
+                    // Requests are handled concurrently so that a tool waiting on the
+                    // client (e.g. for sampling) does not block reading its response.
+                    let server = self.clone();
+                    let session = session.clone();
+                    in_flight.spawn(async move {
+                        let request_id = request.id.clone();
+                        let response = server.dispatch(&session, request).await;
 
-                    if let Some(_id) = request_id {
-                        // It's a request, not a notification
-                        util::write_message(&mut stdout, &serde_json::to_string(&response)?)
-                            .await?;
-                    } // else it was a notification, no response needed
+                        if let Some(_id) = request_id {
+                            // It's a request, not a notification
+                            session.peer.send(serde_json::to_string(&response)?)?;
+                        } // else it was a notification, no response needed
+                        Ok::<_, anyhow::Error>(())
+                    });
+                    while let Some(done) = in_flight.try_join_next() {
+                        log_task_result(done);
+                    }
                 }
                 None => {
-                    // Stdin closed, exit loop
-                    tracing::info!("Stdin closed, shutting down.");
+                    // Input closed, exit loop
+                    tracing::info!("Input closed, shutting down.");
                     break;
                 }
             }
         }
+
+        // The client can no longer answer, so fail anything waiting on it, then let
+        // in-flight requests finish and flush their responses.
+        peer.cancel_pending();
+        while let Some(done) = in_flight.join_next().await {
+            log_task_result(done);
+        }
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&session_id);
+        drop(peer);
+        drop(session);
+        writer.await??;
         Ok(())
     }
 
-    async fn dispatch(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+    async fn dispatch(&self, session: &Session, request: JsonRpcRequest) -> JsonRpcResponse {
         let request_id = request.id.clone().unwrap_or(Value::Null);
 
         let result = match request.method.as_str() {
-            "initialize" => self.handle_initialize(request.params).await,
+            "initialize" => self.handle_initialize(session, request.params).await,
+            "notifications/initialized" => Ok(Value::Null),
             "tools/list" => self.handle_tools_list().await,
-            "tools/call" => self.handle_tools_call(request.params).await,
+            "tools/call" => self.handle_tools_call(session, request.params).await,
             "resources/list" => self.handle_resources_list(request.params).await,
             _ => Err(ServerError::MethodNotFound(request.method).into()),
         };
@@ -92,6 +298,7 @@ impl McpServer {
                         (-32601, format!("Method not found: {}", s))
                     }
                     Some(ServerError::InvalidParameters { .. }) => (-32602, e.to_string()),
+                    Some(ServerError::AccessDenied(_)) => (-32602, e.to_string()),
                     Some(ServerError::ToolError(s)) => (-32000, s.clone()),
                     _ => (-32603, e.to_string()), // Generic internal error
                 };
@@ -124,11 +331,20 @@ impl McpServer {
         serde_json::to_string(&error_response).unwrap_or_else(|_| "{\"jsonrpc\": \"2.0\", \"error\": {\"code\": -32603, \"message\": \"Internal error during error serialization\"}, \"id\": null}".to_string())
     }
 
-    async fn handle_initialize(&self, _params: Option<Value>) -> Result<Value> {
-        // Note: The current MCP spec for 'initialize' doesn't use any parameters,
-        // but we accept them for forward compatibility.
+    async fn handle_initialize(&self, session: &Session, params: Option<Value>) -> Result<Value> {
+        // Remember what the client can do for us, e.g. whether it supports sampling
+        if let Some(params) = params {
+            let params: InitializeParams = serde_json::from_value(params)?;
+            session.peer.set_client_capabilities(params.capabilities);
+        }
+        session.tools_list_changed.store(true, Ordering::Relaxed);
+
         let result = InitializeResult {
             protocol_version: MCP_VERSION.to_string(),
+            capabilities: ServerCapabilities {
+                tools: ListChangedCapability { list_changed: true },
+                resources: json!({}),
+            },
             server: ServerInfo {
                 name: "aionr2".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -138,48 +354,34 @@ impl McpServer {
     }
 
     async fn handle_tools_list(&self) -> Result<Value> {
-        let tools = vec![
-            ToolDefinition {
-                name: "run_inference".to_string(),
-                description: "Runs AI inference by calling the backend AION-R API.".to_string(),
-                inputs: json!({
-                    "type": "object",
-                    "properties": {
-                        "model": { "type": "string" },
-                        "prompt": { "type": "string" },
-                        "params": { "type": "object" }
-                    },
-                    "required": ["model", "prompt"]
-                }),
-            },
-            ToolDefinition {
-                name: "data_analysis".to_string(),
-                description: "Runs data analysis by calling the backend AION-R API.".to_string(),
-                inputs: json!({
-                    "type": "object",
-                    "properties": {
-                        "data": {},
-                        "ops": { "type": "array" }
-                    },
-                    "required": ["data", "ops"]
-                }),
-            },
-        ];
+        let tools = self
+            .tools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .definitions();
         let result = ToolsListResult { tools };
         Ok(serde_json::to_value(result)?)
     }
 
-    async fn handle_tools_call(&self, params: Option<Value>) -> Result<Value> {
+    async fn handle_tools_call(&self, session: &Session, params: Option<Value>) -> Result<Value> {
         let params: ToolsCallParams = serde_json::from_value(params.unwrap_or(Value::Null))?;
+        let ctx = ToolContext {
+            config: self.config.clone(),
+            inference: self.backends.inference.clone(),
+            analysis: self.backends.analysis.clone(),
+            sampling: SamplingClient::new(session.peer.clone()),
+            elicitation: ElicitationClient::new(session.peer.clone()),
+            roots: session.roots.clone(),
+        };
 
-        match params.name.as_str() {
-            "run_inference" => {
-                tools::inference::run_inference(&self.api_client, &params.inputs).await
-            }
-            "data_analysis" => {
-                tools::analytics::data_analysis(&self.api_client, &params.inputs).await
-            }
-            _ => {
+        let tool = self
+            .tools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&params.name);
+        match tool {
+            Some(tool) => tool.call(&ctx, &params.inputs).await,
+            None => {
                 Err(ServerError::MethodNotFound(format!("Tool '{}' not found", params.name)).into())
             }
         }
@@ -190,9 +392,17 @@ impl McpServer {
         match params.uri.as_str() {
             "aion-r://models/catalog" => {
                 // Example implementation: forward to an API endpoint
-                self.api_client.list_models().await
+                self.backends.inference.list_models().await
             }
             _ => Ok(json!([])), // Return empty list for unknown resources
         }
     }
 }
+
+fn log_task_result(result: Result<Result<()>, tokio::task::JoinError>) {
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::error!(error = %e, "Failed to send response"),
+        Err(e) => tracing::error!(error = %e, "Request handler panicked"),
+    }
+}
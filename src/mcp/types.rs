@@ -36,19 +36,33 @@ pub struct JsonRpcError {
 // MCP Method-specific Parameters and Results
 
 #[derive(Serialize, Deserialize, Debug)]
-#[allow(dead_code)] // Not constructed by the server, but part of the spec
 pub struct InitializeParams {
     #[serde(rename = "protocolVersion")]
     pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: Value,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct InitializeResult {
     #[serde(rename = "protocolVersion")]
     pub protocol_version: String,
+    pub capabilities: ServerCapabilities,
     pub server: ServerInfo,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ServerCapabilities {
+    pub tools: ListChangedCapability,
+    pub resources: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListChangedCapability {
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ServerInfo {
     pub name: String,
@@ -60,7 +74,7 @@ pub struct ToolsListResult {
     pub tools: Vec<ToolDefinition>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: String,
@@ -89,3 +103,73 @@ pub struct ResourcesListParams {
 pub struct ResourcesListResult {
     pub resources: Vec<Value>,
 }
+
+// Sampling (server-to-client)
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SamplingMessage {
+    pub role: String,
+    pub content: SamplingContent,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SamplingContent {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: String,
+    pub content: SamplingContent,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+// Elicitation (server-to-client)
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitParams {
+    pub message: String,
+    pub requested_schema: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Value>,
+}
@@ -1,30 +1,411 @@
 // src/tools/analytics.rs
 
-use crate::api::client::ApiClient;
+use crate::analysis::columnar::ScanPlan;
+use crate::analysis::engine;
+use crate::analysis::ops::Op;
+use crate::analysis::render::{OutputFormat, RenderOptions};
+use crate::analysis::sample::{sample, SampleMethod, SampleSpec};
+use crate::analysis::table::Table;
+use crate::analysis::validate::validate;
 use crate::errors::ServerError;
+use crate::mcp::types::ToolDefinition;
+use crate::tools::context::ToolContext;
+use crate::tools::dataset::{self, load_dataset, Dataset};
+use crate::tools::registry::Tool;
 use anyhow::Result;
-use serde_json::Value;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Attempts at shrinking a sample until it fits the backend byte budget.
+const MAX_SAMPLING_ROUNDS: usize = 8;
+
+/// Number of data items shown to the client model when planning ops from a query.
+const PLANNING_SAMPLE_SIZE: usize = 5;
+
+const PLANNING_SYSTEM_PROMPT: &str = "You translate data analysis requests into \
+operations for the AION-R analysis API. Reply with only a JSON array of operations, \
+for example [\"mean\", \"std_dev\"], and no other text.";
+
+/// Where `data_analysis` runs its ops.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisMode {
+    /// Run on the local engine, failing if an op is not supported there.
+    Local,
+    /// Always forward to the analysis backend.
+    Remote,
+    /// Run locally when every op is supported, otherwise forward to the backend.
+    #[default]
+    Auto,
+}
+
+pub struct DataAnalysisTool;
+
+#[async_trait]
+impl Tool for DataAnalysisTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "data_analysis".to_string(),
+            description: "Runs data analysis locally or by calling the backend AION-R API."
+                .to_string(),
+            inputs: dataset::input_schema(json!({
+                    "ops": {
+                        "type": "array",
+                        "items": {
+                            "anyOf": [
+                                Op::schema(),
+                                {
+                                    "type": "string",
+                                    "description": "Shorthand for an op without arguments, or the name of an op only the backend supports."
+                                }
+                            ]
+                        }
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Natural-language request used to choose 'ops' with the client's model when 'ops' is omitted."
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["local", "remote", "auto"],
+                        "description": "Run the ops on the local engine, on the backend, or locally when all ops are supported (default)."
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["markdown", "csv", "text", "json"],
+                        "description": "How returned rows are also shown as text content: a Markdown table (default), CSV, aligned plain text, or not at all."
+                    },
+                    "max_display_rows": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Most rows shown as text (default 50); all rows are still returned as JSON."
+                    },
+                    "max_display_columns": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Most columns shown as text (default 20)."
+                    },
+                    "sampling": {
+                        "type": "object",
+                        "description": "How data larger than the server's row or byte budget for the backend is sampled down before it is sent (default random with seed 0).",
+                        "properties": {
+                            "method": { "type": "string", "enum": ["random", "stratified", "head_tail"] },
+                            "by": { "type": "string", "description": "Column whose groups a stratified sample keeps in proportion." },
+                            "seed": { "type": "integer", "minimum": 0 }
+                        },
+                        "additionalProperties": false
+                    }
+            })),
+        }
+    }
+
+    async fn call(&self, ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+        data_analysis(ctx, inputs).await
+    }
+}
 
 /// Tool: data_analysis
-/// Runs data analysis by calling the backend AION-R API.
-pub async fn data_analysis(api_client: &ApiClient, inputs: &Value) -> Result<Value> {
-    let data = inputs
-        .get("data")
-        .ok_or_else(|| ServerError::InvalidParameters {
-            method: "data_analysis".to_string(),
-            details: "Missing 'data' field".to_string(),
-        })?;
+/// Runs data analysis locally or by calling the backend AION-R API.
+///
+/// Common ops over tabular data run on the local engine; `mode` decides whether
+/// the others fall through to the backend.
+/// The dataset is inline `data`, a `data_file` inside the client's roots, or a
+/// `data_uri` naming such a file or an `aion-r://` resource. It may be JSON or CSV,
+/// TSV, or NDJSON text, which is parsed into a table first, or a Parquet or Arrow
+/// IPC file, of which only the columns and row groups the ops need are read, or a
+/// sheet or range of an Excel workbook.
+/// Instead of `ops`, callers may pass a natural-language `query`; the ops are then
+/// chosen by the client's model through sampling and echoed back as `planned_ops`.
+/// Data going to the backend is first sampled down to the configured row and byte
+/// budgets, as described by `sampling` in the result.
+pub async fn data_analysis(ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+    let mode = match inputs.get("mode") {
+        Some(mode) => AnalysisMode::deserialize(mode).map_err(|_| {
+            ServerError::invalid(
+                "data_analysis",
+                "'mode' must be one of 'local', 'remote', or 'auto'",
+            )
+        })?,
+        None => AnalysisMode::default(),
+    };
+    let output = render_options(inputs)?;
+    let sampling = sample_spec(inputs)?;
+    // Columnar files are read only as far as local ops need them.
+    let scan = match mode {
+        AnalysisMode::Remote => ScanPlan::default(),
+        _ => ScanPlan::from_ops(inputs.get("ops")),
+    };
+    let dataset = load_dataset(ctx, inputs, &scan).await?;
+
+    let (ops, planned) = match (inputs.get("ops"), inputs["query"].as_str()) {
+        (Some(ops), _) => (ops.clone(), false),
+        (None, Some(query)) if ctx.sampling.is_available() => {
+            let sample = dataset.sample(PLANNING_SAMPLE_SIZE)?;
+            (plan_ops(ctx, &sample, query).await?, true)
+        }
+        (None, Some(_)) => {
+            return Err(ServerError::invalid(
+                "data_analysis",
+                "A 'query' requires a client that supports sampling; pass 'ops' instead",
+            )
+            .into())
+        }
+        (None, None) => {
+            return Err(ServerError::invalid("data_analysis", "Missing 'ops' field").into())
+        }
+    };
+
+    let local_ops = match mode {
+        AnalysisMode::Remote => None,
+        AnalysisMode::Local => Some(parse_local_ops(&ops)?),
+        AnalysisMode::Auto => Op::parse_all(&ops).filter(|_| dataset.is_tabular()),
+    };
 
-    let ops = inputs
-        .get("ops")
-        .ok_or_else(|| ServerError::InvalidParameters {
-            method: "data_analysis".to_string(),
-            details: "Missing 'ops' field".to_string(),
+    tracing::info!(
+        planned,
+        local = local_ops.is_some(),
+        "Executing data_analysis tool"
+    );
+
+    let mut result = match local_ops {
+        Some(local_ops) => {
+            tokio::task::spawn_blocking(move || run_local(dataset, local_ops, &output))
+                .await
+                .map_err(|e| ServerError::ToolError(format!("The analysis task failed: {}", e)))??
+        }
+        None => {
+            let (data, sampled) = fit_backend_budget(ctx, dataset.into_json()?, &sampling)?;
+            let mut result = ctx.analysis.data_analysis(&data, &ops).await?;
+            if let (Some(sampled), Some(obj)) = (sampled, result.as_object_mut()) {
+                obj.insert("sampling".to_string(), sampled);
+            }
+            result
+        }
+    };
+    if result.get("content").is_none() {
+        let table = result.get("rows").and_then(Table::from_json);
+        if let Some(content) = table.and_then(|table| output.content(&table)) {
+            result["content"] = content;
+        }
+    }
+
+    if planned {
+        if let Some(obj) = result.as_object_mut() {
+            obj.insert("planned_ops".to_string(), ops);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads `output_format`, `max_display_rows`, and `max_display_columns`.
+fn render_options(inputs: &Value) -> Result<RenderOptions> {
+    let mut options = RenderOptions::default();
+    if let Some(format) = inputs.get("output_format") {
+        options.format = OutputFormat::deserialize(format).map_err(|_| {
+            ServerError::invalid(
+                "data_analysis",
+                "'output_format' must be one of 'markdown', 'csv', 'text', or 'json'",
+            )
         })?;
+    }
+    for (key, limit) in [
+        ("max_display_rows", &mut options.max_rows),
+        ("max_display_columns", &mut options.max_columns),
+    ] {
+        if let Some(value) = inputs.get(key) {
+            *limit = value.as_u64().filter(|&n| n > 0).ok_or_else(|| {
+                ServerError::invalid(
+                    "data_analysis",
+                    format!("'{}' must be a positive integer", key),
+                )
+            })? as usize;
+        }
+    }
+    Ok(options)
+}
+
+/// Reads `sampling`.
+fn sample_spec(inputs: &Value) -> Result<SampleSpec> {
+    let spec = match inputs.get("sampling") {
+        Some(sampling) => SampleSpec::deserialize(sampling).map_err(|e| {
+            ServerError::invalid("data_analysis", format!("Invalid 'sampling': {}", e))
+        })?,
+        None => SampleSpec::default(),
+    };
+    if spec.method == SampleMethod::Stratified && spec.by.is_none() {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            "Stratified sampling needs a 'by' column",
+        )
+        .into());
+    }
+    Ok(spec)
+}
+
+/// Samples `data` down to the backend row and byte budgets, if it exceeds them.
+/// Bytes are counted on the data as the backend sends it, after any privacy policy.
+/// Returns the data to send and, if it was sampled, a description of how.
+fn fit_backend_budget(
+    ctx: &ToolContext,
+    data: Value,
+    spec: &SampleSpec,
+) -> Result<(Value, Option<Value>)> {
+    let config = &ctx.config;
+    let outgoing_bytes = |data: &Value| -> Result<u64> {
+        Ok(serde_json::to_vec(&*ctx.analysis.outgoing_data(data)?)?.len() as u64)
+    };
+    if config.max_backend_rows.is_none() && config.max_backend_bytes.is_none() {
+        return Ok((data, None));
+    }
+    let max_rows = config.max_backend_rows.unwrap_or(usize::MAX);
+    let max_bytes = config.max_backend_bytes.unwrap_or(u64::MAX);
+    let bytes = outgoing_bytes(&data)?;
+    let table = Table::from_json(&data);
+    let rows = table.as_ref().map(|table| table.rows.len());
+    if bytes <= max_bytes && rows.is_none_or(|rows| rows <= max_rows) {
+        return Ok((data, None));
+    }
+    let table = table.ok_or_else(|| {
+        ServerError::invalid(
+            "data_analysis",
+            format!(
+                "The data is {} bytes, over the backend budget of {}, and is not tabular, so it cannot be sampled",
+                bytes, max_bytes
+            ),
+        )
+    })?;
 
-    tracing::info!("Executing data_analysis tool");
+    // Start from the average row size, then shrink until the sample fits.
+    let total = table.rows.len();
+    let mut n = max_rows
+        .min(total)
+        .min((max_bytes as f64 / bytes as f64 * total as f64) as usize);
+    let mut rounds = 1;
+    let (sampled_rows, sampled, sampled_bytes) = loop {
+        let sampled = sample(&table, n, spec)?;
+        let json = sampled.to_json();
+        let sampled_bytes = outgoing_bytes(&json)?;
+        if sampled_bytes <= max_bytes || n == 0 || rounds == MAX_SAMPLING_ROUNDS {
+            break (sampled.rows.len(), json, sampled_bytes);
+        }
+        n = (n as f64 * max_bytes as f64 / sampled_bytes as f64 * 0.95) as usize;
+        rounds += 1;
+    };
+    if sampled_rows == 0 || sampled_bytes > max_bytes {
+        return Err(ServerError::invalid(
+            "data_analysis",
+            format!(
+                "The data cannot be sampled below the backend budget of {} bytes",
+                max_bytes
+            ),
+        )
+        .into());
+    }
 
-    let result = api_client.data_analysis(data, ops).await?;
+    tracing::info!(
+        method = spec.method.name(),
+        rows = total,
+        sampled = sampled_rows,
+        "Sampled data for the analysis backend"
+    );
+    let mut description = spec.describe();
+    description["rows_before"] = json!(total);
+    description["rows_after"] = json!(sampled_rows);
+    description["bytes_before"] = json!(bytes);
+    description["bytes_after"] = json!(sampled_bytes);
+    Ok((sampled, Some(description)))
+}
 
+/// Parses ops for the local engine, explaining which one it cannot run.
+pub(crate) fn parse_local_ops(ops: &Value) -> Result<Vec<Op>> {
+    let ops = ops
+        .as_array()
+        .ok_or_else(|| ServerError::invalid("data_analysis", "'ops' must be an array"))?
+        .iter()
+        .enumerate()
+        .map(|(i, op)| {
+            Op::parse(op).map_err(|e| {
+                let name = op.as_str().or_else(|| op["op"].as_str()).unwrap_or("?");
+                ServerError::invalid(
+                    "data_analysis",
+                    format!(
+                        "ops[{}] ({}) is not supported by the local engine: {}",
+                        i, name, e
+                    ),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ops)
+}
+
+/// Validates and runs `ops` on the local engine. Arrow data runs on its arrays when
+/// the pipeline allows it, and on the row engine otherwise, whose final table is
+/// rendered as text content in its own column order.
+fn run_local(dataset: Dataset, ops: Vec<Op>, output: &RenderOptions) -> Result<Value> {
+    let table = match dataset {
+        Dataset::Arrow(data) => {
+            let ops = validate(data.schema(), ops)?;
+            if let Some(result) = data.execute(&ops) {
+                return result;
+            }
+            let mut result = engine::execute(data.to_table(None)?, &ops)?;
+            result["scan"] = data.scan;
+            return Ok(result);
+        }
+        dataset => dataset.into_table()?,
+    };
+    let ops = validate(table.schema(), ops)?;
+    let outcome = engine::run(table, &ops)?;
+    let content = if outcome.transformed {
+        output.content(&outcome.table)
+    } else {
+        None
+    };
+    let mut result = outcome.into_json();
+    if let Some(content) = content {
+        result["content"] = content;
+    }
     Ok(result)
 }
+
+/// Asks the client's model to pick analysis ops for a natural-language query.
+async fn plan_ops(ctx: &ToolContext, data: &Value, query: &str) -> Result<Value> {
+    let sample = match data.as_array() {
+        Some(items) => Value::from(
+            items
+                .iter()
+                .take(PLANNING_SAMPLE_SIZE)
+                .cloned()
+                .collect::<Vec<_>>(),
+        ),
+        None => data.clone(),
+    };
+    let prompt = format!(
+        "Request: {}\n\nData sample (first {} items):\n{}",
+        query, PLANNING_SAMPLE_SIZE, sample
+    );
+
+    let reply = ctx
+        .sampling
+        .complete(PLANNING_SYSTEM_PROMPT, &prompt, 512)
+        .await?;
+
+    parse_ops_reply(&reply).ok_or_else(|| {
+        ServerError::ToolError(format!(
+            "The client model did not return a JSON array of ops: {}",
+            reply
+        ))
+        .into()
+    })
+}
+
+/// Extracts the JSON array from a model reply, tolerating surrounding prose or code fences.
+fn parse_ops_reply(reply: &str) -> Option<Value> {
+    let start = reply.find('[')?;
+    let end = reply.rfind(']')?;
+    let ops: Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    ops.is_array().then_some(ops)
+}
@@ -0,0 +1,185 @@
+// src/tools/chart.rs
+
+use crate::analysis::chart::{render_svg, svg_to_png, ChartSpec};
+use crate::analysis::columnar::ScanPlan;
+use crate::analysis::engine;
+use crate::analysis::ops::Op;
+use crate::analysis::validate::validate;
+use crate::errors::ServerError;
+use crate::mcp::types::ToolDefinition;
+use crate::tools::analytics::parse_local_ops;
+use crate::tools::context::ToolContext;
+use crate::tools::dataset::{self, load_dataset};
+use crate::tools::registry::Tool;
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Smallest and largest chart width or height, in pixels.
+const SIZE_RANGE: std::ops::RangeInclusive<u32> = 100..=4000;
+
+/// Image formats a chart can be returned in.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+pub struct RenderChartTool;
+
+#[async_trait]
+impl Tool for RenderChartTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "render_chart".to_string(),
+            description: "Draws a line, bar, scatter, or histogram chart of a dataset, optionally after running local analysis ops, and returns it as image content.".to_string(),
+            inputs: {
+                let mut inputs = dataset::input_schema(json!({
+                "chart": {
+                    "type": "string",
+                    "enum": ["line", "bar", "scatter", "histogram"]
+                },
+                "x": {
+                    "type": "string",
+                    "description": "Column along the horizontal axis: numbers, timestamps, or (for line and bar charts) categories. The row number if omitted. For histograms, the column to bin."
+                },
+                "y": {
+                    "anyOf": [
+                        { "type": "string" },
+                        { "type": "array", "items": { "type": "string" } }
+                    ],
+                    "description": "Numeric column or columns drawn as series; every other numeric column if omitted."
+                },
+                "bins": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 200,
+                    "description": "Number of histogram bins; chosen from the number of values if omitted."
+                },
+                "title": { "type": "string" },
+                "x_label": { "type": "string" },
+                "y_label": { "type": "string" },
+                "width": {
+                    "type": "integer",
+                    "minimum": 100,
+                    "maximum": 4000,
+                    "description": "Width in pixels (default 800)."
+                },
+                "height": {
+                    "type": "integer",
+                    "minimum": 100,
+                    "maximum": 4000,
+                    "description": "Height in pixels (default 500)."
+                },
+                "ops": {
+                    "type": "array",
+                    "items": Op::schema(),
+                    "description": "Local data_analysis ops run first; the table they produce is drawn."
+                },
+                "formats": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["png", "svg"] },
+                    "description": "Images to return (default [\"png\"])."
+                }
+                }));
+                inputs["required"] = json!(["chart"]);
+                inputs
+            },
+        }
+    }
+
+    async fn call(&self, ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+        render_chart(ctx, inputs).await
+    }
+}
+
+/// Tool: render_chart
+/// Draws a chart of a dataset on the server and returns it as MCP image content.
+///
+/// The dataset is given as for `data_analysis`; local `ops` may reshape it first,
+/// e.g. `group_by` before a bar chart or `resample` before a line chart.
+pub async fn render_chart(ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+    let spec = ChartSpec::deserialize(inputs)
+        .map_err(|e| ServerError::invalid("render_chart", e.to_string()))?;
+    if !SIZE_RANGE.contains(&spec.width) || !SIZE_RANGE.contains(&spec.height) {
+        return Err(ServerError::invalid(
+            "render_chart",
+            format!(
+                "'width' and 'height' must be between {} and {} pixels",
+                SIZE_RANGE.start(),
+                SIZE_RANGE.end()
+            ),
+        )
+        .into());
+    }
+    let formats = match inputs.get("formats") {
+        Some(formats) => Vec::<ImageFormat>::deserialize(formats)
+            .ok()
+            .filter(|formats| !formats.is_empty())
+            .ok_or_else(|| {
+                ServerError::invalid(
+                    "render_chart",
+                    "'formats' must be a non-empty array of 'png' and 'svg'",
+                )
+            })?,
+        None => vec![ImageFormat::Png],
+    };
+
+    let table = async {
+        let table = load_dataset(ctx, inputs, &ScanPlan::default())
+            .await?
+            .into_table()?;
+        match inputs.get("ops") {
+            Some(ops) => {
+                let ops = validate(table.schema(), parse_local_ops(ops)?)?;
+                tokio::task::spawn_blocking(move || Ok(engine::run(table, &ops)?.table))
+                    .await
+                    .map_err(|e| {
+                        ServerError::ToolError(format!("The analysis task failed: {}", e))
+                    })?
+            }
+            None => Ok(table),
+        }
+    }
+    .await
+    .map_err(|e| ServerError::with_method(e, "render_chart"))?;
+
+    tracing::info!(
+        chart = spec.kind.name(),
+        rows = table.rows.len(),
+        "Executing render_chart tool"
+    );
+
+    let rows = table.rows.len();
+    let summary = format!("{} chart of {} rows", spec.kind.name(), rows);
+    let images = tokio::task::spawn_blocking(move || -> Result<Vec<Value>> {
+        let svg = render_svg(&table, &spec)?;
+        formats
+            .iter()
+            .map(|format| {
+                Ok(match format {
+                    ImageFormat::Png => image(&svg_to_png(&svg)?, "image/png"),
+                    ImageFormat::Svg => image(svg.as_bytes(), "image/svg+xml"),
+                })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| ServerError::ToolError(format!("The chart task failed: {}", e)))??;
+
+    let mut content = images;
+    content.push(json!({ "type": "text", "text": summary }));
+    Ok(json!({ "content": content }))
+}
+
+fn image(data: &[u8], mime_type: &str) -> Value {
+    json!({
+        "type": "image",
+        "data": BASE64.encode(data),
+        "mimeType": mime_type,
+    })
+}
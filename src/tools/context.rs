@@ -0,0 +1,19 @@
+// src/tools/context.rs
+
+use crate::api::backend::{AnalysisBackend, InferenceBackend};
+use crate::config::Config;
+use crate::mcp::elicitation::ElicitationClient;
+use crate::mcp::roots::Roots;
+use crate::mcp::sampling::SamplingClient;
+use std::sync::Arc;
+
+/// Everything a tool invocation may use: the backends and handles back to the client.
+#[derive(Clone)]
+pub struct ToolContext {
+    pub config: Arc<Config>,
+    pub inference: Arc<dyn InferenceBackend>,
+    pub analysis: Arc<dyn AnalysisBackend>,
+    pub sampling: SamplingClient,
+    pub elicitation: ElicitationClient,
+    pub roots: Roots,
+}
@@ -0,0 +1,317 @@
+// src/tools/dataset.rs
+
+use crate::analysis::columnar::{self, ArrowData, ScanPlan};
+use crate::analysis::excel::{self, ExcelOptions};
+use crate::analysis::formats::{self, CsvOptions, DataFormat};
+use crate::analysis::table::Table;
+use crate::errors::ServerError;
+use crate::tools::context::ToolContext;
+use crate::util;
+use anyhow::Result;
+use bytes::Bytes;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use url::Url;
+
+/// Input schema of a tool reading a dataset: the dataset source properties, plus `properties`.
+pub(crate) fn input_schema(properties: Value) -> Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": source_properties(),
+    });
+    if let (Some(all), Value::Object(extra)) = (schema["properties"].as_object_mut(), properties) {
+        all.extend(extra);
+    }
+    schema
+}
+
+/// Schema properties naming a dataset and how to parse it, as read by [`load_dataset`].
+pub(crate) fn source_properties() -> Value {
+    json!({
+        "data": {
+            "description": "Inline dataset: JSON, or CSV, TSV, or NDJSON text."
+        },
+        "data_file": {
+            "type": "string",
+            "description": "Absolute path or file:// URI of a dataset inside one of the client's roots, used instead of 'data'."
+        },
+        "data_uri": {
+            "type": "string",
+            "description": "file:// URI of a dataset inside one of the client's roots, or an aion-r:// resource such as aion-r://datasets/<id>, used instead of 'data'."
+        },
+        "format": {
+            "type": "string",
+            "enum": ["json", "csv", "tsv", "ndjson", "parquet", "arrow", "excel"],
+            "description": "Format of text 'data' or of 'data_file'; detected from the content or file extension if omitted. Parquet, Arrow IPC, and Excel data must come from 'data_file' or 'data_uri'."
+        },
+        "excel": {
+            "type": "object",
+            "properties": {
+                "sheet": { "type": "string", "description": "Worksheet to read; the first sheet by default." },
+                "range": { "type": "string", "description": "Cells to read, such as 'B2:F40' or 'Sales!B2:F40', or a defined name; the sheet's used range by default." },
+                "header": { "type": "boolean", "description": "Whether the first row holds column names; detected if omitted." }
+            }
+        },
+        "csv": {
+            "type": "object",
+            "properties": {
+                "header": { "type": "boolean", "description": "Whether the first row holds column names; detected if omitted." },
+                "delimiter": { "type": "string", "description": "Field delimiter; ',' for CSV and tab for TSV by default." },
+                "quote": { "type": "string", "description": "Quote character, '\"' by default." },
+                "infer_types": { "type": "boolean", "description": "Convert numbers and booleans from text (default true)." }
+            }
+        }
+    })
+}
+
+/// A loaded dataset.
+pub(crate) enum Dataset {
+    /// JSON data, sent to the backend as is.
+    Json(Value),
+    /// Text or a workbook parsed into a table.
+    Table(Table),
+    /// Parquet or Arrow IPC data.
+    Arrow(ArrowData),
+}
+
+impl Dataset {
+    /// Parses text in `format`.
+    fn parse(text: &str, format: DataFormat, options: &CsvOptions) -> Result<Self> {
+        if format == DataFormat::Json {
+            let data = serde_json::from_str(text).map_err(|e| {
+                ServerError::invalid("data_analysis", format!("Invalid JSON data: {}", e))
+            })?;
+            return Ok(Dataset::Json(data));
+        }
+        Ok(Dataset::Table(formats::parse_table(text, format, options)?))
+    }
+
+    /// Decodes file contents; `format` is guessed from the contents if unknown.
+    fn read(
+        bytes: Vec<u8>,
+        format: Option<DataFormat>,
+        options: &CsvOptions,
+        excel: &ExcelOptions,
+        scan: &ScanPlan,
+    ) -> Result<Self> {
+        match format.or_else(|| DataFormat::detect_binary(&bytes)) {
+            Some(DataFormat::Parquet) => Ok(Dataset::Arrow(columnar::read_parquet(
+                Bytes::from(bytes),
+                scan,
+            )?)),
+            Some(DataFormat::Arrow) => Ok(Dataset::Arrow(columnar::read_ipc(
+                Cursor::new(bytes),
+                scan,
+            )?)),
+            Some(DataFormat::Excel) => Ok(Dataset::Table(excel::read_workbook(bytes, excel)?)),
+            format => {
+                let text = String::from_utf8(bytes).map_err(|_| {
+                    ServerError::invalid("data_analysis", "The data is not valid UTF-8 text")
+                })?;
+                let format = format.unwrap_or_else(|| DataFormat::detect(&text));
+                Self::parse(&text, format, options)
+            }
+        }
+    }
+
+    /// Decodes a Parquet or Arrow IPC file, reading only the parts `scan` needs.
+    fn read_columnar(file: std::fs::File, format: DataFormat, scan: &ScanPlan) -> Result<Self> {
+        let data = match format {
+            DataFormat::Parquet => columnar::read_parquet(file, scan)?,
+            _ => columnar::read_ipc(BufReader::new(file), scan)?,
+        };
+        Ok(Dataset::Arrow(data))
+    }
+
+    pub(crate) fn is_tabular(&self) -> bool {
+        match self {
+            Dataset::Json(data) => Table::from_json(data).is_some(),
+            Dataset::Table(_) | Dataset::Arrow(_) => true,
+        }
+    }
+
+    /// The dataset as JSON, which is what the backend receives: tables become an
+    /// array of row objects.
+    pub(crate) fn into_json(self) -> Result<Value> {
+        Ok(match self {
+            Dataset::Json(data) => data,
+            Dataset::Table(table) => table.to_json(),
+            Dataset::Arrow(data) => data.to_table(None)?.to_json(),
+        })
+    }
+
+    /// The dataset as a table, for the local engines.
+    pub(crate) fn into_table(self) -> Result<Table> {
+        match self {
+            Dataset::Json(data) => Table::from_json(&data).ok_or_else(|| {
+                ServerError::invalid(
+                    "data_analysis",
+                    "The data is not tabular: expected an array of objects, an object of arrays, or an array of scalars",
+                )
+                .into()
+            }),
+            Dataset::Table(table) => Ok(table),
+            Dataset::Arrow(data) => data.to_table(None),
+        }
+    }
+
+    /// The first `n` items or rows, shown to the client model when planning ops.
+    pub(crate) fn sample(&self, n: usize) -> Result<Value> {
+        Ok(match self {
+            Dataset::Json(Value::Array(items)) => {
+                Value::Array(items.iter().take(n).cloned().collect())
+            }
+            Dataset::Json(data) => data.clone(),
+            Dataset::Table(table) => Table {
+                columns: table.columns.clone(),
+                rows: table.rows.iter().take(n).cloned().collect(),
+            }
+            .to_json(),
+            Dataset::Arrow(data) => data.to_table(Some(n))?.to_json(),
+        })
+    }
+}
+
+/// Loads the dataset from inline `data`, `data_file`, or `data_uri`, honouring `format` and `csv`.
+pub(crate) async fn load_dataset(
+    ctx: &ToolContext,
+    inputs: &Value,
+    scan: &ScanPlan,
+) -> Result<Dataset> {
+    let format: Option<DataFormat> = match inputs.get("format") {
+        Some(format) => Some(DataFormat::deserialize(format).map_err(|_| {
+            ServerError::invalid(
+                "data_analysis",
+                "'format' must be one of 'json', 'csv', 'tsv', 'ndjson', 'parquet', 'arrow', or 'excel'",
+            )
+        })?),
+        None => None,
+    };
+    let options: CsvOptions = match inputs.get("csv") {
+        Some(options) => CsvOptions::deserialize(options).map_err(|e| {
+            ServerError::invalid("data_analysis", format!("Invalid 'csv' options: {}", e))
+        })?,
+        None => CsvOptions::default(),
+    };
+    let excel: ExcelOptions = match inputs.get("excel") {
+        Some(options) => ExcelOptions::deserialize(options).map_err(|e| {
+            ServerError::invalid("data_analysis", format!("Invalid 'excel' options: {}", e))
+        })?,
+        None => ExcelOptions::default(),
+    };
+
+    let location = match (inputs["data_file"].as_str(), inputs["data_uri"].as_str()) {
+        (Some(_), Some(_)) => {
+            return Err(ServerError::invalid(
+                "data_analysis",
+                "Pass either 'data_file' or 'data_uri', not both",
+            )
+            .into())
+        }
+        (file, uri) => file.or(uri),
+    };
+
+    match (inputs.get("data"), location) {
+        (Some(_), None) if format.is_some_and(DataFormat::is_binary) => Err(ServerError::invalid(
+            "data_analysis",
+            "Parquet, Arrow, and Excel data must be read from 'data_file' or 'data_uri'",
+        )
+        .into()),
+        (Some(Value::String(text)), None) if format != Some(DataFormat::Json) => {
+            let format = format.unwrap_or_else(|| DataFormat::detect(text));
+            Dataset::parse(text, format, &options)
+        }
+        (Some(data), None) => match format {
+            None | Some(DataFormat::Json) => Ok(Dataset::Json(data.clone())),
+            Some(_) => Err(ServerError::invalid(
+                "data_analysis",
+                "CSV, TSV, and NDJSON 'data' must be given as a string",
+            )
+            .into()),
+        },
+        (None, Some(location)) => {
+            let format = format.or_else(|| DataFormat::from_path(Path::new(location)));
+            let dataset = match read_location(ctx, location).await? {
+                Location::File(mut file) => {
+                    let format = match format {
+                        Some(format) => Some(format),
+                        None => sniff_format(&mut file).await?,
+                    };
+                    match format {
+                        Some(format @ (DataFormat::Parquet | DataFormat::Arrow)) => {
+                            let file = file.into_std().await;
+                            let scan = scan.clone();
+                            tokio::task::spawn_blocking(move || {
+                                Dataset::read_columnar(file, format, &scan)
+                            })
+                            .await
+                            .map_err(|e| {
+                                ServerError::ToolError(format!("Reading the file failed: {}", e))
+                            })?
+                        }
+                        format => {
+                            let max_bytes = ctx.config.max_data_bytes;
+                            let bytes = util::read_limited(file, max_bytes, location).await?;
+                            Dataset::read(bytes, format, &options, &excel, scan)
+                        }
+                    }
+                }
+                Location::Bytes(bytes) => Dataset::read(bytes, format, &options, &excel, scan),
+                Location::Json(data) => Ok(Dataset::Json(data)),
+            };
+            dataset.map_err(|e| {
+                ServerError::with_method_context(e, "data_analysis", &format!("'{}'", location))
+            })
+        }
+        (Some(_), Some(_)) => Err(ServerError::invalid(
+            "data_analysis",
+            "Pass only one of 'data', 'data_file', or 'data_uri'",
+        )
+        .into()),
+        (None, None) => Err(ServerError::invalid("data_analysis", "Missing 'data' field").into()),
+    }
+}
+
+/// The contents behind a `data_file` or `data_uri`.
+enum Location {
+    /// A file inside the client's roots, opened but not yet read.
+    File(tokio::fs::File),
+    Bytes(Vec<u8>),
+    Json(Value),
+}
+
+/// Guesses a binary format from the first bytes of `file`, leaving it at the start.
+async fn sniff_format(file: &mut tokio::fs::File) -> Result<Option<DataFormat>> {
+    let mut head = Vec::new();
+    (&mut *file).take(8).read_to_end(&mut head).await?;
+    file.rewind().await?;
+    Ok(DataFormat::detect_binary(&head))
+}
+
+/// Opens a file inside the client's roots or reads an `aion-r://` resource, failing
+/// if either is over the configured size limit.
+async fn read_location(ctx: &ToolContext, location: &str) -> Result<Location> {
+    let max_bytes = ctx.config.max_data_bytes;
+    match Url::parse(location).ok().filter(|u| u.scheme() == "aion-r") {
+        Some(url) => match (url.host_str(), url.path().trim_matches('/')) {
+            (Some("models"), "catalog") => Ok(Location::Json(ctx.inference.list_models().await?)),
+            (Some("datasets"), id) if !id.is_empty() => Ok(Location::Bytes(
+                ctx.analysis.read_dataset(id, max_bytes).await?,
+            )),
+            _ => Err(ServerError::invalid(
+                "data_analysis",
+                format!(
+                    "Unknown resource '{}'; expected aion-r://datasets/<id> or aion-r://models/catalog",
+                    location
+                ),
+            )
+            .into()),
+        },
+        None => Ok(Location::File(
+            ctx.roots.open_file(location, max_bytes).await?,
+        )),
+    }
+}
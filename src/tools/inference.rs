@@ -1,20 +1,50 @@
 // src/tools/inference.rs
 
-use crate::api::client::ApiClient;
 use crate::errors::ServerError;
+use crate::mcp::types::ToolDefinition;
+use crate::tools::context::ToolContext;
+use crate::tools::registry::Tool;
 use anyhow::Result;
-use serde_json::Value;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Rough number of prompt characters per token, used for cost estimates.
+const CHARS_PER_TOKEN: u64 = 4;
+
+pub struct RunInferenceTool;
+
+#[async_trait]
+impl Tool for RunInferenceTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "run_inference".to_string(),
+            description: "Runs AI inference by calling the backend AION-R API.".to_string(),
+            inputs: json!({
+                "type": "object",
+                "properties": {
+                    "model": {
+                        "type": "string",
+                        "description": "Model to run. If omitted, the user is asked to choose one when the client supports elicitation."
+                    },
+                    "prompt": { "type": "string" },
+                    "params": { "type": "object" }
+                },
+                "required": ["prompt"]
+            }),
+        }
+    }
+
+    async fn call(&self, ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+        run_inference(ctx, inputs).await
+    }
+}
 
 /// Tool: run_inference
 /// Runs AI inference by calling the backend AION-R API.
-pub async fn run_inference(api_client: &ApiClient, inputs: &Value) -> Result<Value> {
-    let model = inputs["model"]
-        .as_str()
-        .ok_or_else(|| ServerError::InvalidParameters {
-            method: "run_inference".to_string(),
-            details: "Missing or invalid 'model' field".to_string(),
-        })?;
-
+///
+/// A missing `model`, or a call estimated to exceed the configured token threshold,
+/// is resolved by asking the user through elicitation when the client supports it.
+pub async fn run_inference(ctx: &ToolContext, inputs: &Value) -> Result<Value> {
     let prompt = inputs["prompt"]
         .as_str()
         .ok_or_else(|| ServerError::InvalidParameters {
@@ -22,13 +52,108 @@ pub async fn run_inference(api_client: &ApiClient, inputs: &Value) -> Result<Val
             details: "Missing or invalid 'prompt' field".to_string(),
         })?;
 
+    let model = match inputs["model"].as_str() {
+        Some(model) => model.to_string(),
+        None => elicit_model(ctx).await?,
+    };
+
     let params = inputs.get("params");
 
+    if let Some(threshold) = ctx.config.confirm_token_threshold {
+        let estimate = estimate_tokens(prompt, params);
+        if estimate > threshold {
+            confirm_cost(ctx, &model, estimate, threshold).await?;
+        }
+    }
+
     tracing::info!(model = model, "Executing run_inference tool");
 
-    let result = api_client
-        .run_inference(model, prompt, &params.cloned())
+    let result = ctx
+        .inference
+        .run_inference(&model, prompt, &params.cloned())
         .await?;
 
     Ok(result)
 }
+
+/// Asks the user which model to run.
+async fn elicit_model(ctx: &ToolContext) -> Result<String> {
+    if !ctx.elicitation.is_available() {
+        return Err(
+            ServerError::invalid("run_inference", "Missing or invalid 'model' field").into(),
+        );
+    }
+
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "model": {
+                "type": "string",
+                "title": "Model",
+                "description": "Name of the model to run the inference with"
+            }
+        },
+        "required": ["model"]
+    });
+    let content = ctx
+        .elicitation
+        .request_fields("Which model should run this inference?", schema)
+        .await?
+        .ok_or_else(|| {
+            ServerError::ToolError("Inference cancelled: no model was chosen".to_string())
+        })?;
+
+    match content.get("model").and_then(Value::as_str) {
+        Some(model) if !model.trim().is_empty() => Ok(model.trim().to_string()),
+        _ => Err(ServerError::invalid("run_inference", "No model was provided").into()),
+    }
+}
+
+/// Asks the user to explicitly approve an expensive call.
+async fn confirm_cost(ctx: &ToolContext, model: &str, estimate: u64, threshold: u64) -> Result<()> {
+    if !ctx.elicitation.is_available() {
+        return Err(ServerError::invalid(
+            "run_inference",
+            format!(
+                "Estimated {} tokens exceeds the confirmation threshold of {}, and the client cannot ask the user to confirm",
+                estimate, threshold
+            ),
+        )
+        .into());
+    }
+
+    let message = format!(
+        "This inference on '{}' is estimated to use about {} tokens (threshold {}). Run it anyway?",
+        model, estimate, threshold
+    );
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "confirm": {
+                "type": "boolean",
+                "title": "Run the inference",
+                "default": false
+            }
+        },
+        "required": ["confirm"]
+    });
+    let content = ctx.elicitation.request_fields(&message, schema).await?;
+
+    match content {
+        Some(content) if content.get("confirm").and_then(Value::as_bool) == Some(true) => Ok(()),
+        _ => Err(ServerError::ToolError(
+            "Inference cancelled: the user did not confirm the estimated cost".to_string(),
+        )
+        .into()),
+    }
+}
+
+/// Estimates total tokens as the prompt size plus the requested completion length.
+fn estimate_tokens(prompt: &str, params: Option<&Value>) -> u64 {
+    let prompt_tokens = (prompt.len() as u64).div_ceil(CHARS_PER_TOKEN);
+    let completion_tokens = params
+        .and_then(|p| p.get("max_tokens").or_else(|| p.get("max_new_tokens")))
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    prompt_tokens + completion_tokens
+}
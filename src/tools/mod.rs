@@ -1,2 +1,8 @@
 pub mod analytics;
+pub mod chart;
+pub mod context;
+pub(crate) mod dataset;
 pub mod inference;
+pub mod profile;
+pub mod query;
+pub mod registry;
@@ -0,0 +1,80 @@
+// src/tools/profile.rs
+
+use crate::analysis::columnar::ScanPlan;
+use crate::analysis::profile::{profile, summary};
+use crate::errors::ServerError;
+use crate::mcp::types::ToolDefinition;
+use crate::tools::context::ToolContext;
+use crate::tools::dataset::{self, load_dataset};
+use crate::tools::registry::Tool;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Example values per column when the caller does not set `examples`.
+const DEFAULT_EXAMPLES: usize = 3;
+
+/// Most example values per column a caller may ask for.
+const MAX_EXAMPLES: u64 = 20;
+
+pub struct ProfileDataTool;
+
+#[async_trait]
+impl Tool for ProfileDataTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "profile_data".to_string(),
+            description: "Describes a dataset's columns: inferred type, null rate, distinct values, range, and examples. Call it first to learn the column names and types that data_analysis ops, query_data, and render_chart expect.".to_string(),
+            inputs: dataset::input_schema(json!({
+                "examples": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": MAX_EXAMPLES,
+                    "description": "Distinct example values shown per column (default 3)."
+                }
+            })),
+        }
+    }
+
+    async fn call(&self, ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+        profile_data(ctx, inputs).await
+    }
+}
+
+/// Tool: profile_data
+/// Loads a dataset in any supported format and profiles each of its columns.
+///
+/// Columns are typed as `integer`, `number`, `timestamp` (date-time text),
+/// `text`, `boolean`, `nested`, `mixed`, or `empty`. The result carries the
+/// profile both as JSON and as a Markdown summary in its text content.
+pub async fn profile_data(ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+    let examples = match inputs.get("examples") {
+        Some(n) => n.as_u64().filter(|&n| n <= MAX_EXAMPLES).ok_or_else(|| {
+            ServerError::invalid(
+                "profile_data",
+                format!("'examples' must be an integer from 0 to {}", MAX_EXAMPLES),
+            )
+        })? as usize,
+        None => DEFAULT_EXAMPLES,
+    };
+
+    let table = async {
+        load_dataset(ctx, inputs, &ScanPlan::default())
+            .await?
+            .into_table()
+    }
+    .await
+    .map_err(|e| ServerError::with_method(e, "profile_data"))?;
+
+    tracing::info!(
+        rows = table.rows.len(),
+        columns = table.columns.len(),
+        "Executing profile_data tool"
+    );
+
+    let mut result = tokio::task::spawn_blocking(move || profile(&table, examples))
+        .await
+        .map_err(|e| ServerError::ToolError(format!("The profiling task failed: {}", e)))?;
+    result["content"] = json!([{ "type": "text", "text": summary(&result) }]);
+    Ok(result)
+}
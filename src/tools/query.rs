@@ -0,0 +1,284 @@
+// src/tools/query.rs
+
+use crate::analysis::columnar::ScanPlan;
+use crate::analysis::table::Table;
+use crate::errors::ServerError;
+use crate::mcp::types::ToolDefinition;
+use crate::tools::context::ToolContext;
+use crate::tools::dataset::{self, load_dataset};
+use crate::tools::registry::Tool;
+use anyhow::Result;
+use async_trait::async_trait;
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// Rows returned when the caller does not set `max_rows`.
+const DEFAULT_MAX_ROWS: usize = 1000;
+
+/// Name of the table holding a dataset passed at the top level rather than in `datasets`.
+const DEFAULT_TABLE: &str = "data";
+
+pub struct QueryDataTool;
+
+#[async_trait]
+impl Tool for QueryDataTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "query_data".to_string(),
+            description: "Runs a read-only SQL query over tabular datasets loaded into an embedded SQLite database.".to_string(),
+            inputs: {
+                let mut inputs = dataset::input_schema(json!({
+                "sql": {
+                    "type": "string",
+                    "description": "A single read-only SQLite statement, such as a SELECT."
+                },
+                "datasets": {
+                    "type": "object",
+                    "description": "Datasets by table name. Each is given like the top-level dataset, with 'data', 'data_file', or 'data_uri' and optional 'format', 'csv', and 'excel'. A top-level dataset is loaded as the table 'data'.",
+                    "additionalProperties": {
+                        "type": "object",
+                        "properties": dataset::source_properties()
+                    }
+                },
+                "max_rows": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Most rows to return (default 1000); 'truncated' tells whether there were more."
+                }
+                }));
+                inputs["required"] = json!(["sql"]);
+                inputs
+            },
+        }
+    }
+
+    async fn call(&self, ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+        query_data(ctx, inputs).await
+    }
+}
+
+/// Tool: query_data
+/// Loads each dataset into a table of an in-memory SQLite database and runs a
+/// read-only query over them.
+///
+/// The result carries the rows as objects, and as a Markdown table in text content.
+pub async fn query_data(ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+    let sql = inputs["sql"]
+        .as_str()
+        .ok_or_else(|| ServerError::invalid("query_data", "Missing 'sql' field"))?
+        .to_string();
+    let max_rows = match inputs.get("max_rows") {
+        Some(n) => n.as_u64().filter(|&n| n > 0).ok_or_else(|| {
+            ServerError::invalid("query_data", "'max_rows' must be a positive integer")
+        })? as usize,
+        None => DEFAULT_MAX_ROWS,
+    };
+
+    let mut sources: Vec<(&str, &Value)> = Vec::new();
+    if ["data", "data_file", "data_uri"]
+        .iter()
+        .any(|key| inputs.get(key).is_some())
+    {
+        sources.push((DEFAULT_TABLE, inputs));
+    }
+    match inputs.get("datasets") {
+        Some(Value::Object(datasets)) => {
+            sources.extend(datasets.iter().map(|(name, spec)| (name.as_str(), spec)))
+        }
+        Some(_) => {
+            return Err(ServerError::invalid("query_data", "'datasets' must be an object").into())
+        }
+        None => {}
+    }
+    if sources.is_empty() {
+        return Err(ServerError::invalid(
+            "query_data",
+            "Pass a dataset with 'data', 'data_file', or 'data_uri', or several in 'datasets'",
+        )
+        .into());
+    }
+
+    let mut tables = Vec::with_capacity(sources.len());
+    for (name, spec) in sources {
+        if name.is_empty() || tables.iter().any(|(n, _)| n == name) {
+            return Err(ServerError::invalid(
+                "query_data",
+                format!("Invalid or repeated table name '{}'", name),
+            )
+            .into());
+        }
+        let table = async {
+            load_dataset(ctx, spec, &ScanPlan::default())
+                .await?
+                .into_table()
+        }
+        .await
+        .map_err(|e| {
+            ServerError::with_method_context(e, "query_data", &format!("table '{}'", name))
+        })?;
+        tables.push((name.to_string(), table));
+    }
+
+    tracing::info!(tables = tables.len(), "Executing query_data tool");
+
+    let time_limit = Duration::from_secs(ctx.config.query_timeout);
+    let (table, truncated) =
+        tokio::task::spawn_blocking(move || run_query(&tables, &sql, max_rows, time_limit))
+            .await
+            .map_err(|e| ServerError::ToolError(format!("The query task failed: {}", e)))??;
+
+    Ok(json!({
+        "columns": table.columns,
+        "rows": table.to_json(),
+        "row_count": table.rows.len(),
+        "truncated": truncated,
+        "content": [{ "type": "text", "text": table.to_markdown() }],
+    }))
+}
+
+fn sql_error(error: rusqlite::Error) -> anyhow::Error {
+    ServerError::invalid("query_data", format!("SQL error: {}", error)).into()
+}
+
+/// Loads `tables` into a fresh in-memory database and runs `sql`, returning at most
+/// `max_rows` rows and whether more were available. The query is interrupted once
+/// it has run for `time_limit`.
+fn run_query(
+    tables: &[(String, Table)],
+    sql: &str,
+    max_rows: usize,
+    time_limit: Duration,
+) -> Result<(Table, bool)> {
+    let mut conn = Connection::open_in_memory().map_err(sql_error)?;
+
+    let tx = conn.transaction().map_err(sql_error)?;
+    for (name, table) in tables {
+        if table.columns.is_empty() {
+            return Err(ServerError::invalid(
+                "query_data",
+                format!("table '{}' has no columns", name),
+            )
+            .into());
+        }
+        // Columns have no declared type, so values keep the type they were loaded with.
+        let columns: Vec<String> = table.columns.iter().map(|c| quote(c)).collect();
+        tx.execute(
+            &format!("CREATE TABLE {} ({})", quote(name), columns.join(", ")),
+            [],
+        )
+        .map_err(sql_error)?;
+        let mut insert = tx
+            .prepare(&format!(
+                "INSERT INTO {} VALUES ({})",
+                quote(name),
+                vec!["?"; columns.len()].join(", ")
+            ))
+            .map_err(sql_error)?;
+        for row in &table.rows {
+            insert
+                .execute(params_from_iter(row.iter().map(to_sql)))
+                .map_err(sql_error)?;
+        }
+    }
+    tx.commit().map_err(sql_error)?;
+
+    conn.pragma_update(None, "query_only", true)
+        .map_err(sql_error)?;
+    // Queries may read the loaded tables, but not open files or change settings.
+    conn.authorizer(Some(|context: AuthContext<'_>| match context.action {
+        AuthAction::Attach { .. } | AuthAction::Detach { .. } | AuthAction::Pragma { .. } => {
+            Authorization::Deny
+        }
+        _ => Authorization::Allow,
+    }))
+    .map_err(sql_error)?;
+    let deadline = Instant::now() + time_limit;
+    conn.progress_handler(10_000, Some(move || Instant::now() > deadline))
+        .map_err(sql_error)?;
+
+    let mut statement = conn.prepare(sql).map_err(sql_error)?;
+    if !statement.readonly() {
+        return Err(
+            ServerError::invalid("query_data", "Only read-only statements are allowed").into(),
+        );
+    }
+    let columns = result_columns(statement.column_names());
+    let step_error = |error: rusqlite::Error| {
+        if error.sqlite_error_code() == Some(rusqlite::ErrorCode::OperationInterrupted) {
+            return ServerError::ToolError(format!(
+                "The query ran longer than {} seconds",
+                time_limit.as_secs()
+            ))
+            .into();
+        }
+        sql_error(error)
+    };
+    let mut rows = statement.query([]).map_err(step_error)?;
+    let mut result = Vec::new();
+    let mut truncated = false;
+    while let Some(row) = rows.next().map_err(step_error)? {
+        if result.len() == max_rows {
+            truncated = true;
+            break;
+        }
+        result.push(
+            (0..columns.len())
+                .map(|i| row.get_ref(i).map(from_sql))
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sql_error)?,
+        );
+    }
+
+    Ok((
+        Table {
+            columns,
+            rows: result,
+        },
+        truncated,
+    ))
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Result column names, made unique so each row can be returned as an object.
+fn result_columns(names: Vec<&str>) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::with_capacity(names.len());
+    for (i, name) in names.into_iter().enumerate() {
+        if columns.iter().any(|c| c == name) {
+            columns.push(format!("{}_{}", name, i + 1));
+        } else {
+            columns.push(name.to_string());
+        }
+    }
+    columns
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        // Nested values are stored as JSON text, which SQLite's JSON functions can read.
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn from_sql(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+        ValueRef::Text(text) | ValueRef::Blob(text) => {
+            Value::String(String::from_utf8_lossy(text).into_owned())
+        }
+    }
+}
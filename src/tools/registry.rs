@@ -0,0 +1,124 @@
+// src/tools/registry.rs
+
+use crate::mcp::types::ToolDefinition;
+use crate::tools::analytics::DataAnalysisTool;
+use crate::tools::chart::RenderChartTool;
+use crate::tools::context::ToolContext;
+use crate::tools::inference::RunInferenceTool;
+use crate::tools::profile::ProfileDataTool;
+use crate::tools::query::QueryDataTool;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A tool that can be announced in `tools/list` and invoked through `tools/call`.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// The name, description and input schema announced to clients.
+    fn definition(&self) -> ToolDefinition;
+
+    /// Runs the tool with the caller-supplied inputs.
+    async fn call(&self, ctx: &ToolContext, inputs: &Value) -> Result<Value>;
+}
+
+struct RegisteredTool {
+    tool: Arc<dyn Tool>,
+    enabled: bool,
+}
+
+/// The set of tools a server offers, which may change while it is running.
+///
+/// Every change that affects what clients see bumps `version`, so callers can tell
+/// whether a `notifications/tools/list_changed` is warranted.
+pub struct ToolRegistry {
+    tools: BTreeMap<String, RegisteredTool>,
+    version: u64,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self {
+            tools: BTreeMap::new(),
+            version: 0,
+        }
+    }
+
+    /// A registry holding the tools built into this server, all enabled.
+    pub fn with_builtin_tools() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(RunInferenceTool));
+        registry.register(Arc::new(DataAnalysisTool));
+        registry.register(Arc::new(QueryDataTool));
+        registry.register(Arc::new(RenderChartTool));
+        registry.register(Arc::new(ProfileDataTool));
+        registry
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Adds an enabled tool, replacing any tool with the same name.
+    pub fn register(&mut self, tool: Arc<dyn Tool>) {
+        let name = tool.definition().name;
+        self.tools.insert(
+            name,
+            RegisteredTool {
+                tool,
+                enabled: true,
+            },
+        );
+        self.version += 1;
+    }
+
+    /// Removes a tool. Returns whether it was registered.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let removed = self.tools.remove(name).is_some();
+        if removed {
+            self.version += 1;
+        }
+        removed
+    }
+
+    /// Enables or disables a tool. Returns whether anything changed.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.tools.get_mut(name) {
+            Some(entry) if entry.enabled != enabled => {
+                entry.enabled = enabled;
+                self.version += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Names of all registered tools, enabled or not.
+    pub fn names(&self) -> Vec<String> {
+        self.tools.keys().cloned().collect()
+    }
+
+    /// Definitions of the enabled tools, in name order.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .values()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.tool.definition())
+            .collect()
+    }
+
+    /// The tool with this name, if it is registered and enabled.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools
+            .get(name)
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.tool.clone())
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,11 +1,16 @@
 // src/util.rs
 
+use crate::errors::ServerError;
 use anyhow::Result;
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Stdin, Stdout};
+use futures::{Stream, StreamExt};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
 
-/// Reads a complete JSON-RPC message from stdio.
-/// A message is defined as a block of text terminated by a blank line.
-pub async fn read_message(stdin: &mut BufReader<Stdin>) -> Result<Option<String>> {
+/// Reads a complete JSON-RPC message from the transport.
+/// A message is a block of headers terminated by a blank line, followed by a body
+/// of `Content-Length` bytes.
+pub async fn read_message<R: AsyncBufRead + Unpin>(stdin: &mut R) -> Result<Option<String>> {
     let mut buffer = String::new();
     let mut content_length = 0;
 
@@ -39,10 +44,50 @@ pub async fn read_message(stdin: &mut BufReader<Stdin>) -> Result<Option<String>
     Ok(None)
 }
 
-/// Writes a complete JSON-RPC message to stdout.
-pub async fn write_message(stdout: &mut Stdout, message: &str) -> Result<()> {
+/// Writes a complete JSON-RPC message to the transport.
+pub async fn write_message<W: AsyncWrite + Unpin>(stdout: &mut W, message: &str) -> Result<()> {
     let response = format!("Content-Length: {}\r\n\r\n{}", message.len(), message);
     stdout.write_all(response.as_bytes()).await?;
     stdout.flush().await?;
     Ok(())
 }
+
+/// Reads `reader` to the end, failing once more than `max_bytes` have been read.
+/// `what` names the source in the error message.
+pub async fn read_limited<R: AsyncRead + Unpin>(
+    reader: R,
+    max_bytes: u64,
+    what: &str,
+) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(max_bytes + 1).read_to_end(&mut bytes).await?;
+    if bytes.len() as u64 > max_bytes {
+        return Err(size_limit_error(what, max_bytes));
+    }
+    Ok(bytes)
+}
+
+/// Collects a stream of byte chunks, failing once more than `max_bytes` have arrived.
+pub async fn collect_limited<S, B, E>(mut stream: S, max_bytes: u64, what: &str) -> Result<Vec<u8>>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<anyhow::Error>,
+{
+    let mut bytes = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        bytes.extend_from_slice(chunk.map_err(Into::into)?.as_ref());
+        if bytes.len() as u64 > max_bytes {
+            return Err(size_limit_error(what, max_bytes));
+        }
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn size_limit_error(what: &str, max_bytes: u64) -> anyhow::Error {
+    ServerError::ToolError(format!(
+        "{} is larger than the {}-byte limit",
+        what, max_bytes
+    ))
+    .into()
+}
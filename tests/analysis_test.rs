@@ -0,0 +1,1641 @@
+// tests/analysis_test.rs
+
+mod common;
+
+use anyhow::Result;
+use common::TestClient;
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn sales() -> serde_json::Value {
+    json!([
+        { "region": "north", "product": "a", "units": 10, "price": 2.5 },
+        { "region": "south", "product": "b", "units": 4, "price": 10.0 },
+        { "region": "north", "product": "b", "units": 6, "price": 10.0 },
+        { "region": "east", "product": "a", "units": null, "price": 2.5 },
+        { "region": "south", "product": "a", "units": 8, "price": 2.5 }
+    ])
+}
+
+#[tokio::test]
+async fn test_local_engine_pipeline() -> Result<()> {
+    // Nothing listens on the backend URL: every op must run locally
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": sales(),
+                "ops": [
+                    "count",
+                    { "op": "sum", "column": "units" },
+                    { "op": "median", "column": "units" },
+                    { "op": "stddev", "column": "price" },
+                    { "op": "filter", "column": "units", "cmp": "gte", "value": 5 },
+                    { "op": "group_by", "by": ["region"], "aggregations": [
+                        { "fn": "sum", "column": "units", "as": "units" },
+                        { "fn": "count" }
+                    ] },
+                    { "op": "sort", "by": ["units"], "descending": true },
+                    { "op": "limit", "n": 1 }
+                ]
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["engine"], "local", "{}", resp);
+    assert_eq!(result["status"], "completed");
+    assert_eq!(
+        result["results"][0],
+        json!({ "metric": "count", "value": 5 })
+    );
+    assert_eq!(result["results"][1]["value"], 28.0);
+    assert_eq!(result["results"][2]["value"], 7.0);
+    let stddev = result["results"][3]["value"].as_f64().unwrap();
+    assert!((stddev - 4.1079).abs() < 1e-3, "{}", stddev);
+    assert_eq!(
+        result["rows"],
+        json!([{ "region": "north", "units": 16.0, "count": 2 }])
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": { "x": [1, 2, 3], "label": ["a", "b", "a"] },
+                "ops": ["describe", "mean"]
+            }),
+        )
+        .await?;
+    let results = &resp["result"]["results"];
+    assert_eq!(results[0]["value"]["x"]["max"], 3.0);
+    assert_eq!(
+        results[0]["value"]["label"],
+        json!({ "count": 3, "unique": 2 })
+    );
+    assert_eq!(
+        results[1],
+        json!({ "metric": "mean", "column": "x", "value": 2.0 })
+    );
+
+    let resp = client
+        .call_tool("data_analysis", json!({ "data": sales(), "ops": ["mean"] }))
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_local_engine_edge_cases() -> Result<()> {
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+
+    let rows = |ops: Value| json!({ "data": sales(), "ops": ops });
+    for (ops, expected) in [
+        (
+            json!([
+                { "op": "filter", "column": "region", "cmp": "ne", "value": "north" },
+                { "op": "select", "columns": ["region"] }
+            ]),
+            json!([{ "region": "south" }, { "region": "east" }, { "region": "south" }]),
+        ),
+        (
+            json!([
+                { "op": "filter", "column": "region", "cmp": "contains", "value": "th" },
+                { "op": "filter", "column": "units", "cmp": "in", "value": [4, 6.0] },
+                { "op": "select", "columns": ["units"] }
+            ]),
+            json!([{ "units": 4 }, { "units": 6 }]),
+        ),
+        (
+            json!([
+                { "op": "sort", "by": ["units"], "descending": true },
+                { "op": "select", "columns": ["units"] }
+            ]),
+            json!([
+                { "units": 10 }, { "units": 8 }, { "units": 6 }, { "units": 4 }, { "units": null }
+            ]),
+        ),
+        (
+            json!([
+                { "op": "sort", "by": ["units"] },
+                { "op": "limit", "n": 2 },
+                { "op": "select", "columns": ["units"] }
+            ]),
+            json!([{ "units": 4 }, { "units": 6 }]),
+        ),
+        (
+            json!([
+                { "op": "group_by", "by": ["units"], "aggregations": [{ "fn": "count" }] },
+                { "op": "filter", "column": "units", "cmp": "eq", "value": null }
+            ]),
+            json!([{ "units": null, "count": 1 }]),
+        ),
+    ] {
+        let resp = client.call_tool("data_analysis", rows(ops.clone())).await?;
+        assert_eq!(resp["result"]["rows"], expected, "{}: {}", ops, resp);
+    }
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": { "x": [], "label": [] },
+                "ops": ["count", "describe", { "op": "aggregate", "aggregations": [
+                    { "fn": "count" }, { "fn": "sum", "column": "x" }
+                ] }]
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["results"][0]["value"], 0, "{}", resp);
+    assert_eq!(
+        result["results"][1]["value"]["label"],
+        json!({ "count": 0, "unique": 0 })
+    );
+    assert_eq!(result["rows"], json!([{ "count": 0, "sum_x": null }]));
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": sales(),
+                "ops": [{ "op": "sort", "by": ["missing"] }]
+            }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_analysis_mode_routing() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .and(body_partial_json(json!({ "data": [1, 2, 3] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "completed",
+            "results": [{ "metric": "forecast", "value": [4] }]
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+    let mut client = TestClient::start(&["--aion-r-api-url", &mock_server.uri()]).await?;
+
+    // An op the local engine lacks falls through to the backend
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2, 3], "ops": ["mean", "forecast"] }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["metric"], "forecast");
+
+    // Remote mode forwards even supported ops
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2, 3], "ops": ["mean"], "mode": "remote" }),
+        )
+        .await?;
+    assert!(resp["result"].get("engine").is_none());
+
+    // Local mode refuses ops it cannot run
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2, 3], "ops": ["mean", "forecast"], "mode": "local" }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("ops[1] (forecast) is not supported"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ops_are_validated_before_execution() -> Result<()> {
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+
+    let resp = client.request("tools/list", json!({})).await?;
+    let tool = resp["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "data_analysis")
+        .unwrap()
+        .clone();
+    let variants = tool["inputs"]["properties"]["ops"]["items"]["anyOf"][0]["oneOf"]
+        .as_array()
+        .unwrap();
+    assert!(variants
+        .iter()
+        .any(|v| v["properties"]["op"]["const"] == "group_by"));
+
+    let cases = [
+        (
+            json!([{ "op": "mean", "column": "region" }]),
+            "ops[0] (mean): column 'region' holds text, not numbers",
+        ),
+        (
+            json!(["count", { "op": "sort", "by": ["colour"] }]),
+            "ops[1] (sort): unknown column 'colour'",
+        ),
+        (
+            json!([{ "op": "filter", "column": "units", "cmp": "gt", "value": "5" }]),
+            "ops[0] (filter): cannot compare column 'units', which holds numbers, with text",
+        ),
+        (
+            json!([
+                { "op": "group_by", "by": ["region"], "aggregations": [{ "fn": "sum", "column": "units" }] },
+                { "op": "max", "column": "price" }
+            ]),
+            "ops[1] (max): unknown column 'price'; available columns: region, sum_units",
+        ),
+        (
+            json!([{ "op": "aggregate", "aggregations": [{ "fn": "mean" }] }]),
+            "ops[0] (aggregate): aggregations[0]: 'mean' needs a 'column'",
+        ),
+        (
+            json!([{ "op": "sort", "by": [] }]),
+            "ops[0] (sort): 'by' needs at least one column",
+        ),
+        (
+            json!([{ "op": "select", "columns": ["units", "units"] }]),
+            "ops[0] (select): column 'units' is selected twice",
+        ),
+        (
+            json!([{ "op": "filter", "column": "units", "cmp": "in", "value": 4 }]),
+            "ops[0] (filter): 'in' needs an array of values to compare with",
+        ),
+        (
+            json!([{ "op": "filter", "column": "units", "cmp": "contains", "value": 4 }]),
+            "ops[0] (filter): 'contains' needs a text or array column, but 'units' holds numbers",
+        ),
+        (
+            json!([{ "op": "group_by", "by": ["region"], "aggregations": [
+                { "fn": "sum", "column": "units" }, { "fn": "sum", "column": "units" }
+            ] }]),
+            "ops[0] (group_by): aggregations[1]: output column 'sum_units' already exists",
+        ),
+        (
+            json!([{ "op": "median" }]),
+            "ops[0] (median): 'column' is required when the data has several numeric columns",
+        ),
+    ];
+    for (ops, message) in cases {
+        for mode in ["auto", "local"] {
+            let resp = client
+                .call_tool(
+                    "data_analysis",
+                    json!({ "data": sales(), "ops": ops, "mode": mode }),
+                )
+                .await?;
+            assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+            let actual = resp["error"]["message"].as_str().unwrap();
+            assert!(actual.contains(message), "{}", actual);
+        }
+    }
+
+    // Columns produced by earlier steps can be referenced, and omitted columns are resolved
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": sales(),
+                "ops": [
+                    { "op": "group_by", "by": ["product"], "aggregations": [{ "fn": "sum", "column": "units" }] },
+                    "max"
+                ]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["results"][0],
+        json!({ "metric": "max", "column": "sum_units", "value": 18.0 })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_csv_tsv_and_ndjson_inputs() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .and(body_partial_json(json!({
+            "data": [
+                { "city": "Lyon, FR", "temp": 21.5, "sunny": true },
+                { "city": "Oslo", "temp": null, "sunny": false }
+            ]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "completed" })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let mut client = TestClient::start(&["--aion-r-api-url", &mock_server.uri()]).await?;
+
+    // The header is detected, quoted fields keep their delimiter, and types are inferred
+    let csv = "city,temp,sunny\n\"Lyon, FR\",21.5,true\nOslo,,false\n";
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": csv, "ops": ["count", { "op": "max", "column": "temp" }] }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["engine"], "local", "{}", resp);
+    assert_eq!(resp["result"]["results"][0]["value"], 2);
+    assert_eq!(resp["result"]["results"][1]["value"], 21.5);
+
+    // The backend receives the same rows as JSON objects
+    let resp = client
+        .call_tool("data_analysis", json!({ "data": csv, "ops": ["forecast"] }))
+        .await?;
+    assert_eq!(resp["result"]["status"], "completed", "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": "1;x\n2;y\n3;x\n",
+                "format": "csv",
+                "csv": { "delimiter": ";", "header": false },
+                "ops": [{ "op": "sum", "column": "column_1" }, { "op": "describe", "columns": ["column_2"] }]
+            }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 6.0, "{}", resp);
+    assert_eq!(
+        resp["result"]["results"][1]["value"]["column_2"]["unique"],
+        2
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": "id\tscore\n1\t0.5\n2\t1.5\n", "ops": ["mean"], "csv": { "infer_types": true } }),
+        )
+        .await?;
+    assert_eq!(
+        resp["error"]["code"], -32602,
+        "two numeric columns: {}",
+        resp
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": "{\"n\": 1}\n{\"n\": 4}\n\n{\"n\": 7}\n",
+                "ops": ["median"]
+            }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 4.0, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": "a,b\n1,2,3\n", "format": "csv", "ops": ["count"] }),
+        )
+        .await?;
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Row 2 has 3 fields, expected 2"));
+
+    // Without type inference, fields stay text: leading zeros survive
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": "code,note\n007,\"say \"\"hi\"\"\"\n010,\n",
+                "format": "csv",
+                "csv": { "infer_types": false },
+                "ops": [{ "op": "filter", "column": "code", "cmp": "eq", "value": "007" }]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([{ "code": "007", "note": "say \"hi\"" }]),
+        "{}",
+        resp
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": "a,b\n", "format": "csv", "ops": ["count", "describe"] }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 0, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": "{\"n\": 1}\n{\"n\": \n", "format": "ndjson", "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    assert!(
+        resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("line 2"),
+        "{}",
+        resp
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_uri_resources_and_files() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/datasets/sales"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("region,units\nnorth,3\nsouth,5\n"),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "id": "small", "parameters": 7 },
+            { "id": "large", "parameters": 70 }
+        ])))
+        .mount(&mock_server)
+        .await;
+    let mut client = TestClient::start(&[
+        "--aion-r-api-url",
+        &mock_server.uri(),
+        "--max-data-bytes",
+        "64",
+    ])
+    .await?;
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_uri": "aion-r://datasets/sales", "ops": ["sum"] }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 8.0, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_uri": "aion-r://models/catalog", "ops": ["max"] }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 70.0, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_uri": "aion-r://reports/q1", "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+
+    // Files are read through the client's roots and the size limit applies
+    let dir = std::env::temp_dir().join(format!("aionr2-uri-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let dir = dir.canonicalize()?;
+    std::fs::write(dir.join("small.tsv"), "a\tb\n1\t2\n")?;
+    std::fs::write(dir.join("big.csv"), "n\n".repeat(100))?;
+
+    client
+        .request(
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05", "capabilities": { "roots": {} } }),
+        )
+        .await?;
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {
+                "name": "data_analysis",
+                "inputs": {
+                    "data_uri": format!("file://{}", dir.join("small.tsv").display()),
+                    "ops": [{ "op": "sum", "column": "b" }]
+                }
+            },
+            "id": 100
+        }))
+        .await?;
+    let roots_req = client.recv().await?;
+    assert_eq!(roots_req["method"], "roots/list");
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "result": { "roots": [{ "uri": format!("file://{}", dir.display()) }] },
+            "id": roots_req["id"]
+        }))
+        .await?;
+    let resp = client.recv().await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 2.0, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_uri": format!("file://{}", dir.join("big.csv").display()), "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32000);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("larger than the 64-byte limit"));
+
+    // Columnar files are checked before they are decoded
+    let mut parquet = b"PAR1".to_vec();
+    parquet.extend([0; 96]);
+    parquet.extend(b"PAR1");
+    std::fs::write(dir.join("big.parquet"), parquet)?;
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_uri": dir.join("big.parquet").display().to_string(), "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32000, "{}", resp);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("larger than the 64-byte limit"));
+
+    for (location, message) in [
+        ("small.tsv".to_string(), "is not an absolute path"),
+        (format!("{}/../small.tsv", dir.display()), "Cannot access"),
+        (
+            format!("{}/..", dir.display()),
+            "outside the client's roots",
+        ),
+    ] {
+        let resp = client
+            .call_tool(
+                "data_analysis",
+                json!({ "data_uri": location, "ops": ["count"] }),
+            )
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        assert!(
+            resp["error"]["message"].as_str().unwrap().contains(message),
+            "{}",
+            resp
+        );
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parquet_and_arrow_files() -> Result<()> {
+    use arrow::array::{Float64Array, Int64Array, RecordBatch, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("region", DataType::Utf8, false),
+        Field::new("price", DataType::Float64, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from_iter_values(0..100)),
+            Arc::new(StringArray::from_iter_values((0..100).map(|i| {
+                if i % 2 == 0 {
+                    "north"
+                } else {
+                    "south"
+                }
+            }))),
+            Arc::new(Float64Array::from_iter_values((0..100).map(|i| i as f64))),
+        ],
+    )?;
+
+    let dir = std::env::temp_dir().join(format!("aionr2-columnar-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let dir = dir.canonicalize()?;
+
+    // Ten row groups of ten rows each, sorted by id
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(10)
+        .build();
+    let mut writer = ArrowWriter::try_new(
+        std::fs::File::create(dir.join("sales.parquet"))?,
+        schema.clone(),
+        Some(props),
+    )?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    let mut writer = arrow::ipc::writer::FileWriter::try_new(
+        std::fs::File::create(dir.join("sales.arrow"))?,
+        &schema,
+    )?;
+    writer.write(&batch)?;
+    writer.finish()?;
+
+    let mut client = TestClient::start(&[]).await?;
+    client
+        .request(
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05", "capabilities": { "roots": {} } }),
+        )
+        .await?;
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {
+                "name": "data_analysis",
+                "inputs": {
+                    "data_uri": format!("file://{}", dir.join("sales.parquet").display()),
+                    "ops": [
+                        { "op": "filter", "column": "id", "cmp": "gte", "value": 75 },
+                        { "op": "select", "columns": ["id", "price"] },
+                        { "op": "sum", "column": "price" }
+                    ]
+                }
+            },
+            "id": 100
+        }))
+        .await?;
+    let roots_req = client.recv().await?;
+    assert_eq!(roots_req["method"], "roots/list");
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "result": { "roots": [{ "uri": format!("file://{}", dir.display()) }] },
+            "id": roots_req["id"]
+        }))
+        .await?;
+    let resp = client.recv().await?;
+    let result = &resp["result"];
+    assert_eq!(
+        result["results"][0]["value"],
+        json!((75..100).sum::<i64>() as f64),
+        "{}",
+        resp
+    );
+    // Only the filtered and summed columns are read, and row groups below id 70 are skipped
+    assert_eq!(
+        result["scan"]["columns"],
+        json!(["id", "price"]),
+        "{}",
+        resp
+    );
+    assert_eq!(result["scan"]["row_groups_read"], 3, "{}", resp);
+    assert_eq!(result["scan"]["row_groups_total"], 10, "{}", resp);
+    assert_eq!(result["rows"].as_array().unwrap().len(), 25);
+
+    // Filters and statistics run on the Arrow arrays, with the row engine's output
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": dir.join("sales.parquet"),
+                "ops": [
+                    { "op": "filter", "column": "region", "cmp": "eq", "value": "north" },
+                    { "op": "filter", "column": "price", "cmp": "lt", "value": 10 },
+                    { "op": "mean", "column": "price" }
+                ]
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["results"][0]["value"], 4.0, "{}", resp);
+    assert_eq!(
+        result["rows"],
+        json!([0, 2, 4, 6, 8]
+            .iter()
+            .map(|&i| json!({ "id": i, "region": "north", "price": i as f64 }))
+            .collect::<Vec<_>>())
+    );
+    assert_eq!(result["scan"]["row_groups_read"], 1, "{}", resp);
+
+    // Sorting and limiting before a projection still reads only the columns used
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": dir.join("sales.parquet"),
+                "ops": [
+                    { "op": "filter", "column": "region", "cmp": "eq", "value": "south" },
+                    { "op": "sort", "by": ["price"], "descending": true },
+                    { "op": "limit", "n": 2 },
+                    { "op": "select", "columns": ["price"] }
+                ]
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(
+        result["rows"],
+        json!([{ "price": 99.0 }, { "price": 97.0 }]),
+        "{}",
+        resp
+    );
+    assert_eq!(result["scan"]["columns"], json!(["region", "price"]));
+
+    // Arrow IPC files are detected from their contents; unsupported pipelines use the row engine
+    std::fs::rename(dir.join("sales.arrow"), dir.join("sales.bin"))?;
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": dir.join("sales.bin"),
+                "ops": [
+                    { "op": "group_by", "by": ["region"], "aggregations": [{ "fn": "count" }] }
+                ]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([{ "region": "north", "count": 50 }, { "region": "south", "count": 50 }]),
+        "{}",
+        resp
+    );
+
+    // Columns written by time-series ops do not stop the projection
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": dir.join("sales.bin"),
+                "ops": [
+                    { "op": "lag", "column": "price" },
+                    { "op": "select", "columns": ["id", "lag_price"] },
+                    { "op": "limit", "n": 2 }
+                ]
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(
+        result["rows"],
+        json!([{ "id": 0, "lag_price": null }, { "id": 1, "lag_price": 0.0 }]),
+        "{}",
+        resp
+    );
+    assert_eq!(result["scan"]["columns"], json!(["id", "price"]));
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_file": dir.join("sales.bin"), "ops": [{ "op": "mean", "column": "region" }] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": "PAR1", "format": "parquet", "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_excel_workbooks() -> Result<()> {
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+    let dir = std::env::temp_dir().join(format!("aionr2-excel-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let dir = dir.canonicalize()?;
+    let path = dir.join("report.xlsx");
+
+    let mut workbook = Workbook::new();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    let sheet = workbook.add_worksheet().set_name("Sales")?;
+    sheet.merge_range(0, 0, 0, 2, "Q1 report", &Format::new())?;
+    sheet.write_string(1, 0, "region")?;
+    sheet.write_string(1, 1, "date")?;
+    sheet.write_string(1, 2, "units")?;
+    // A region merged down two rows, and a blank spacer row
+    sheet.merge_range(2, 0, 3, 0, "north", &Format::new())?;
+    sheet.write_datetime_with_format(2, 1, ExcelDateTime::from_ymd(2024, 1, 15)?, &date)?;
+    sheet.write_number(2, 2, 10)?;
+    sheet.write_datetime_with_format(
+        3,
+        1,
+        ExcelDateTime::from_ymd(2024, 1, 16)?.and_hms(9, 30, 0)?,
+        &Format::new().set_num_format("yyyy-mm-dd hh:mm"),
+    )?;
+    sheet.write_number(3, 2, 6)?;
+    sheet.write_string(4, 0, "south")?;
+    sheet.write_datetime_with_format(4, 1, ExcelDateTime::from_ymd(2024, 2, 1)?, &date)?;
+    sheet.write_number(4, 2, 4.5)?;
+    sheet.write_string(6, 0, "south")?;
+    sheet.write_datetime_with_format(6, 1, ExcelDateTime::from_ymd(2024, 2, 3)?, &date)?;
+    sheet.write_number(6, 2, 8)?;
+    workbook
+        .add_worksheet()
+        .set_name("Notes")?
+        .write_string(0, 0, "draft")?;
+    workbook.define_name("SalesData", "=Sales!$A$2:$C$7")?;
+    workbook.save(&path)?;
+
+    let mut client = TestClient::start(&[]).await?;
+    client
+        .request(
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05", "capabilities": { "roots": {} } }),
+        )
+        .await?;
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {
+                "name": "data_analysis",
+                "inputs": {
+                    "data_file": path,
+                    "excel": { "range": "SalesData" },
+                    "ops": [{ "op": "select", "columns": ["region", "date", "units"] }]
+                }
+            },
+            "id": 100
+        }))
+        .await?;
+    let roots_req = client.recv().await?;
+    assert_eq!(roots_req["method"], "roots/list");
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "result": { "roots": [{ "uri": format!("file://{}", dir.display()) }] },
+            "id": roots_req["id"]
+        }))
+        .await?;
+    let resp = client.recv().await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([
+            { "region": "north", "date": "2024-01-15", "units": 10 },
+            { "region": "north", "date": "2024-01-16T09:30:00", "units": 6 },
+            { "region": "south", "date": "2024-02-01", "units": 4.5 },
+            { "region": "south", "date": "2024-02-03", "units": 8 }
+        ]),
+        "{}",
+        resp
+    );
+
+    // A sheet-qualified range without a header row
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": path,
+                "excel": { "range": "Sales!A3:C5", "header": false },
+                "ops": [
+                    { "op": "group_by", "by": ["column_1"], "aggregations": [{ "fn": "sum", "column": "column_3" }] }
+                ]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([
+            { "column_1": "north", "sum_column_3": 16.0 },
+            { "column_1": "south", "sum_column_3": 4.5 }
+        ]),
+        "{}",
+        resp
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_file": path, "excel": { "sheet": "Notes" }, "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["results"][0]["value"], 1, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_file": path, "excel": { "sheet": "Costs" }, "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Unknown sheet 'Costs'; available sheets: Sales, Notes"));
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_file": path, "excel": { "range": "B2:nope" }, "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": path,
+                "excel": { "sheet": "Notes", "range": "Sales!A2:C7" },
+                "ops": ["count"]
+            }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("'range' refers to sheet 'Sales', not 'Notes'"));
+
+    // Repeated and blank headings get positional names; booleans keep their type
+    let types = dir.join("types.xlsx");
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.write_string(0, 0, "id")?;
+    sheet.write_string(0, 1, "id")?;
+    sheet.write_string(0, 3, "ok")?;
+    sheet.write_number(1, 0, 1)?;
+    sheet.write_number(1, 1, 2.5)?;
+    sheet.write_string(1, 2, "x")?;
+    sheet.write_boolean(1, 3, true)?;
+    sheet.write_number(2, 0, 2)?;
+    sheet.write_boolean(2, 3, false)?;
+    workbook.save(&types)?;
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data_file": types,
+                "excel": { "header": true },
+                "ops": [{ "op": "filter", "column": "ok", "cmp": "eq", "value": true }]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([{ "id": 1, "id_2": 2.5, "column_3": "x", "ok": true }]),
+        "{}",
+        resp
+    );
+
+    let broken = dir.join("broken.xlsx");
+    std::fs::write(&broken, b"PK\x03\x04 not really a workbook")?;
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data_file": broken, "ops": ["count"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    assert!(
+        resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid Excel workbook"),
+        "{}",
+        resp
+    );
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+fn assert_close(actual: &serde_json::Value, expected: f64) {
+    let actual = actual.as_f64().unwrap_or(f64::NAN);
+    assert!(
+        (actual - expected).abs() < 1e-3,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[tokio::test]
+async fn test_statistical_tests_and_regression() -> Result<()> {
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+    let data = json!({
+        "x": [1, 2, 3, 4, 5],
+        "y": [2, 4, 5, 4, 5],
+        "group": ["a", "a", "b", "b", "b"]
+    });
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": data,
+                "ops": [
+                    "correlation",
+                    { "op": "correlation", "method": "spearman" },
+                    { "op": "regression", "y": "y", "x": ["x"] },
+                    { "op": "t_test", "column": "y", "mu": 3 },
+                    { "op": "t_test", "column": "y", "paired_with": "x" },
+                    { "op": "t_test", "column": "y", "by": "group" },
+                    { "op": "chi_square", "column": "group" }
+                ]
+            }),
+        )
+        .await?;
+    let results = &resp["result"]["results"];
+    assert_eq!(resp["result"]["engine"], "local", "{}", resp);
+
+    let pearson = &results[0]["value"];
+    assert_eq!(pearson["method"], "Pearson correlation");
+    assert_eq!(pearson["columns"], json!(["x", "y"]));
+    assert_close(&pearson["matrix"]["x"]["x"], 1.0);
+    assert_close(&pearson["matrix"]["x"]["y"], 0.6_f64.sqrt());
+    assert_close(&pearson["p_values"]["y"]["x"], 0.1240);
+    let spearman = &results[1]["value"];
+    assert_eq!(spearman["method"], "Spearman rank correlation");
+    assert_close(&spearman["matrix"]["x"]["y"], 0.7379);
+
+    let regression = &results[2]["value"];
+    assert_eq!(regression["method"], "Ordinary least squares");
+    assert_eq!(regression["coefficients"][0]["term"], "intercept");
+    assert_close(&regression["coefficients"][0]["estimate"], 2.2);
+    assert_eq!(regression["coefficients"][1]["term"], "x");
+    assert_close(&regression["coefficients"][1]["estimate"], 0.6);
+    assert_close(&regression["coefficients"][1]["p_value"], 0.1240);
+    assert_close(&regression["r_squared"], 0.6);
+    assert_close(&regression["f_statistic"], 4.5);
+
+    let one_sample = &results[3];
+    assert_eq!(one_sample["metric"], "t_test");
+    assert_eq!(one_sample["value"]["method"], "One-sample t-test");
+    assert_close(&one_sample["value"]["statistic"], 1.8257);
+    assert_close(&one_sample["value"]["df"], 4.0);
+    assert_close(&one_sample["value"]["p_value"], 0.1422);
+    let paired = &results[4]["value"];
+    assert_eq!(paired["method"], "Paired t-test");
+    assert_close(&paired["estimate"], 1.0);
+    let welch = &results[5]["value"];
+    assert_eq!(welch["method"], "Welch's two-sample t-test");
+    assert_close(&welch["estimate"], -1.6667);
+    assert_eq!(welch["groups"][0]["value"], "a");
+
+    let chi_square = &results[6]["value"];
+    assert_eq!(
+        chi_square["method"],
+        "Chi-square goodness-of-fit test against equal frequencies"
+    );
+    assert_close(&chi_square["statistic"], 0.2);
+    assert_close(&chi_square["p_value"], 0.6547);
+    assert_eq!(chi_square["observed"], json!({ "a": 2, "b": 3 }));
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": sales(),
+                "ops": [{ "op": "chi_square", "column": "region", "by": "product" }]
+            }),
+        )
+        .await?;
+    let independence = &resp["result"]["results"][0]["value"];
+    assert_eq!(
+        independence["method"], "Pearson's chi-square test of independence",
+        "{}",
+        resp
+    );
+    assert_close(&independence["df"], 2.0);
+
+    for (op, message) in [
+        (
+            json!({ "op": "regression", "y": "y", "x": ["y"] }),
+            "column 'y' appears twice in the model",
+        ),
+        (
+            json!({ "op": "t_test", "column": "group" }),
+            "column 'group' holds text, not numbers",
+        ),
+        (
+            json!({ "op": "correlation", "columns": ["x"] }),
+            "needs at least two numeric columns",
+        ),
+    ] {
+        let resp = client
+            .call_tool("data_analysis", json!({ "data": data, "ops": [op] }))
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(text.contains(message), "{}", text);
+    }
+
+    // Degenerate data is reported rather than turned into NaNs
+    let data = json!({
+        "x": [1, 2, 3, 4, 5],
+        "double": [2, 4, 6, 8, 10],
+        "flat": [3, 3, 3, 3, 3],
+        "y": [1, 3, 2, 5, 4],
+        "sparse": [1, null, null, null, null],
+        "same": ["a", "a", "a", "a", "a"],
+        "group": ["a", "a", "a", "a", "b"],
+        "three": ["a", "b", "c", "a", "b"]
+    });
+    for (op, message) in [
+        (
+            json!({ "op": "regression", "y": "y", "x": ["x", "double"] }),
+            "the predictors are collinear",
+        ),
+        (
+            json!({ "op": "regression", "y": "y", "x": ["sparse"] }),
+            "regression on 1 predictors needs more than 2 complete rows, but has 1",
+        ),
+        (
+            json!({ "op": "t_test", "column": "y", "paired_with": "sparse" }),
+            "a t-test needs at least two values",
+        ),
+        (
+            json!({ "op": "t_test", "column": "y", "by": "group" }),
+            "a two-sample t-test needs at least two values in each group",
+        ),
+        (
+            json!({ "op": "t_test", "column": "y", "by": "three" }),
+            "column 'three' must split the rows into two groups, but has 3",
+        ),
+        (
+            json!({ "op": "chi_square", "column": "same" }),
+            "column 'same' needs at least two distinct values",
+        ),
+        (
+            json!({ "op": "chi_square", "column": "group", "by": "same" }),
+            "columns 'group' and 'same' need at least two distinct values each",
+        ),
+    ] {
+        let resp = client
+            .call_tool("data_analysis", json!({ "data": data, "ops": [op] }))
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(text.contains(message), "{}", text);
+    }
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": data,
+                "ops": [{ "op": "correlation", "columns": ["x", "double", "flat"], "method": "spearman" }]
+            }),
+        )
+        .await?;
+    let value = &resp["result"]["results"][0]["value"];
+    assert_eq!(value["matrix"]["x"]["double"], 1.0, "{}", resp);
+    assert_eq!(value["p_values"]["x"]["double"], 0.0);
+    assert_eq!(value["matrix"]["x"]["flat"], Value::Null);
+    assert_eq!(value["p_values"]["flat"]["x"], Value::Null);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_time_series_ops() -> Result<()> {
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+    let data = json!({
+        "at": [1704067200, 1704067440, 1704067920, 1704067980, 1704069060],
+        "local": ["01/01/2024 00:00", "01/01/2024 00:04", "01/01/2024 00:12", null, "01/01/2024 00:31"],
+        "value": [1, 3, 5, 7, 100]
+    });
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": data,
+                "ops": [
+                    { "op": "parse_time", "column": "at" },
+                    { "op": "parse_time", "column": "local", "format": "%d/%m/%Y %H:%M", "as": "parsed" },
+                    { "op": "rolling", "column": "value", "window": 2 },
+                    { "op": "lag", "column": "value" },
+                    { "op": "diff", "column": "value" },
+                    { "op": "anomalies", "column": "value", "method": "iqr" },
+                    { "op": "anomalies", "column": "value", "threshold": 1.5 },
+                    { "op": "select", "columns": ["at", "parsed", "rolling_mean_value", "lag_value", "diff_value"] }
+                ]
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(
+        result["rows"][1],
+        json!({
+            "at": "2024-01-01T00:04:00Z",
+            "parsed": "2024-01-01T00:04:00Z",
+            "rolling_mean_value": 2.0,
+            "lag_value": 1,
+            "diff_value": 2
+        }),
+        "{}",
+        resp
+    );
+    assert_eq!(result["rows"][0]["rolling_mean_value"], json!(null));
+    assert_eq!(result["rows"][0]["lag_value"], json!(null));
+    assert_eq!(result["rows"][3]["parsed"], json!(null));
+    assert_eq!(result["rows"][4]["diff_value"], 93);
+
+    let iqr = &result["results"][0]["value"];
+    assert_eq!(iqr["method"], "interquartile range");
+    assert_eq!(iqr["q1"], 3.0);
+    assert_eq!(iqr["upper_bound"], 13.0);
+    assert_eq!(iqr["count"], 1);
+    assert_eq!(iqr["anomalies"][0]["index"], 4);
+    assert_eq!(iqr["anomalies"][0]["value"], 100);
+    assert_eq!(iqr["anomalies"][0]["row"]["at"], "2024-01-01T00:31:00Z");
+    let z_score = &result["results"][1]["value"];
+    assert_eq!(z_score["method"], "z-score");
+    assert_eq!(z_score["threshold"], 1.5);
+    assert_eq!(z_score["count"], 1);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": data,
+                "ops": [
+                    { "op": "parse_time", "column": "at" },
+                    { "op": "resample", "column": "at", "every": "10m", "aggregations": [
+                        { "fn": "mean", "column": "value", "as": "value" },
+                        { "fn": "count" }
+                    ] }
+                ]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([
+            { "at": "2024-01-01T00:00:00Z", "value": 2.0, "count": 2 },
+            { "at": "2024-01-01T00:10:00Z", "value": 6.0, "count": 2 },
+            { "at": "2024-01-01T00:20:00Z", "value": null, "count": 0 },
+            { "at": "2024-01-01T00:30:00Z", "value": 100.0, "count": 1 }
+        ]),
+        "{}",
+        resp
+    );
+
+    for (ops, message) in [
+        (
+            json!([{ "op": "resample", "column": "local", "every": "1h", "aggregations": [{ "fn": "count" }] }]),
+            "Cannot read \"01/01/2024 00:00\" in row 0 of column 'local' as a timestamp; convert it with 'parse_time' first",
+        ),
+        (
+            json!([{ "op": "resample", "column": "at", "every": "5x", "aggregations": [] }]),
+            "invalid interval '5x'",
+        ),
+        (
+            json!([{ "op": "lag", "column": "value", "as": "at" }]),
+            "output column 'at' already exists",
+        ),
+        (
+            json!([{ "op": "rolling", "column": "local", "window": 3 }]),
+            "column 'local' holds text, not numbers",
+        ),
+    ] {
+        let resp = client
+            .call_tool(
+                "data_analysis",
+                json!({ "data": data, "ops": ops, "mode": "local" }),
+            )
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(text.contains(message), "{}", text);
+    }
+
+    // Negative periods look ahead, and periods beyond the table leave only nulls
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": { "v": [1, 4, 9] },
+                "ops": [
+                    { "op": "lag", "column": "v", "periods": -1, "as": "next" },
+                    { "op": "diff", "column": "v", "periods": -2, "as": "ahead" },
+                    { "op": "lag", "column": "v", "periods": 5, "as": "far" }
+                ]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["rows"],
+        json!([
+            { "v": 1, "next": 4, "ahead": -8, "far": null },
+            { "v": 4, "next": 9, "ahead": null, "far": null },
+            { "v": 9, "next": null, "ahead": null, "far": null }
+        ]),
+        "{}",
+        resp
+    );
+
+    for (data, op, message) in [
+        (
+            json!({ "at": ["2024-01-01", "2024-12-31"] }),
+            json!({ "op": "resample", "column": "at", "every": "1m", "aggregations": [{ "fn": "count" }] }),
+            "Resampling would produce 525601 intervals, more than the limit of 100000",
+        ),
+        (
+            json!({ "v": [1, null] }),
+            json!({ "op": "anomalies", "column": "v" }),
+            "z-score anomaly detection needs at least two values",
+        ),
+        (
+            json!({ "v": [null, null] }),
+            json!({ "op": "anomalies", "column": "v", "method": "iqr" }),
+            "Column 'v' has no numbers",
+        ),
+    ] {
+        let resp = client
+            .call_tool("data_analysis", json!({ "data": data, "ops": [op] }))
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(text.contains(message), "{}", text);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rolling_windows() -> Result<()> {
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+    let data = json!({ "value": [3, null, 1, 4, 1, 5] });
+    let ops: Vec<Value> = ["count", "sum", "mean", "stddev", "min", "max", "median"]
+        .iter()
+        .map(|func| json!({ "op": "rolling", "column": "value", "window": 3, "fn": func }))
+        .chain([json!({ "op": "rolling", "column": "value", "window": 7, "as": "wide" })])
+        .collect();
+    let resp = client
+        .call_tool("data_analysis", json!({ "data": data, "ops": ops }))
+        .await?;
+    let rows = resp["result"]["rows"].as_array().expect("rows");
+    let column = |name: &str| -> Vec<Value> { rows.iter().map(|r| r[name].clone()).collect() };
+
+    // Windows cover rows, not values: nulls count toward the window but not the numbers
+    assert_eq!(
+        column("rolling_count_value"),
+        json!([null, null, 2, 2, 3, 3]).as_array().unwrap().clone(),
+        "{}",
+        resp
+    );
+    assert_eq!(
+        column("rolling_sum_value"),
+        json!([null, null, 4.0, 5.0, 6.0, 10.0])
+            .as_array()
+            .unwrap()
+            .clone()
+    );
+    assert_eq!(
+        column("rolling_min_value"),
+        json!([null, null, 1.0, 1.0, 1.0, 1.0])
+            .as_array()
+            .unwrap()
+            .clone()
+    );
+    assert_eq!(
+        column("rolling_max_value"),
+        json!([null, null, 3.0, 4.0, 4.0, 5.0])
+            .as_array()
+            .unwrap()
+            .clone()
+    );
+    assert_eq!(
+        column("rolling_median_value"),
+        json!([null, null, 2.0, 2.5, 1.0, 4.0])
+            .as_array()
+            .unwrap()
+            .clone()
+    );
+    let approx = |name: &str, expected: [f64; 4]| {
+        let actual = column(name);
+        assert_eq!(actual[..2], [Value::Null, Value::Null], "{}", name);
+        for (value, expected) in actual[2..].iter().zip(expected) {
+            let value = value.as_f64().expect("a number");
+            assert!(
+                (value - expected).abs() < 1e-9,
+                "{}: {} != {}",
+                name,
+                value,
+                expected
+            );
+        }
+    };
+    approx("rolling_mean_value", [2.0, 2.5, 2.0, 10.0 / 3.0]);
+    approx(
+        "rolling_stddev_value",
+        [
+            2f64.sqrt(),
+            4.5f64.sqrt(),
+            3f64.sqrt(),
+            (26.0f64 / 3.0 / 2.0).sqrt(),
+        ],
+    );
+    // A window wider than the data never fills
+    assert!(column("wide").iter().all(Value::is_null), "{}", resp);
+
+    // Time-series ops over a table without rows return no rows
+    for op in [
+        json!({ "op": "rolling", "column": "value", "window": 2 }),
+        json!({ "op": "lag", "column": "value" }),
+        json!({ "op": "resample", "column": "value", "every": "1h", "aggregations": [{ "fn": "count" }] }),
+    ] {
+        let resp = client
+            .call_tool(
+                "data_analysis",
+                json!({ "data": { "value": [] }, "ops": [op], "mode": "local" }),
+            )
+            .await?;
+        assert_eq!(resp["result"]["rows"], json!([]), "{}: {}", op, resp);
+    }
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": data, "ops": [{ "op": "rolling", "column": "value", "window": 0 }] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tabular_output_formats() -> Result<()> {
+    let mut client = TestClient::start(&["--aion-r-api-url", "http://127.0.0.1:9"]).await?;
+    let ops = json!([{ "op": "sort", "by": ["units"] }]);
+    let data = "region,product,units,price\nnorth,a,10,2.5\nsouth,b,4,10\nnorth,b,6,10\neast,a,,2.5\nsouth,a,8,2.5\n";
+    let csv = json!({ "infer_types": true });
+
+    // Columns keep the file's order, which JSON rows would lose
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": data, "csv": csv, "ops": ops }),
+        )
+        .await?;
+    let content = &resp["result"]["content"];
+    assert_eq!(content.as_array().map(Vec::len), Some(1), "{}", resp);
+    assert_eq!(content[0]["type"], "text");
+    let markdown = content[0]["text"].as_str().unwrap();
+    assert!(
+        markdown.starts_with(
+            "| region | product | units | price |\n| --- | --- | --- | --- |\n| south | b | 4 | 10 |\n"
+        ),
+        "{}",
+        markdown
+    );
+    assert!(
+        markdown.ends_with("| east | a |  | 2.5 |\n"),
+        "{}",
+        markdown
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": data,
+                "csv": csv,
+                "ops": ops,
+                "output_format": "text",
+                "max_display_rows": 2,
+                "max_display_columns": 3
+            }),
+        )
+        .await?;
+    let content = &resp["result"]["content"];
+    assert_eq!(
+        content[0]["text"],
+        "region  product  units\n------  -------  -----\nsouth   b            4\nnorth   b            6\n",
+        "{}",
+        resp
+    );
+    assert_eq!(
+        content[1]["text"],
+        "Truncated: showing the first 2 of 5 rows and 3 of 4 columns (omitted: price)."
+    );
+    assert_eq!(resp["result"]["rows"].as_array().unwrap().len(), 5);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": [{ "name": "a, b", "note": "say \"hi\"" }],
+                "ops": [{ "op": "limit", "n": 1 }],
+                "output_format": "csv"
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["content"][0]["text"],
+        "name,note\n\"a, b\",\"say \"\"hi\"\"\"\n"
+    );
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": sales(), "ops": ops, "output_format": "json" }),
+        )
+        .await?;
+    assert!(resp["result"].get("content").is_none(), "{}", resp);
+
+    // Statistics alone are not tabular
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": sales(), "ops": ["count"] }),
+        )
+        .await?;
+    assert!(resp["result"].get("content").is_none(), "{}", resp);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": sales(), "ops": ops, "output_format": "html" }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+
+    for (key, value) in [
+        ("max_display_rows", json!(0)),
+        ("max_display_columns", json!("5")),
+    ] {
+        let mut inputs = json!({ "data": sales(), "ops": ops });
+        inputs[key] = value;
+        let resp = client.call_tool("data_analysis", inputs).await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        assert!(resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains(&format!("'{}' must be a positive integer", key)));
+    }
+
+    // Cells cannot break the Markdown table, and an empty result still has its header
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": [{ "cmd": "a | b", "note": "two\nlines" }],
+                "ops": [{ "op": "limit", "n": 5 }]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["content"][0]["text"],
+        "| cmd | note |\n| --- | --- |\n| a \\| b | two lines |\n",
+        "{}",
+        resp
+    );
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": sales(),
+                "ops": [
+                    { "op": "filter", "column": "units", "cmp": "gt", "value": 100 },
+                    { "op": "select", "columns": ["region", "units"] }
+                ]
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["content"],
+        json!([{ "type": "text", "text": "| region | units |\n| --- | --- |\n" }]),
+        "{}",
+        resp
+    );
+
+    // Only the first omitted columns are named
+    let wide: serde_json::Map<String, Value> = (1..=14)
+        .map(|i| (format!("c{:02}", i), json!([i])))
+        .collect();
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": wide,
+                "ops": [{ "op": "limit", "n": 1 }],
+                "max_display_columns": 2
+            }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["content"][1]["text"],
+        "Truncated: showing 2 of 14 columns (omitted: c03, c04, c05, c06, c07, c08, c09, c10, c11, c12, and 2 more).",
+        "{}",
+        resp
+    );
+    Ok(())
+}
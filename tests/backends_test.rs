@@ -0,0 +1,686 @@
+// tests/backends_test.rs
+
+mod common;
+
+use anyhow::Result;
+use common::TestClient;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_openai_backend_chat_and_models() -> Result<()> {
+    let openai = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(json!({
+            "model": "llama-3",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "Meaning of life?" }
+            ],
+            "temperature": 0.2,
+            "stream": false
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "model": "llama-3",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "42" },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6 }
+        })))
+        .mount(&openai)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "cmpl-2",
+            "choices": [{ "index": 0, "text": " forty-two", "finish_reason": "length" }]
+        })))
+        .mount(&openai)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": [{ "id": "llama-3", "object": "model", "owned_by": "vllm" }]
+        })))
+        .mount(&openai)
+        .await;
+
+    let mut client =
+        TestClient::start(&["--backend", "openai", "--openai-api-url", &openai.uri()]).await?;
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({
+                "model": "llama-3",
+                "prompt": "Meaning of life?",
+                "params": { "system": "Be brief.", "temperature": 0.2 }
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["inference_id"], "chatcmpl-1");
+    assert_eq!(result["output"], "42");
+    assert_eq!(result["usage"]["total_tokens"], 6);
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "llama-3", "prompt": "The answer is", "params": { "mode": "completion" } }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["output"], " forty-two");
+    assert_eq!(resp["result"]["model"], "llama-3");
+
+    let resp = client
+        .request(
+            "resources/list",
+            json!({ "uri": "aion-r://models/catalog" }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"],
+        json!([{ "id": "llama-3", "name": "llama-3", "owned_by": "vllm" }])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ollama_backend_streams_ndjson() -> Result<()> {
+    let ollama = MockServer::start().await;
+    let chat_stream = [
+        json!({ "model": "llama3", "message": { "role": "assistant", "content": "Forty" }, "done": false }),
+        json!({ "model": "llama3", "message": { "role": "assistant", "content": "-two" }, "done": false }),
+        json!({
+            "model": "llama3",
+            "message": { "role": "assistant", "content": "" },
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 7,
+            "eval_count": 3
+        }),
+    ]
+    .iter()
+    .map(|chunk| format!("{}\n", chunk))
+    .collect::<String>();
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .and(body_partial_json(json!({
+            "model": "llama3",
+            "messages": [{ "role": "user", "content": "Meaning of life?" }],
+            "stream": true,
+            "options": { "temperature": 0.1, "num_predict": 16 }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(chat_stream, "application/x-ndjson"))
+        .mount(&ollama)
+        .await;
+
+    let generate_stream = format!(
+        "{}\n{}",
+        json!({ "model": "llama3", "response": "4", "done": false }),
+        json!({ "model": "llama3", "response": "2", "done": true, "done_reason": "length" })
+    );
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .and(body_partial_json(
+            json!({ "prompt": "The answer is", "system": "Terse.", "format": "json" }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(generate_stream, "application/x-ndjson"),
+        )
+        .mount(&ollama)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "models": [{
+                "name": "llama3:latest",
+                "size": 4661224676u64,
+                "details": { "family": "llama", "parameter_size": "8B" }
+            }]
+        })))
+        .mount(&ollama)
+        .await;
+
+    let mut client =
+        TestClient::start(&["--backend", "ollama", "--ollama-url", &ollama.uri()]).await?;
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({
+                "model": "llama3",
+                "prompt": "Meaning of life?",
+                "params": { "temperature": 0.1, "max_tokens": 16 }
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["output"], "Forty-two");
+    assert_eq!(result["finish_reason"], "stop");
+    assert_eq!(result["usage"]["completion_tokens"], 3);
+    assert_eq!(result["chunks"], 3);
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({
+                "model": "llama3",
+                "prompt": "The answer is",
+                "params": { "mode": "generate", "system": "Terse.", "format": "json" }
+            }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["output"], "42");
+
+    let resp = client
+        .request(
+            "resources/list",
+            json!({ "uri": "aion-r://models/catalog" }),
+        )
+        .await?;
+    assert_eq!(resp["result"][0]["id"], "llama3:latest");
+    assert_eq!(resp["result"][0]["family"], "llama");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_routes_select_backend_by_model() -> Result<()> {
+    let openai = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "chatcmpl-1",
+            "choices": [{ "index": 0, "message": { "role": "assistant", "content": "from vllm" } }]
+        })))
+        .mount(&openai)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [{ "id": "llama-3-8b", "owned_by": "vllm" }]
+        })))
+        .mount(&openai)
+        .await;
+
+    let aion = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/infer"))
+        .and(body_partial_json(json!({ "model": "universe-brain-v2" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "success",
+            "inference_id": "inf_1",
+            "output": "from aion"
+        })))
+        .mount(&aion)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/models"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!([{ "id": "universe-brain-v2" }])),
+        )
+        .mount(&aion)
+        .await;
+
+    let routes_file =
+        std::env::temp_dir().join(format!("aionr2-routes-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &routes_file,
+        json!({
+            "backends": {
+                "vllm": { "kind": "openai", "url": openai.uri() },
+                "aion": { "kind": "aion-r", "url": aion.uri() }
+            },
+            "routes": [
+                { "glob": "llama-*", "backend": "vllm" },
+                { "prefix": "universe-", "backend": "aion" }
+            ]
+        })
+        .to_string(),
+    )?;
+
+    let mut client = TestClient::start(&["--routes", routes_file.to_str().unwrap()]).await?;
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "llama-3-8b", "prompt": "hi" }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["output"], "from vllm");
+    assert_eq!(resp["result"]["source"], "vllm");
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "universe-brain-v2", "prompt": "hi" }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["output"], "from aion");
+    assert_eq!(resp["result"]["source"], "aion");
+
+    let resp = client
+        .call_tool("run_inference", json!({ "model": "gpt-4", "prompt": "hi" }))
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+
+    let resp = client
+        .request(
+            "resources/list",
+            json!({ "uri": "aion-r://models/catalog" }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"],
+        json!([
+            { "id": "universe-brain-v2", "source": "aion" },
+            { "id": "llama-3-8b", "name": "llama-3-8b", "owned_by": "vllm", "source": "vllm" }
+        ])
+    );
+
+    std::fs::remove_file(&routes_file)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_backend_fixtures_and_scenario() -> Result<()> {
+    let mut client = TestClient::start(&["--backend", "mock"]).await?;
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "mock-small", "prompt": "hello" }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["output"],
+        "Mock response from mock-small to: hello"
+    );
+    let id = resp["result"]["inference_id"].clone();
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "mock-small", "prompt": "hello" }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["inference_id"], id);
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2, 3, 6], "ops": ["mean", "max"], "mode": "remote" }),
+        )
+        .await?;
+    assert_eq!(
+        resp["result"]["results"],
+        json!({ "mean": 3.0, "max": 6.0 })
+    );
+
+    let scenario_file =
+        std::env::temp_dir().join(format!("aionr2-scenario-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &scenario_file,
+        json!({
+            "models": [{ "id": "scripted" }],
+            "inference": [
+                { "prompt_contains": "fail", "latency_ms": 10, "error": "overloaded" },
+                { "model": "stream-*", "chunks": ["Hel", "lo"], "chunk_delay_ms": 5 }
+            ],
+            "analysis": [
+                { "op": "forecast", "response": { "status": "success", "forecast": [7] } }
+            ]
+        })
+        .to_string(),
+    )?;
+    let mut client = TestClient::start(&[
+        "--backend",
+        "mock",
+        "--mock-scenario",
+        scenario_file.to_str().unwrap(),
+    ])
+    .await?;
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "mock-small", "prompt": "please fail" }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32000);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("overloaded"));
+
+    let resp = client
+        .call_tool(
+            "run_inference",
+            json!({ "model": "stream-1", "prompt": "hi" }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["output"], "Hello");
+    assert_eq!(resp["result"]["chunks"], 2);
+
+    let resp = client
+        .call_tool("data_analysis", json!({ "data": [1], "ops": ["forecast"] }))
+        .await?;
+    assert_eq!(resp["result"]["forecast"], json!([7]));
+
+    let resp = client
+        .request(
+            "resources/list",
+            json!({ "uri": "aion-r://models/catalog" }),
+        )
+        .await?;
+    assert_eq!(resp["result"], json!([{ "id": "scripted" }]));
+
+    std::fs::remove_file(&scenario_file)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_privacy_policy_anonymizes_backend_data() -> Result<()> {
+    use hmac::{Hmac, Mac};
+
+    let aion = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "completed" })))
+        .mount(&aion)
+        .await;
+
+    let policy_file =
+        std::env::temp_dir().join(format!("aionr2-privacy-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &policy_file,
+        json!({
+            "salt": "pepper",
+            "rules": [
+                { "columns": ["ssn", "card_*"], "action": "drop" },
+                { "columns": ["email"], "action": "hash" },
+                { "columns": ["phone"], "action": "mask", "keep_last": 4 },
+                { "columns": ["age"], "action": "bucket", "width": 10 },
+                { "columns": ["region", "spend"], "action": "keep" }
+            ],
+            "unmatched": "drop"
+        })
+        .to_string(),
+    )?;
+
+    let mut client = TestClient::start(&[
+        "--aion-r-api-url",
+        &aion.uri(),
+        "--privacy-policy",
+        policy_file.to_str().unwrap(),
+    ])
+    .await?;
+
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({
+                "data": [
+                    { "email": "ann@example.com", "phone": "555-0101", "ssn": "123-45-6789", "card_number": "4111", "age": 34, "region": "north", "spend": 12.5, "notes": "vip" },
+                    { "email": "bob@example.com", "phone": "42", "ssn": null, "card_number": "5500", "age": 61.5, "region": "south", "spend": 3, "notes": null },
+                    { "email": "ann@example.com", "phone": null, "ssn": "987-65-4321", "card_number": null, "age": "unknown", "region": "north", "spend": 7, "notes": "" }
+                ],
+                "ops": ["mean"],
+                "mode": "remote"
+            }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["status"], "completed", "{}", resp);
+    assert_eq!(
+        resp["result"]["anonymized"],
+        json!({
+            "age": "bucket", "card_number": "drop", "email": "hash",
+            "notes": "drop", "phone": "mask", "ssn": "drop"
+        })
+    );
+
+    let requests = aion.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body)?;
+    let hash = |text: &str| {
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(b"pepper").unwrap();
+        mac.update(text.as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    };
+    assert_eq!(
+        body["data"],
+        json!([
+            { "email": hash("ann@example.com"), "phone": "****0101", "age": 30, "region": "north", "spend": 12.5 },
+            { "email": hash("bob@example.com"), "phone": "**", "age": 60, "region": "south", "spend": 3 },
+            { "email": hash("ann@example.com"), "phone": null, "age": null, "region": "north", "spend": 7 }
+        ])
+    );
+    assert_eq!(body["ops"], json!(["mean"]));
+
+    // Data the policy cannot read column by column never leaves the machine
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": { "nested": { "email": "ann@example.com" } }, "ops": ["mean"] }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    assert_eq!(aion.received_requests().await.unwrap().len(), 1);
+
+    // Local runs are not affected
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [{ "ssn": "123-45-6789" }], "ops": ["count"], "mode": "local" }),
+        )
+        .await?;
+    assert!(resp["result"].get("anonymized").is_none(), "{}", resp);
+
+    std::fs::write(
+        &policy_file,
+        json!({ "rules": [{ "columns": ["email"], "action": "hash" }] }).to_string(),
+    )?;
+    assert!(
+        TestClient::start(&["--privacy-policy", policy_file.to_str().unwrap()])
+            .await
+            .is_err()
+    );
+
+    std::fs::remove_file(&policy_file)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oversized_data_is_sampled_for_the_backend() -> Result<()> {
+    let aion = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "completed" })))
+        .mount(&aion)
+        .await;
+    let mut client = TestClient::start(&[
+        "--aion-r-api-url",
+        &aion.uri(),
+        "--max-backend-rows",
+        "10",
+        "--max-backend-bytes",
+        "2000",
+    ])
+    .await?;
+
+    let data: Vec<_> = (0..100)
+        .map(|id| json!({ "id": id, "group": if id % 5 == 0 { "b" } else { "a" } }))
+        .collect();
+    let mut sent_ids = Vec::new();
+    for sampling in [
+        json!({ "seed": 7 }),
+        json!({ "seed": 7 }),
+        json!({ "method": "stratified", "by": "group", "seed": 1 }),
+        json!({ "method": "head_tail" }),
+    ] {
+        let resp = client
+            .call_tool(
+                "data_analysis",
+                json!({ "data": data, "ops": ["mean"], "mode": "remote", "sampling": sampling }),
+            )
+            .await?;
+        let described = &resp["result"]["sampling"];
+        assert_eq!(described["rows_before"], 100, "{}", resp);
+        assert_eq!(described["rows_after"], 10);
+        assert!(described["bytes_after"].as_u64().unwrap() <= 2000);
+        assert!(described["bytes_before"].as_u64().unwrap() > 2000);
+
+        let requests = aion.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests.last().unwrap().body)?;
+        let rows = body["data"].as_array().unwrap();
+        let ids: Vec<u64> = rows.iter().map(|r| r["id"].as_u64().unwrap()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "{:?}", ids);
+        if sampling["method"] == "stratified" {
+            assert_eq!(
+                described,
+                &json!({
+                    "method": "stratified", "by": "group", "seed": 1,
+                    "rows_before": 100, "rows_after": 10,
+                    "bytes_before": described["bytes_before"], "bytes_after": described["bytes_after"]
+                })
+            );
+            assert_eq!(rows.iter().filter(|r| r["group"] == "b").count(), 2);
+        }
+        sent_ids.push(ids);
+    }
+    // The same seed picks the same rows
+    assert_eq!(sent_ids[0], sent_ids[1]);
+    assert_eq!(sent_ids[3], [0, 1, 2, 3, 4, 95, 96, 97, 98, 99]);
+
+    // The byte budget can be the tighter one
+    let wide: Vec<_> = (0..8)
+        .map(|id| json!({ "id": id, "note": "x".repeat(400) }))
+        .collect();
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": wide, "ops": ["count"], "mode": "remote" }),
+        )
+        .await?;
+    let described = &resp["result"]["sampling"];
+    assert_eq!(described["method"], "random", "{}", resp);
+    assert_eq!(described["seed"], 0);
+    assert!(described["rows_after"].as_u64().unwrap() <= 4);
+    assert!(described["bytes_after"].as_u64().unwrap() <= 2000);
+
+    // Data within the budgets is sent as it is
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2, 3], "ops": ["mean"], "mode": "remote" }),
+        )
+        .await?;
+    assert!(resp["result"].get("sampling").is_none(), "{}", resp);
+    let requests = aion.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests.last().unwrap().body)?;
+    assert_eq!(body["data"], json!([1, 2, 3]));
+
+    for inputs in [
+        json!({ "data": { "blob": "x".repeat(3000) }, "ops": ["mean"] }),
+        json!({ "data": data, "ops": ["mean"], "mode": "remote", "sampling": { "method": "stratified" } }),
+        json!({ "data": data, "ops": ["mean"], "mode": "remote", "sampling": { "method": "systematic" } }),
+    ] {
+        let resp = client.call_tool("data_analysis", inputs).await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_timeout_is_configurable() -> Result<()> {
+    let aion = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "status": "completed" }))
+                .set_delay(std::time::Duration::from_secs(3)),
+        )
+        .mount(&aion)
+        .await;
+    let mut client =
+        TestClient::start(&["--aion-r-api-url", &aion.uri(), "--request-timeout", "1"]).await?;
+
+    let started = std::time::Instant::now();
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2], "ops": ["mean"], "mode": "remote" }),
+        )
+        .await?;
+    assert!(resp.get("error").is_some(), "{}", resp);
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_byte_budget_counts_anonymized_data() -> Result<()> {
+    let aion = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "completed" })))
+        .mount(&aion)
+        .await;
+    let policy_file =
+        std::env::temp_dir().join(format!("aionr2-privacy-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &policy_file,
+        json!({ "salt": "pepper", "rules": [{ "columns": ["email"], "action": "hash" }] })
+            .to_string(),
+    )?;
+    let mut client = TestClient::start(&[
+        "--aion-r-api-url",
+        &aion.uri(),
+        "--privacy-policy",
+        policy_file.to_str().unwrap(),
+        "--max-backend-bytes",
+        "4000",
+    ])
+    .await?;
+
+    // About 3 KB as given, but hashing makes every email 64 characters long
+    let data: Vec<_> = (0..100)
+        .map(|id| json!({ "id": id, "email": format!("u{}@x.io", id) }))
+        .collect();
+    assert!(serde_json::to_vec(&data)?.len() < 4000);
+    let resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": data, "ops": ["count"], "mode": "remote" }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["anonymized"], json!({ "email": "hash" }), "{}", resp);
+    assert_eq!(result["sampling"]["rows_before"], 100);
+    assert!(result["sampling"]["bytes_before"].as_u64().unwrap() > 4000);
+
+    let requests = aion.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body)?;
+    assert!(serde_json::to_vec(&body["data"])?.len() <= 4000);
+    assert_eq!(
+        body["data"].as_array().unwrap().len() as u64,
+        result["sampling"]["rows_after"].as_u64().unwrap()
+    );
+    assert!(body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|row| row["email"].as_str().unwrap().len() == 64));
+
+    std::fs::remove_file(&policy_file)?;
+    Ok(())
+}
@@ -0,0 +1,198 @@
+// tests/chart_test.rs
+
+mod common;
+
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use common::TestClient;
+use serde_json::{json, Value};
+
+fn decode(block: &Value) -> Vec<u8> {
+    BASE64
+        .decode(block["data"].as_str().unwrap())
+        .expect("image data is base64")
+}
+
+#[tokio::test]
+async fn test_render_chart_returns_images() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+    let metrics = json!({
+        "at": ["2024-01-01T00:00:00Z", "2024-01-01T00:20:00Z", "2024-01-01T00:40:00Z", "2024-01-01T01:10:00Z"],
+        "host": ["a", "b", "a", "b"],
+        "latency": [120, 80, 140, 95],
+        "errors": [1, 0, 3, 2]
+    });
+
+    let resp = client
+        .call_tool(
+            "render_chart",
+            json!({
+                "data": metrics,
+                "chart": "line",
+                "x": "at",
+                "y": ["latency", "errors"],
+                "title": "Latency & errors",
+                "formats": ["png", "svg"]
+            }),
+        )
+        .await?;
+    let content = resp["result"]["content"]
+        .as_array()
+        .expect("content blocks");
+    assert_eq!(content.len(), 3, "{}", resp);
+    assert_eq!(content[0]["type"], "image");
+    assert_eq!(content[0]["mimeType"], "image/png");
+    let png = decode(&content[0]);
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    // The IHDR chunk holds the image size
+    assert_eq!(u32::from_be_bytes(png[16..20].try_into()?), 800);
+    assert_eq!(u32::from_be_bytes(png[20..24].try_into()?), 500);
+    assert_eq!(content[1]["mimeType"], "image/svg+xml");
+    let svg = String::from_utf8(decode(&content[1]))?;
+    assert!(svg.starts_with("<svg"), "{}", svg);
+    assert_eq!(svg.matches("<polyline").count(), 2);
+    assert!(svg.contains("Latency &amp; errors"));
+    assert!(svg.contains(">01-01 00:00<"), "{}", svg);
+    assert_eq!(
+        content[2],
+        json!({ "type": "text", "text": "line chart of 4 rows" })
+    );
+
+    // Ops run first; the table they produce is drawn
+    let resp = client
+        .call_tool(
+            "render_chart",
+            json!({
+                "data": metrics,
+                "ops": [{ "op": "group_by", "by": ["host"], "aggregations": [
+                    { "fn": "mean", "column": "latency", "as": "latency" }
+                ] }],
+                "chart": "bar",
+                "x": "host",
+                "width": 400,
+                "height": 300,
+                "formats": ["svg"]
+            }),
+        )
+        .await?;
+    let content = resp["result"]["content"].as_array().unwrap();
+    assert_eq!(content.len(), 2, "{}", resp);
+    let svg = String::from_utf8(decode(&content[0]))?;
+    assert!(svg.contains(r#"width="400" height="300""#));
+    // Background plus one bar per host
+    assert_eq!(svg.matches("<rect").count(), 3, "{}", svg);
+    assert!(svg.contains(">a<") && svg.contains(">b<"));
+    assert_eq!(content[1]["text"], "bar chart of 2 rows");
+
+    let resp = client
+        .call_tool(
+            "render_chart",
+            json!({
+                "data": [1, 2, 2, 3, 3, 3, 4, 4, 5],
+                "chart": "histogram",
+                "bins": 4,
+                "formats": ["svg"]
+            }),
+        )
+        .await?;
+    let svg = String::from_utf8(decode(&resp["result"]["content"][0]))?;
+    assert_eq!(svg.matches("<rect").count(), 5, "{}", svg);
+    assert!(svg.contains(">count<"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_render_chart_rejects_bad_inputs() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+    let data = json!([
+        { "region": "north", "units": 10 },
+        { "region": "south", "units": 4 }
+    ]);
+
+    for (inputs, message) in [
+        (json!({ "chart": "pie" }), "unknown variant `pie`"),
+        (
+            json!({ "chart": "scatter", "x": "region", "y": "units" }),
+            "A scatter chart needs a numeric or timestamp 'x' column",
+        ),
+        (
+            json!({ "chart": "line", "y": "price" }),
+            "Unknown column 'price'; available columns: region, units",
+        ),
+        (
+            json!({ "chart": "bar", "y": "region" }),
+            "Column 'region' does not hold numbers",
+        ),
+        (
+            json!({ "chart": "bar", "formats": ["gif"] }),
+            "'formats' must be a non-empty array",
+        ),
+        (
+            json!({ "chart": "bar", "width": 10 }),
+            "'width' and 'height' must be between 100 and 4000 pixels",
+        ),
+        (
+            json!({ "chart": "bar", "ops": [{ "op": "mean", "column": "region" }] }),
+            "ops[0] (mean): column 'region' holds text, not numbers",
+        ),
+    ] {
+        let mut inputs = inputs;
+        inputs["data"] = data.clone();
+        let resp = client.call_tool("render_chart", inputs).await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(
+            text.contains("render_chart") && text.contains(message),
+            "{}",
+            text
+        );
+    }
+
+    // Limits that keep the rendering cheap, and data with nothing to draw
+    let many: Vec<i64> = (0..10_001).collect();
+    for (inputs, message) in [
+        (
+            json!({ "data": &many[..201], "chart": "bar" }),
+            "A bar chart can show at most 200 categories, but the data has 201 rows",
+        ),
+        (
+            json!({ "data": many, "chart": "line" }),
+            "The chart would have 10001 points, more than the limit of 10000",
+        ),
+        (
+            json!({ "data": [1, 2], "chart": "histogram", "bins": 0 }),
+            "'bins' must be between 1 and 200",
+        ),
+        (
+            json!({ "data": [1, 2], "chart": "histogram", "bins": 201 }),
+            "'bins' must be between 1 and 200",
+        ),
+        (
+            json!({ "data": { "v": [null, null], "w": [1, 2] }, "chart": "histogram", "y": "v" }),
+            "Column 'v' holds no numbers",
+        ),
+        (
+            json!({ "data": { "v": [] }, "chart": "bar", "y": "v" }),
+            "There are no rows to draw",
+        ),
+        (
+            json!({
+                "data": [1, 2],
+                "chart": "line",
+                "ops": [{ "op": "filter", "column": "value", "cmp": "gt", "value": 5 }]
+            }),
+            "There are no rows to draw",
+        ),
+        (
+            json!({ "data": [{ "name": "a" }], "chart": "line" }),
+            "There is no numeric column to draw; name one in 'y'",
+        ),
+    ] {
+        let resp = client.call_tool("render_chart", inputs).await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(text.contains(message), "{}", text);
+    }
+    Ok(())
+}
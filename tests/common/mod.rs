@@ -0,0 +1,104 @@
+// tests/common/mod.rs
+
+#![allow(dead_code)] // Each test crate uses a different subset of these helpers
+
+use aionr2::{Config, McpServer};
+use anyhow::Result;
+use clap::Parser;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+// Helper function to write a JSON-RPC message to the in-process server
+pub async fn write_rpc_message(
+    stdin: &mut (impl AsyncWriteExt + Unpin),
+    message: &Value,
+) -> Result<()> {
+    let msg_str = serde_json::to_string(message)?;
+    let rpc_frame = format!("Content-Length: {}\r\n\r\n{}", msg_str.len(), msg_str);
+    stdin.write_all(rpc_frame.as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+// Helper function to read a JSON-RPC message from the in-process server
+pub async fn read_rpc_message(
+    stdout: &mut (impl AsyncBufReadExt + Unpin),
+) -> Result<Option<Value>> {
+    let mut buffer = String::new();
+    let mut content_length = 0;
+
+    loop {
+        buffer.clear();
+        if stdout.read_line(&mut buffer).await? == 0 {
+            return Ok(None); // EOF
+        }
+        if buffer.trim().is_empty() {
+            break; // End of headers
+        }
+        if let Some(len_str) = buffer.strip_prefix("Content-Length:") {
+            content_length = len_str.trim().parse::<usize>()?;
+        }
+    }
+
+    let mut body_buf = vec![0; content_length];
+    stdout.read_exact(&mut body_buf).await?;
+    Ok(Some(serde_json::from_slice(&body_buf)?))
+}
+
+/// A client connected to an in-process server session.
+pub struct TestClient {
+    pub server: McpServer,
+    pub stdin: DuplexStream,
+    pub stdout: BufReader<DuplexStream>,
+    next_id: u64,
+}
+
+impl TestClient {
+    /// Starts a server configured with the given command-line arguments.
+    pub async fn start(args: &[&str]) -> Result<Self> {
+        let config = Config::try_parse_from(std::iter::once("aionr2").chain(args.iter().copied()))?;
+        Ok(Self::connect(McpServer::new(&config).await?))
+    }
+
+    /// Runs a new session of `server` over in-memory pipes.
+    pub fn connect(server: McpServer) -> Self {
+        let (client_write, server_read) = tokio::io::duplex(1024 * 1024);
+        let (server_write, client_read) = tokio::io::duplex(1024 * 1024);
+        let session = server.clone();
+        tokio::spawn(async move { session.serve(server_read, server_write).await });
+
+        Self {
+            server,
+            stdin: client_write,
+            stdout: BufReader::new(client_read),
+            next_id: 1,
+        }
+    }
+
+    pub async fn send(&mut self, message: &Value) -> Result<()> {
+        write_rpc_message(&mut self.stdin, message).await
+    }
+
+    pub async fn recv(&mut self) -> Result<Value> {
+        read_rpc_message(&mut self.stdout)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Server closed the connection"))
+    }
+
+    /// Sends a request and returns the full JSON-RPC response.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": id }))
+            .await?;
+        let response = self.recv().await?;
+        assert_eq!(response["id"], id);
+        Ok(response)
+    }
+
+    /// Calls a tool and returns the full JSON-RPC response.
+    pub async fn call_tool(&mut self, name: &str, inputs: Value) -> Result<Value> {
+        self.request("tools/call", json!({ "name": name, "inputs": inputs }))
+            .await
+    }
+}
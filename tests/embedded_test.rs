@@ -0,0 +1,209 @@
+// tests/embedded_test.rs
+
+mod common;
+
+use aionr2::{AnalysisBackend, Backends, Config, InferenceBackend, McpServer, Tool, ToolContext};
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Parser;
+use common::TestClient;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+struct EchoTool;
+
+#[async_trait]
+impl Tool for EchoTool {
+    fn definition(&self) -> aionr2::mcp::types::ToolDefinition {
+        aionr2::mcp::types::ToolDefinition {
+            name: "echo".to_string(),
+            description: "Returns its inputs.".to_string(),
+            inputs: json!({ "type": "object" }),
+        }
+    }
+
+    async fn call(&self, _ctx: &ToolContext, inputs: &Value) -> Result<Value> {
+        Ok(json!({ "echo": inputs }))
+    }
+}
+
+#[tokio::test]
+async fn test_embedded_server_over_duplex() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/infer"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "success",
+            "output": "in-process"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let mut client = TestClient::start(&["--aion-r-api-url", &mock_server.uri()]).await?;
+
+    let init_resp = client
+        .request("initialize", json!({ "protocolVersion": "2024-11-05" }))
+        .await?;
+    assert_eq!(init_resp["result"]["server"]["name"], "aionr2");
+
+    let call_resp = client
+        .call_tool("run_inference", json!({ "model": "m", "prompt": "p" }))
+        .await?;
+    assert_eq!(call_resp["result"]["output"], "in-process");
+
+    // Closing the input ends the session
+    drop(client.stdin);
+    assert!(common::read_rpc_message(&mut client.stdout)
+        .await?
+        .is_none());
+    Ok(())
+}
+
+/// Asks the user for input only after a delay, once the client may have gone.
+struct LateElicitTool;
+
+#[async_trait]
+impl Tool for LateElicitTool {
+    fn definition(&self) -> aionr2::mcp::types::ToolDefinition {
+        aionr2::mcp::types::ToolDefinition {
+            name: "late_elicit".to_string(),
+            description: "Elicits after a delay.".to_string(),
+            inputs: json!({ "type": "object" }),
+        }
+    }
+
+    async fn call(&self, ctx: &ToolContext, _inputs: &Value) -> Result<Value> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let content = ctx
+            .elicitation
+            .request_fields("Still there?", json!({ "type": "object" }))
+            .await?;
+        Ok(json!({ "content": content }))
+    }
+}
+
+#[tokio::test]
+async fn test_requests_after_input_closes_fail_fast() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+    client
+        .request(
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05", "capabilities": { "elicitation": {} } }),
+        )
+        .await?;
+    client
+        .server
+        .update_tools(|tools| tools.register(Arc::new(LateElicitTool)));
+    client.recv().await?;
+
+    client
+        .send(&json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": { "name": "late_elicit", "inputs": {} },
+            "id": 99
+        }))
+        .await?;
+    drop(client.stdin);
+
+    // The tool's request fails at once rather than waiting for an answer that
+    // cannot come, so the session still ends promptly.
+    let response = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match common::read_rpc_message(&mut client.stdout).await? {
+                Some(message) if message.get("method").is_some() => continue,
+                Some(response) => return Ok::<_, anyhow::Error>(response),
+                None => anyhow::bail!("Session ended without answering the tool call"),
+            }
+        }
+    })
+    .await??;
+    assert_eq!(response["id"], 99);
+    assert_eq!(response["error"]["code"], -32000, "{}", response);
+    assert!(common::read_rpc_message(&mut client.stdout)
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embedder_registers_custom_tool() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+    client
+        .request("initialize", json!({ "protocolVersion": "2024-11-05" }))
+        .await?;
+
+    client
+        .server
+        .update_tools(|tools| tools.register(Arc::new(EchoTool)));
+
+    let notification = client.recv().await?;
+    assert_eq!(notification["method"], "notifications/tools/list_changed");
+
+    let call_resp = client
+        .call_tool("echo", json!({ "hello": "world" }))
+        .await?;
+    assert_eq!(call_resp["result"]["echo"]["hello"], "world");
+    Ok(())
+}
+
+struct FixedBackend;
+
+#[async_trait]
+impl InferenceBackend for FixedBackend {
+    async fn run_inference(
+        &self,
+        model: &str,
+        prompt: &str,
+        _params: &Option<Value>,
+    ) -> Result<Value> {
+        Ok(json!({ "status": "success", "output": format!("{}:{}", model, prompt) }))
+    }
+
+    async fn list_models(&self) -> Result<Value> {
+        Ok(json!([{ "id": "fixed" }]))
+    }
+}
+
+#[async_trait]
+impl AnalysisBackend for FixedBackend {
+    async fn data_analysis(&self, data: &Value, _ops: &Value) -> Result<Value> {
+        Ok(json!({ "status": "completed", "rows": data.as_array().map_or(0, Vec::len) }))
+    }
+}
+
+#[tokio::test]
+async fn test_custom_backends() -> Result<()> {
+    let config = Config::try_parse_from(["aionr2"])?;
+    let backend = Arc::new(FixedBackend);
+    let backends = Backends {
+        inference: backend.clone(),
+        analysis: backend,
+    };
+    let mut client = TestClient::connect(McpServer::with_backends(&config, backends)?);
+
+    let call_resp = client
+        .call_tool("run_inference", json!({ "model": "m", "prompt": "p" }))
+        .await?;
+    assert_eq!(call_resp["result"]["output"], "m:p");
+
+    let call_resp = client
+        .call_tool(
+            "data_analysis",
+            json!({ "data": [1, 2, 3], "ops": [], "mode": "remote" }),
+        )
+        .await?;
+    assert_eq!(call_resp["result"]["rows"], 3);
+
+    let list_resp = client
+        .request(
+            "resources/list",
+            json!({ "uri": "aion-r://models/catalog" }),
+        )
+        .await?;
+    assert_eq!(list_resp["result"][0]["id"], "fixed");
+    Ok(())
+}
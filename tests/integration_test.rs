@@ -5,7 +5,7 @@ use serde_json::{json, Value};
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{body_partial_json, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 // Helper function to write a JSON-RPC message to the child process
@@ -52,6 +52,11 @@ async fn read_rpc_message(stdout: &mut (impl AsyncBufReadExt + Unpin)) -> Result
 
 // Helper to spawn the server process for testing
 async fn spawn_server(mock_server: &MockServer) -> Child {
+    spawn_server_with(mock_server, &[], "warn") // Keep test logs clean
+}
+
+// Helper to spawn the server process with extra CLI arguments and a log level
+fn spawn_server_with(mock_server: &MockServer, args: &[&str], log_level: &str) -> Child {
     let binary_name = if cfg!(windows) {
         "aionr2.exe"
     } else {
@@ -64,8 +69,9 @@ async fn spawn_server(mock_server: &MockServer) -> Child {
         .join(binary_name);
 
     Command::new(binary_path)
-        .env("AION_R_API_URL", &mock_server.uri())
-        .env("RUST_LOG", "warn") // Keep test logs clean
+        .args(args)
+        .env("AION_R_API_URL", mock_server.uri())
+        .env("RUST_LOG", log_level)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped()) // Pipe stderr to check for errors if needed
@@ -105,9 +111,12 @@ async fn test_initialize_and_tools_list() -> Result<()> {
     let list_resp = read_rpc_message(&mut stdout).await?.unwrap();
     assert_eq!(list_resp["id"], 2);
     let tools = list_resp["result"]["tools"].as_array().unwrap();
-    assert_eq!(tools.len(), 2);
+    assert_eq!(tools.len(), 5);
     assert!(tools.iter().any(|t| t["name"] == "run_inference"));
     assert!(tools.iter().any(|t| t["name"] == "data_analysis"));
+    assert!(tools.iter().any(|t| t["name"] == "query_data"));
+    assert!(tools.iter().any(|t| t["name"] == "render_chart"));
+    assert!(tools.iter().any(|t| t["name"] == "profile_data"));
 
     // Shutdown
     child.kill().await?;
@@ -250,3 +259,464 @@ async fn test_resources_list_models() -> Result<()> {
     child.kill().await?;
     Ok(())
 }
+
+#[tokio::test]
+async fn test_logs_are_kept_off_stdout() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let mut child = spawn_server_with(&mock_server, &[], "debug");
+    let mut stdin = child.stdin.take().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.take().expect("Failed to open stdout"));
+
+    let init_req = json!({
+        "jsonrpc": "2.0",
+        "method": "initialize",
+        "params": { "protocolVersion": "2024-11-05" },
+        "id": 1
+    });
+    write_rpc_message(&mut stdin, &init_req).await?;
+
+    // The very first bytes on stdout must be a protocol frame, not a log line
+    let mut first_line = String::new();
+    stdout.read_line(&mut first_line).await?;
+    assert!(
+        first_line.starts_with("Content-Length:"),
+        "unexpected stdout output: {:?}",
+        first_line
+    );
+
+    drop(stdin);
+    let output = child.wait_with_output().await?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Starting aionr2 MCP server"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_json_log_file() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let log_dir = std::env::temp_dir().join(format!("aionr2-logs-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&log_dir)?;
+    let log_path = log_dir.join("server.log");
+    let log_arg = log_path.to_string_lossy().to_string();
+
+    let mut child = spawn_server_with(
+        &mock_server,
+        &["--log-file", &log_arg, "--log-format", "json"],
+        "info",
+    );
+    drop(child.stdin.take());
+    let output = child.wait_with_output().await?;
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    let contents = std::fs::read_to_string(&log_path)?;
+    let lines: Vec<Value> = contents
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert!(lines
+        .iter()
+        .any(|l| l["fields"]["message"] == "Starting aionr2 MCP server..."));
+
+    std::fs::remove_dir_all(&log_dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_analysis_query_uses_client_sampling() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let mut child = spawn_server(&mock_server).await;
+    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.as_mut().expect("Failed to open stdout"));
+
+    // The backend must receive the ops chosen by the client model
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .and(body_partial_json(json!({ "ops": ["mean"] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "completed",
+            "results": [{"metric": "mean", "value": 15.4}]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let init_req = json!({
+        "jsonrpc": "2.0",
+        "method": "initialize",
+        "params": { "protocolVersion": "2024-11-05", "capabilities": { "sampling": {} } },
+        "id": 1
+    });
+    write_rpc_message(stdin, &init_req).await?;
+    read_rpc_message(&mut stdout).await?.unwrap();
+
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": {
+            "name": "data_analysis",
+            "inputs": {
+                "data": [10, 12, 15, 18, 22],
+                "query": "What is the average value?",
+                "mode": "remote"
+            }
+        },
+        "id": 2
+    });
+    write_rpc_message(stdin, &call_req).await?;
+
+    // The server asks the client's model to pick the ops
+    let sampling_req = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(sampling_req["method"], "sampling/createMessage");
+    let prompt = sampling_req["params"]["messages"][0]["content"]["text"]
+        .as_str()
+        .unwrap();
+    assert!(prompt.contains("What is the average value?"));
+
+    let sampling_resp = json!({
+        "jsonrpc": "2.0",
+        "result": {
+            "role": "assistant",
+            "content": { "type": "text", "text": "```json\n[\"mean\"]\n```" },
+            "model": "client-model",
+            "stopReason": "endTurn"
+        },
+        "id": sampling_req["id"]
+    });
+    write_rpc_message(stdin, &sampling_resp).await?;
+
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["id"], 2);
+    assert!(
+        call_resp["error"].is_null(),
+        "RPC call failed: {}",
+        call_resp["error"]
+    );
+    assert_eq!(call_resp["result"]["results"][0]["value"], 15.4);
+    assert_eq!(call_resp["result"]["planned_ops"], json!(["mean"]));
+
+    child.kill().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_analysis_query_without_sampling_support() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let mut child = spawn_server(&mock_server).await;
+    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.as_mut().expect("Failed to open stdout"));
+
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": {
+            "name": "data_analysis",
+            "inputs": { "data": [1, 2, 3], "query": "average" }
+        },
+        "id": 7
+    });
+    write_rpc_message(stdin, &call_req).await?;
+
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["id"], 7);
+    assert_eq!(call_resp["error"]["code"], -32602);
+
+    child.kill().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inference_elicits_missing_model() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let mut child = spawn_server(&mock_server).await;
+    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.as_mut().expect("Failed to open stdout"));
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/infer"))
+        .and(body_partial_json(json!({ "model": "universe-brain-v2" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "success",
+            "output": "42"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let init_req = json!({
+        "jsonrpc": "2.0",
+        "method": "initialize",
+        "params": { "protocolVersion": "2024-11-05", "capabilities": { "elicitation": {} } },
+        "id": 1
+    });
+    write_rpc_message(stdin, &init_req).await?;
+    read_rpc_message(&mut stdout).await?.unwrap();
+
+    // First call: the user picks a model
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": "run_inference", "inputs": { "prompt": "Meaning of life?" } },
+        "id": 2
+    });
+    write_rpc_message(stdin, &call_req).await?;
+
+    let elicit_req = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(elicit_req["method"], "elicitation/create");
+    assert_eq!(
+        elicit_req["params"]["requestedSchema"]["required"],
+        json!(["model"])
+    );
+    let elicit_resp = json!({
+        "jsonrpc": "2.0",
+        "result": { "action": "accept", "content": { "model": "universe-brain-v2" } },
+        "id": elicit_req["id"]
+    });
+    write_rpc_message(stdin, &elicit_resp).await?;
+
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["id"], 2);
+    assert_eq!(call_resp["result"]["output"], "42");
+
+    // Second call: the user declines, so nothing is sent to the backend
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": "run_inference", "inputs": { "prompt": "Meaning of life?" } },
+        "id": 3
+    });
+    write_rpc_message(stdin, &call_req).await?;
+
+    let elicit_req = read_rpc_message(&mut stdout).await?.unwrap();
+    let elicit_resp = json!({
+        "jsonrpc": "2.0",
+        "result": { "action": "decline" },
+        "id": elicit_req["id"]
+    });
+    write_rpc_message(stdin, &elicit_resp).await?;
+
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["id"], 3);
+    assert_eq!(call_resp["error"]["code"], -32000);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+
+    child.kill().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inference_cost_confirmation_without_elicitation() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let mut child = spawn_server_with(&mock_server, &["--confirm-token-threshold", "100"], "warn");
+    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.as_mut().expect("Failed to open stdout"));
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/infer"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "success" })))
+        .mount(&mock_server)
+        .await;
+
+    // Missing model without elicitation support keeps the invalid-params error
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": "run_inference", "inputs": { "prompt": "hi" } },
+        "id": 1
+    });
+    write_rpc_message(stdin, &call_req).await?;
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["error"]["code"], -32602);
+
+    // An expensive call is refused, and the caller cannot confirm it on the user's behalf
+    let inputs = json!({ "model": "big", "prompt": "hi", "params": { "max_tokens": 4096 } });
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": "run_inference", "inputs": inputs },
+        "id": 2
+    });
+    write_rpc_message(stdin, &call_req).await?;
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["error"]["code"], -32602);
+
+    let mut confirmed = inputs.clone();
+    confirmed["confirm"] = json!(true);
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": "run_inference", "inputs": confirmed },
+        "id": 3
+    });
+    write_rpc_message(stdin, &call_req).await?;
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["error"]["code"], -32602);
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+
+    child.kill().await?;
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_data_file_is_scoped_to_client_roots() -> Result<()> {
+    let base = std::env::temp_dir().join(format!("aionr2-roots-{}", uuid::Uuid::new_v4()));
+    let inside = base.join("inside");
+    let outside = base.join("outside");
+    std::fs::create_dir_all(&inside)?;
+    std::fs::create_dir_all(&outside)?;
+    std::fs::write(inside.join("data.json"), "[1, 2, 3]")?;
+    std::fs::write(outside.join("secret.json"), "[42]")?;
+    std::os::unix::fs::symlink(outside.join("secret.json"), inside.join("link.json"))?;
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/analyze"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "completed" })))
+        .mount(&mock_server)
+        .await;
+
+    let mut child = spawn_server(&mock_server).await;
+    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.as_mut().expect("Failed to open stdout"));
+
+    let init_req = json!({
+        "jsonrpc": "2.0",
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": { "roots": { "listChanged": true } }
+        },
+        "id": 1
+    });
+    write_rpc_message(stdin, &init_req).await?;
+    read_rpc_message(&mut stdout).await?.unwrap();
+
+    let call = |id: u64, file: &std::path::Path| {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "tools/call",
+            "params": {
+                "name": "data_analysis",
+                "inputs": {
+                    "data_file": file.to_string_lossy(),
+                    "ops": ["mean"],
+                    "mode": "remote"
+                }
+            },
+            "id": id
+        })
+    };
+    let roots_resp = |id: &Value, dir: &std::path::Path| {
+        json!({
+            "jsonrpc": "2.0",
+            "result": { "roots": [{ "uri": format!("file://{}", dir.display()), "name": "data" }] },
+            "id": id
+        })
+    };
+
+    // A file inside the root is read and forwarded to the backend
+    write_rpc_message(stdin, &call(2, &inside.join("data.json"))).await?;
+    let roots_req = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(roots_req["method"], "roots/list");
+    write_rpc_message(stdin, &roots_resp(&roots_req["id"], &inside)).await?;
+    let resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(resp["id"], 2);
+    assert_eq!(resp["result"]["status"], "completed");
+
+    // Files outside the root, directly or through a symlink, are rejected
+    write_rpc_message(stdin, &call(3, &outside.join("secret.json"))).await?;
+    let resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(resp["id"], 3);
+    assert_eq!(resp["error"]["code"], -32602);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("outside the client's roots"));
+
+    write_rpc_message(stdin, &call(4, &inside.join("link.json"))).await?;
+    let resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(resp["id"], 4);
+    assert_eq!(resp["error"]["code"], -32602);
+
+    // After the roots change, the new set is fetched and applied
+    let changed = json!({ "jsonrpc": "2.0", "method": "notifications/roots/list_changed" });
+    write_rpc_message(stdin, &changed).await?;
+    write_rpc_message(stdin, &call(5, &outside.join("secret.json"))).await?;
+    let roots_req = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(roots_req["method"], "roots/list");
+    write_rpc_message(stdin, &roots_resp(&roots_req["id"], &outside)).await?;
+    let resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(resp["id"], 5);
+    assert_eq!(resp["result"]["status"], "completed");
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let first: Value = serde_json::from_slice(&requests[0].body)?;
+    assert_eq!(first["data"], json!([1, 2, 3]));
+
+    child.kill().await?;
+    std::fs::remove_dir_all(&base)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_tools_list_changed_on_config_reload() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("aionr2-tools-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let tools_config = dir.join("tools.json");
+    std::fs::write(&tools_config, r#"{"disabled": []}"#)?;
+    let tools_arg = tools_config.to_string_lossy().to_string();
+
+    let mock_server = MockServer::start().await;
+    let mut child = spawn_server_with(&mock_server, &["--tools-config", &tools_arg], "warn");
+    let pid = child.id().expect("Server has exited");
+    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+    let mut stdout = BufReader::new(child.stdout.as_mut().expect("Failed to open stdout"));
+
+    let init_req = json!({
+        "jsonrpc": "2.0",
+        "method": "initialize",
+        "params": { "protocolVersion": "2024-11-05" },
+        "id": 1
+    });
+    write_rpc_message(stdin, &init_req).await?;
+    let init_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(
+        init_resp["result"]["capabilities"]["tools"]["listChanged"],
+        true
+    );
+
+    // Disable a tool and ask the server to reload its config
+    std::fs::write(&tools_config, r#"{"disabled": ["data_analysis"]}"#)?;
+    let status = Command::new("kill")
+        .args(["-HUP", &pid.to_string()])
+        .status()
+        .await?;
+    assert!(status.success());
+
+    let notification = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(notification["method"], "notifications/tools/list_changed");
+    assert!(notification.get("id").is_none());
+
+    let list_req = json!({ "jsonrpc": "2.0", "method": "tools/list", "id": 2 });
+    write_rpc_message(stdin, &list_req).await?;
+    let list_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    let tools = list_resp["result"]["tools"].as_array().unwrap();
+    assert_eq!(tools.len(), 4);
+    assert!(tools.iter().all(|t| t["name"] != "data_analysis"));
+
+    let call_req = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": "data_analysis", "inputs": { "data": [1], "ops": [] } },
+        "id": 3
+    });
+    write_rpc_message(stdin, &call_req).await?;
+    let call_resp = read_rpc_message(&mut stdout).await?.unwrap();
+    assert_eq!(call_resp["error"]["code"], -32601);
+
+    child.kill().await?;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
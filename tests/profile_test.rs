@@ -0,0 +1,163 @@
+// tests/profile_test.rs
+
+mod common;
+
+use anyhow::Result;
+use common::TestClient;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_profile_data_describes_columns() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+
+    let resp = client
+        .call_tool(
+            "profile_data",
+            json!({
+                "data": "id,region,units,price,shipped,at\n\
+                         1,north,10,2.5,true,2024-01-01T09:00:00Z\n\
+                         2,south,4,10,false,2024-01-03\n\
+                         3,north,,10,true,2024-01-02 12:30\n\
+                         4,east,8,2.5,,\n",
+                "examples": 2
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["rows"], 4, "{}", resp);
+    assert_eq!(result["column_count"], 6);
+    let columns = result["columns"].as_array().unwrap();
+    let names: Vec<_> = columns
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["id", "region", "units", "price", "shipped", "at"]);
+
+    assert_eq!(
+        columns[0],
+        json!({
+            "name": "id", "type": "integer", "nulls": 0, "null_rate": 0.0,
+            "distinct": 4, "unique": true, "min": 1, "max": 4, "mean": 2.5,
+            "examples": [1, 2]
+        })
+    );
+    assert_eq!(
+        columns[1],
+        json!({
+            "name": "region", "type": "text", "nulls": 0, "null_rate": 0.0,
+            "distinct": 3, "unique": false, "min_length": 4, "max_length": 5,
+            "examples": ["north", "south"]
+        })
+    );
+    assert_eq!(columns[2]["type"], "integer");
+    assert_eq!(columns[2]["nulls"], 1);
+    assert_eq!(columns[2]["null_rate"], 0.25);
+    assert_eq!(columns[2]["min"], 4);
+    assert_eq!(columns[2]["max"], 10);
+    assert_eq!(columns[3]["type"], "number");
+    assert_eq!(columns[3]["distinct"], 2);
+    assert_eq!(columns[3]["min"], 2.5);
+    assert_eq!(columns[4]["type"], "boolean");
+    assert_eq!(columns[4]["examples"], json!([true, false]));
+    assert_eq!(columns[5]["type"], "timestamp");
+    assert_eq!(columns[5]["min"], "2024-01-01T09:00:00Z");
+    assert_eq!(columns[5]["max"], "2024-01-03T00:00:00Z");
+
+    let summary = result["content"][0]["text"].as_str().unwrap();
+    assert!(
+        summary.starts_with(
+            "4 rows, 6 columns\n\n| column | type | nulls | distinct | range | examples |\n\
+             | --- | --- | --- | --- | --- | --- |\n\
+             | id | integer | 0 (0.0%) | 4 | 1 – 4 | 1, 2 |\n\
+             | region | text | 0 (0.0%) | 3 | 4 – 5 chars | north, south |\n"
+        ),
+        "{}",
+        summary
+    );
+    assert!(summary.contains("| units | integer | 1 (25.0%) | 3 | 4 – 10 | 10, 4 |\n"));
+
+    // Other shapes are profiled too, and non-tabular data is rejected
+    let resp = client
+        .call_tool(
+            "profile_data",
+            json!({ "data": { "tags": [["a"], null], "mixed": [1, "x"] } }),
+        )
+        .await?;
+    let columns = resp["result"]["columns"].as_array().unwrap();
+    assert_eq!(columns[0]["name"], "mixed");
+    assert_eq!(columns[0]["type"], "mixed");
+    assert_eq!(columns[1]["type"], "nested");
+    assert!(columns[1].get("min").is_none());
+
+    let resp = client
+        .call_tool("profile_data", json!({ "data": { "a": 1 } }))
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    assert!(resp["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid parameters for method 'profile_data': The data is not tabular"));
+
+    let resp = client
+        .call_tool("profile_data", json!({ "data": [1, 2], "examples": 50 }))
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_profile_data_edge_cases() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+
+    let resp = client
+        .call_tool("profile_data", json!({ "data": { "v": [], "w": [] } }))
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(result["rows"], 0, "{}", resp);
+    assert_eq!(
+        result["columns"][0],
+        json!({
+            "name": "v", "type": "empty", "nulls": 0, "null_rate": 0.0,
+            "distinct": 0, "unique": false, "examples": []
+        })
+    );
+    assert!(result["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("0 rows, 2 columns\n"));
+
+    // An all-null column has no range, and no examples are taken when asked for none
+    let resp = client
+        .call_tool(
+            "profile_data",
+            json!({ "data": { "v": [null, null], "w": [1, 1] }, "examples": 0 }),
+        )
+        .await?;
+    let columns = resp["result"]["columns"].as_array().unwrap();
+    assert_eq!(columns[0]["type"], "empty", "{}", resp);
+    assert_eq!(columns[0]["nulls"], 2);
+    assert_eq!(columns[0]["null_rate"], 1.0);
+    assert!(columns[0].get("min").is_none());
+    assert_eq!(columns[1]["distinct"], 1);
+    assert_eq!(columns[1]["unique"], false);
+    assert_eq!(columns[1]["examples"], json!([]));
+    assert!(resp["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("| v | empty | 2 (100.0%) | 0 |  |  |\n"));
+
+    for examples in [json!(21), json!(-1), json!("3")] {
+        let resp = client
+            .call_tool(
+                "profile_data",
+                json!({ "data": [1, 2], "examples": examples }),
+            )
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        assert!(resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("'examples' must be an integer from 0 to 20"));
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+// tests/query_test.rs
+
+mod common;
+
+use anyhow::Result;
+use common::TestClient;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_query_data_over_several_datasets() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+
+    let resp = client
+        .call_tool(
+            "query_data",
+            json!({
+                "data": [
+                    { "region": "north", "units": 10, "price": 2.5 },
+                    { "region": "south", "units": 4, "price": 10.0 },
+                    { "region": "north", "units": 6, "price": 10.0 },
+                    { "region": "east", "units": null, "price": 2.5 }
+                ],
+                "datasets": {
+                    "targets": { "data": "region,target\nnorth,12\nsouth,5\n" }
+                },
+                "sql": "SELECT d.region, SUM(d.units) AS units, t.target, SUM(d.units) >= t.target AS met \
+                        FROM data d JOIN targets t USING (region) GROUP BY d.region ORDER BY d.region"
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(
+        result["columns"],
+        json!(["region", "units", "target", "met"]),
+        "{}",
+        resp
+    );
+    assert_eq!(
+        result["rows"],
+        json!([
+            { "region": "north", "units": 16, "target": 12, "met": 1 },
+            { "region": "south", "units": 4, "target": 5, "met": 0 }
+        ])
+    );
+    assert_eq!(result["row_count"], 2);
+    assert_eq!(result["truncated"], false);
+    assert_eq!(result["content"][0]["type"], "text");
+    assert_eq!(
+        result["content"][0]["text"],
+        "| region | units | target | met |\n| --- | --- | --- | --- |\n| north | 16 | 12 | 1 |\n| south | 4 | 5 | 0 |\n"
+    );
+
+    let resp = client
+        .call_tool(
+            "query_data",
+            json!({
+                "data": [1, 2, 3, 4],
+                "sql": "SELECT value, value * 1.5 AS scaled, NULL AS note FROM data",
+                "max_rows": 2
+            }),
+        )
+        .await?;
+    let result = &resp["result"];
+    assert_eq!(
+        result["rows"],
+        json!([
+            { "value": 1, "scaled": 1.5, "note": null },
+            { "value": 2, "scaled": 3.0, "note": null }
+        ]),
+        "{}",
+        resp
+    );
+    assert_eq!(result["truncated"], true);
+    assert_eq!(
+        result["content"][0]["text"],
+        "| value | scaled | note |\n| --- | --- | --- |\n| 1 | 1.5 |  |\n| 2 | 3.0 |  |\n"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_data_is_read_only() -> Result<()> {
+    let mut client = TestClient::start(&[]).await?;
+
+    for (sql, message) in [
+        ("DELETE FROM data", "Only read-only statements are allowed"),
+        (
+            "INSERT INTO data VALUES (2)",
+            "Only read-only statements are allowed",
+        ),
+        (
+            "UPDATE data SET a = 2",
+            "Only read-only statements are allowed",
+        ),
+        (
+            "CREATE TABLE other (b)",
+            "Only read-only statements are allowed",
+        ),
+        ("SELECT 1; DROP TABLE data", "Multiple statements provided"),
+        ("ATTACH DATABASE '/tmp/other.db' AS other", "not authorized"),
+        ("PRAGMA query_only = 0", "not authorized"),
+        ("SELECT missing FROM data", "no such column: missing"),
+    ] {
+        let resp = client
+            .call_tool("query_data", json!({ "data": [{ "a": 1 }], "sql": sql }))
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}: {}", sql, resp);
+        let text = resp["error"]["message"].as_str().unwrap();
+        assert!(
+            text.contains("query_data") && text.contains(message),
+            "{}: {}",
+            sql,
+            text
+        );
+    }
+
+    let resp = client
+        .call_tool(
+            "query_data",
+            json!({
+                "datasets": { "empty": {} },
+                "sql": "SELECT 1"
+            }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    assert!(
+        resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("table 'empty': Missing 'data' field"),
+        "{}",
+        resp
+    );
+
+    for data in [json!([]), json!([{}])] {
+        let resp = client
+            .call_tool(
+                "query_data",
+                json!({ "datasets": { "blank": { "data": data } }, "sql": "SELECT 1" }),
+            )
+            .await?;
+        assert_eq!(resp["error"]["code"], -32602, "{}", resp);
+        assert!(
+            resp["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("table 'blank' has no columns"),
+            "{}",
+            resp
+        );
+    }
+
+    let resp = client
+        .call_tool("query_data", json!({ "sql": "SELECT 1" }))
+        .await?;
+    assert_eq!(resp["error"]["code"], -32602);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_data_time_limit() -> Result<()> {
+    let mut client = TestClient::start(&["--query-timeout", "1"]).await?;
+
+    let started = std::time::Instant::now();
+    let resp = client
+        .call_tool(
+            "query_data",
+            json!({
+                "data": [{ "a": 1 }],
+                "sql": "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) \
+                        SELECT count(*) FROM n"
+            }),
+        )
+        .await?;
+    assert_eq!(resp["error"]["code"], -32000, "{}", resp);
+    assert!(
+        resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("ran longer than 1 seconds"),
+        "{}",
+        resp
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    let resp = client
+        .call_tool(
+            "query_data",
+            json!({ "data": [{ "a": 1 }], "sql": "SELECT a FROM data" }),
+        )
+        .await?;
+    assert_eq!(resp["result"]["rows"], json!([{ "a": 1 }]), "{}", resp);
+    Ok(())
+}